@@ -1,26 +1,47 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
 use anchor_lang::system_program;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
 
 declare_id!("Aa3NmVN4aHAbRRoR2kQm9xnUonkydrh96tcAa9riJwRP");
 
-/// Sipzy Vault Program - Dual Token Bonding Curve System
-/// 
-/// Implements two types of bonding curves:
-/// 1. $CREATOR Coin (Linear): Price = slope × supply + base_price
-///    - Long-term "equity" in a creator's career
-///    - Steady, predictable price growth
-/// 
-/// 2. $STREAM Coin (Exponential): Price = base_price × (1 + growth_rate)^supply
-///    - Event-based "hype" for livestreams/videos
-///    - Starts cheap, moons rapidly
+// Sipzy Vault Program - Dual Token Bonding Curve System
+//
+// Implements two types of bonding curves:
+// 1. $CREATOR Coin (Linear): Price = slope × supply + base_price
+//    - Long-term "equity" in a creator's career
+//    - Steady, predictable price growth
+//
+// 2. $STREAM Coin (Exponential): Price = base_price × (1 + growth_rate)^supply
+//    - Event-based "hype" for livestreams/videos
+//    - Starts cheap, moons rapidly
 
 // ============================================================================
 // CONSTANTS
 // ============================================================================
 
-/// Fee in basis points (100 = 1%)
+/// Default fee in basis points (100 = 1%)
 const FEE_BASIS_POINTS: u64 = 100;
 
+/// Highest fee a creator can configure (10%)
+const MAX_FEE_BPS: u64 = 1000;
+
+/// Highest buy tax a creator can configure (10%)
+const MAX_BUY_TAX_BPS: u16 = 1000;
+
+/// Highest share of each sell a creator can have burned (50%)
+const MAX_BURN_ON_SELL_BPS: u16 = 5000;
+
+/// Highest extra launch-window fee for anti-sniping (50%)
+const MAX_SNIPE_FEE_BPS: u64 = 5000;
+
+/// Highest extra sell fee for a just-bought position (50%)
+const MAX_SELL_PENALTY_BPS: u64 = 5000;
+
 /// Default base price for Creator coins: 0.01 SOL
 const DEFAULT_CREATOR_BASE_PRICE: u64 = 10_000_000;
 
@@ -33,8 +54,106 @@ const DEFAULT_STREAM_BASE_PRICE: u64 = 1_000_000;
 /// Default growth rate for Stream coins: 5% (500 basis points)
 const DEFAULT_STREAM_GROWTH_RATE: u64 = 500;
 
-/// Fixed-point precision for exponential calculations (10^9)
-const EXP_PRECISION: u128 = 1_000_000_000;
+/// Highest Stream growth rate: 100% per token (10000 bps)
+/// Also the ceiling of the "growth-rate-shaped" range a nonzero Creator slope
+/// must clear, so a bps value passed as a slope is rejected instead of
+/// launching a mispriced coin
+const MAX_STREAM_GROWTH_BPS: u64 = 10_000;
+
+/// Default fixed-point precision exponent for exponential calculations (10^9)
+const DEFAULT_EXP_PRECISION: u8 = 9;
+
+/// Maximum token decimals (keeps unit² within u128 in the curve math)
+const MAX_DECIMALS: u8 = 9;
+
+/// Maximum pools tracked in a single creator registry
+const MAX_REGISTRY_POOLS: usize = 64;
+
+/// Largest exponential integral computed by per-token summation
+const MAX_SUMMATION_TERMS: u64 = 100;
+
+/// Cap on amount × bits(end_supply) for per-token exponential summation
+/// Every summed price is an exp_power call doing up to two u128 multiply-divides
+/// per bit of the supply, estimated at ~250 CU per bit on SBF, so summation
+/// stays near 400k CU. Uncapped, 100 tokens at a 64-bit supply would cost ~1.6M
+/// CU, past the 1.4M transaction limit; the chunked closed form does a fixed
+/// handful of u128 multiply-divides per 32-token chunk at any supply.
+const MAX_SUMMATION_WORK: u64 = 1_600;
+
+/// Entries kept in a pool's price snapshot ring buffer (a day of 15-min candles)
+const MAX_SNAPSHOTS: usize = 96;
+
+/// Longest single trading pause a creator can set (1 day)
+const MAX_PAUSE_SECS: i64 = 86_400;
+
+/// Slippage tolerance buy_tokens_auto_slippage applies until the creator sets one (1%)
+const DEFAULT_SLIPPAGE_BPS: u16 = 100;
+
+/// Highest default slippage a creator can set (50%)
+const MAX_SLIPPAGE_BPS: u16 = 5000;
+
+/// Longest per-wallet wait between buys a creator can set (1 hour)
+const MAX_BUY_COOLDOWN_SECS: i64 = 3_600;
+
+/// Layout version written as the first byte of get_pool_packed
+const POOL_PACKED_VERSION: u8 = 1;
+
+/// Length of a version-1 get_pool_packed blob
+const POOL_PACKED_LEN: usize = 111;
+
+/// Holders tracked on a pool's leaderboard
+const MAX_TOP_HOLDERS: usize = 10;
+
+/// Rank get_holder_rank returns for a balance outside the tracked top holders
+const UNRANKED: u32 = u32::MAX;
+
+/// Base units either side of the closed-form estimate searched by buy_exact_cost
+const EXACT_COST_SLACK: u64 = 16;
+
+/// Tokens per chunk when pricing large exponential ranges
+/// A 10,000-token buy walks 313 chunks; the large-buy test asserts it stays
+/// under the 1.4M CU transaction limit
+const EXP_CHUNK_TERMS: u64 = 32;
+
+/// Maximum metadata URI length; pools only pay rent for the bytes they use
+const MAX_METADATA_URI_LEN: usize = 200;
+
+/// Entries in a pool's loyalty fee-discount tier table
+const MAX_LOYALTY_TIERS: usize = 3;
+
+/// Largest factor a single buy may move a Stream pool's spot price by
+const MAX_PRICE_MULTIPLE: u64 = 100;
+
+/// Most pools a single sell_basket may touch
+const MAX_BASKET_SIZE: usize = 8;
+
+/// Longest whitelist merkle proof accepted (enough for 2^20 wallets)
+const MAX_MERKLE_PROOF_LEN: usize = 20;
+
+/// Most pools a single set_active_batch may touch
+const MAX_ACTIVE_BATCH_SIZE: usize = 16;
+
+/// Most signers a multisig may hold (approvals are tracked as a u8 bitmap)
+const MAX_MULTISIG_SIGNERS: usize = 5;
+
+/// Longest instruction data an Invoke proposal may carry (fits a metadata URI update)
+const MAX_PROPOSAL_DATA_LEN: usize = 256;
+
+/// Most accounts an Invoke proposal may pass (fits close_pool with every optional account)
+const MAX_PROPOSAL_ACCOUNTS: usize = 10;
+
+/// Client order IDs remembered per position for duplicate detection
+const RECENT_ORDER_IDS: usize = 8;
+
+/// Longest claim window a creator can give a holder airdrop (30 days)
+const MAX_AIRDROP_CLAIM_WINDOW_SECS: i64 = 30 * 86_400;
+
+/// Prefix of the oracle-signed message binding a pool identifier to its creator
+const OWNERSHIP_ATTESTATION_PREFIX: &[u8] = b"sipzy:owner:";
+
+/// Share of the reserve (bps) a single curve-priced sell may take before a
+/// reserve-backed pool prices it pro-rata instead
+const RESERVE_BACKED_SELL_BPS: u64 = 5000;
 
 // ============================================================================
 // PROGRAM
@@ -45,7 +164,8 @@ pub mod sipzy_vault {
     use super::*;
 
     /// Initialize a Creator Pool (Linear Bonding Curve)
-    /// Creates a PDA tied to the YouTube channel ID
+    /// Creates a PDA tied to the YouTube channel ID and generation; a channel
+    /// whose pool was closed is relaunched under the next generation
     /// Price formula: Price(n) = slope × n + base_price
     pub fn initialize_creator_pool(
         ctx: Context<InitializeCreatorPool>,
@@ -54,39 +174,92 @@ pub mod sipzy_vault {
         metadata_uri: String,
         base_price: Option<u64>,
         slope: Option<u64>,
+        generation: u32,
     ) -> Result<()> {
-        require!(channel_id.len() <= 32, SipzyError::IdentifierTooLong);
-        require!(channel_name.len() <= 64, SipzyError::NameTooLong);
-        require!(metadata_uri.len() <= 200, SipzyError::MetadataUriTooLong);
-        
-        let pool = &mut ctx.accounts.pool;
-        let clock = Clock::get()?;
-        
-        pool.pool_type = PoolType::Creator;
-        pool.identifier = channel_id;
-        pool.display_name = channel_name;
-        pool.parent_identifier = String::new(); // No parent for creator pools
-        pool.creator_wallet = ctx.accounts.creator_wallet.key();
-        pool.authority = ctx.accounts.authority.key();
-        pool.total_supply = 0;
-        pool.reserve_sol = 0;
-        pool.base_price = base_price.unwrap_or(DEFAULT_CREATOR_BASE_PRICE);
-        pool.curve_param = slope.unwrap_or(DEFAULT_CREATOR_SLOPE); // slope for linear
-        pool.metadata_uri = metadata_uri;
-        pool.bump = ctx.bumps.pool;
-        pool.created_at = clock.unix_timestamp;
-        pool.is_active = true;
+        create_creator_pool(
+            &mut ctx.accounts.pool,
+            &mut ctx.accounts.registry,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            ctx.accounts.global_stats.as_deref_mut().map(|stats| &mut **stats),
+            CreatorPoolInit {
+                channel_id,
+                channel_name,
+                metadata_uri,
+                base_price,
+                slope,
+                generation,
+                creator_wallet: ctx.accounts.creator_wallet.key(),
+                authority: ctx.accounts.authority.key(),
+                pool_bump: ctx.bumps.pool,
+                registry_bump: ctx.bumps.registry,
+            },
+        )
+    }
+
+    /// Initialize a Creator Pool and make the creator's first buy in one transaction
+    /// The creator wallet signs and pays for the buy, so it is always the first
+    /// holder and no one can buy between init and the creator's purchase.
+    /// Fails with SlippageExceeded if the buy costs more than `max_sol_cost`
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_creator_pool_and_buy(
+        ctx: Context<InitializeCreatorPoolAndBuy>,
+        channel_id: String,
+        channel_name: String,
+        metadata_uri: String,
+        base_price: Option<u64>,
+        slope: Option<u64>,
+        generation: u32,
+        buy_amount: u64,
+        max_sol_cost: u64,
+    ) -> Result<()> {
+        let accounts = &mut *ctx.accounts;
+        create_creator_pool(
+            &mut accounts.pool,
+            &mut accounts.registry,
+            &accounts.payer,
+            &accounts.system_program,
+            accounts.global_stats.as_deref_mut().map(|stats| &mut **stats),
+            CreatorPoolInit {
+                channel_id,
+                channel_name,
+                metadata_uri,
+                base_price,
+                slope,
+                generation,
+                creator_wallet: accounts.creator_wallet.key(),
+                authority: accounts.authority.key(),
+                pool_bump: ctx.bumps.pool,
+                registry_bump: ctx.bumps.registry,
+            },
+        )?;
         
-        emit!(PoolCreated {
-            pool: pool.key(),
-            pool_type: PoolType::Creator,
-            identifier: pool.identifier.clone(),
-            creator_wallet: pool.creator_wallet,
-            base_price: pool.base_price,
-            curve_param: pool.curve_param,
-        });
+        let quote = quote_buy(&accounts.pool, buy_amount)?;
+        require!(quote.total_cost <= max_sol_cost, SipzyError::SlippageExceeded);
         
-        Ok(())
+        let creator_wallet = accounts.creator_wallet.to_account_info();
+        let mut insurance_fund = load_insurance_fund(&accounts.insurance_fund)?;
+        execute_buy(
+            BuyAccounts {
+                pool: &mut accounts.pool,
+                position: &mut accounts.position,
+                trader: creator_wallet.clone(),
+                creator_wallet,
+                reserve_vault: None,
+                quote_accounts: None,
+                system_program: accounts.system_program.to_account_info(),
+                trackers: TradeTrackers {
+                    insurance_fund: insurance_fund.as_mut(),
+                    global_stats: accounts.global_stats.as_deref_mut().map(|stats| &mut **stats),
+                    ..TradeTrackers::default()
+                },
+            },
+            ctx.bumps.position,
+            buy_amount,
+            buy_amount,
+            quote,
+        )?;
+        save_insurance_fund(insurance_fund)
     }
 
     /// Initialize a Stream Pool (Exponential Bonding Curve)
@@ -104,7 +277,7 @@ pub mod sipzy_vault {
         require!(video_id.len() <= 32, SipzyError::IdentifierTooLong);
         require!(channel_id.len() <= 32, SipzyError::IdentifierTooLong);
         require!(video_title.len() <= 64, SipzyError::NameTooLong);
-        require!(metadata_uri.len() <= 200, SipzyError::MetadataUriTooLong);
+        require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, SipzyError::MetadataUriTooLong);
         
         let pool = &mut ctx.accounts.pool;
         let clock = Clock::get()?;
@@ -119,18 +292,105 @@ pub mod sipzy_vault {
         pool.reserve_sol = 0;
         pool.base_price = base_price.unwrap_or(DEFAULT_STREAM_BASE_PRICE);
         pool.curve_param = growth_rate.unwrap_or(DEFAULT_STREAM_GROWTH_RATE); // growth rate for exponential
+        check_curve_param(PoolType::Stream, pool.curve_param)?;
         pool.metadata_uri = metadata_uri;
         pool.bump = ctx.bumps.pool;
         pool.created_at = clock.unix_timestamp;
         pool.is_active = true;
+        pool.reserve_cap = 0;
+        pool.decimals = 0;
+        pool.tick_size = 0;
+        pool.founder_minted = false;
+        pool.reserve_debt = 0;
+        pool.total_buyback_sol = 0;
+        pool.total_buy_volume = 0;
+        pool.total_sell_volume = 0;
+        pool.lifetime_fees = 0;
+        pool.same_slot_sell_block = false;
+        pool.pending_creator_wallet = Pubkey::default();
+        pool.founder_allocation = 0;
+        pool.founder_vest_start = 0;
+        pool.founder_vest_duration = 0;
+        pool.founder_vested_sold = 0;
+        pool.loyalty_tiers = [LoyaltyTier::default(); MAX_LOYALTY_TIERS];
+        pool.graduation_threshold = 0;
+        pool.is_graduated = false;
+        // Inherit the parent channel's economics when its Creator pool is passed
+        pool.fee_bps = ctx.accounts.parent_pool
+            .as_ref()
+            .map_or(FEE_BASIS_POINTS, |parent| parent.fee_bps);
+        pool.snipe_fee_bps = 0;
+        pool.snipe_window_secs = 0;
+        pool.launch_end = 0;
+        pool.compliance_enabled = false;
+        pool.reserve_backed_sells = false;
+        pool.generation = 0;
+        pool.expires_at = 0;
+        pool.max_supply = 0;
+        pool.net_deposited = 0;
+        pool.verified = false;
+        pool.precision_exp = DEFAULT_EXP_PRECISION;
+        pool.pause_until = 0;
+        pool.buy_tax_bps = 0;
+        pool.max_sell_penalty_bps = 0;
+        pool.penalty_window_secs = 0;
+        pool.emergency_withdrawn = 0;
+        pool.max_notional_per_window = 0;
+        pool.window_secs = 0;
+        pool.window_notional = 0;
+        pool.window_start = 0;
+        pool.burn_on_sell_bps = 0;
+        pool.burned_supply = 0;
+        pool.metadata_format = MetadataFormat::Raw as u8;
+        pool.min_reserve_ratio_bps = 0;
+        pool.price_ceiling = 0;
+        pool.fee_free_threshold = 0;
+        pool.seq = 0;
+        pool.buy_cooldown_secs = 0;
+        pool.reserve_vault_enabled = false;
+        pool.reserve_vault_bump = 0;
+        pool.whitelist_until = 0;
+        pool.sells_open_from = 0;
+        pool.sells_open_until = 0;
+        pool.quote_mint = None;
+        pool.quote_reserve_bump = 0;
+        pool.emit_events = true;
+        pool.holder_snapshot_count = 0;
+        pool.airdrop_claim_until = 0;
+        pool.default_slippage_bps = DEFAULT_SLIPPAGE_BPS;
+        pool.metadata_frozen = false;
+        pool.max_price_impact_bps = 0;
+        pool.fee_ceiling_bps = MAX_FEE_BPS;
+        
+        register_pool(
+            &mut ctx.accounts.registry,
+            pool.creator_wallet,
+            pool.key(),
+            Some(pool.parent_identifier.clone()),
+            ctx.bumps.registry,
+        );
+        if let Some(stats) = ctx.accounts.global_stats.as_deref_mut() {
+            record_pool_created(stats, PoolType::Stream);
+        }
+        
+        // Pools are created with no metadata capacity; grow to fit the URI
+        resize_pool_for_metadata(
+            &pool.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            pool.metadata_uri.len(),
+        )?;
         
         emit!(PoolCreated {
             pool: pool.key(),
+            seq: next_seq(pool),
             pool_type: PoolType::Stream,
             identifier: pool.identifier.clone(),
             creator_wallet: pool.creator_wallet,
             base_price: pool.base_price,
             curve_param: pool.curve_param,
+            bump: pool.bump,
+            generation: pool.generation,
         });
         
         Ok(())
@@ -138,141 +398,242 @@ pub mod sipzy_vault {
 
     /// Buy tokens from any pool type
     /// Calculates cost via integral based on pool_type
-    /// Deducts 1% fee to creator_wallet
-    pub fn buy_tokens(ctx: Context<Trade>, amount: u64) -> Result<()> {
+    /// Deducts the pool fee (fee_bps) to creator_wallet and credits the trader's HolderPosition
+    /// A nonzero `client_order_id` is rejected if the position has seen it
+    /// recently, so a resubmitted transaction can't buy twice
+    pub fn buy_tokens(ctx: Context<Trade>, amount: u64, client_order_id: u64) -> Result<()> {
+        let quote = quote_buy(&ctx.accounts.pool, amount)?;
+        record_order_id(&mut ctx.accounts.position, client_order_id)?;
+        process_buy(ctx.accounts, ctx.bumps.position, amount, amount, quote)
+    }
+
+    /// Buy up to `amount` tokens for at most `max_sol_cost` lamports (fee included)
+    /// Without `allow_partial` the buy fills in full or fails: MaxSupplyExceeded
+    /// past the supply cap, SlippageExceeded over budget. With it, the buy is
+    /// trimmed to the cap and then to the largest amount the budget covers, and
+    /// is reported as a PartialBuy when less than `amount` fills
+    pub fn buy_with_limit(
+        ctx: Context<Trade>,
+        amount: u64,
+        max_sol_cost: u64,
+        allow_partial: bool,
+        client_order_id: u64,
+    ) -> Result<BuyResult> {
         require!(amount > 0, SipzyError::InvalidAmount);
-        require!(ctx.accounts.pool.is_active, SipzyError::PoolInactive);
         
         let pool = &ctx.accounts.pool;
-        let start_supply = pool.total_supply;
-        let end_supply = start_supply.checked_add(amount).ok_or(SipzyError::Overflow)?;
+        let mut fill = amount;
+        if !within_max_supply(pool, pool.total_supply.saturating_add(amount)) {
+            require!(allow_partial, SipzyError::MaxSupplyExceeded);
+            fill = pool.max_supply.saturating_sub(pool.total_supply);
+            require!(fill > 0, SipzyError::MaxSupplyExceeded);
+        }
         
-        // Calculate total cost based on pool type
-        let total_cost = match pool.pool_type {
-            PoolType::Creator => calculate_linear_integral(
-                start_supply,
-                end_supply,
-                pool.base_price,
-                pool.curve_param,
-            )?,
-            PoolType::Stream => calculate_exponential_integral(
-                start_supply,
-                end_supply,
-                pool.base_price,
-                pool.curve_param,
-            )?,
-        };
+        let mut quote = quote_buy(pool, fill)?;
+        if quote.total_cost > max_sol_cost {
+            require!(allow_partial, SipzyError::SlippageExceeded);
+            
+            // Cost grows with amount, so the largest affordable fill is well defined
+            let mut low = 0;
+            let mut high = fill - 1;
+            while low < high {
+                let mid = low + (high - low).div_ceil(2);
+                if quote_buy(pool, mid)?.total_cost <= max_sol_cost {
+                    low = mid;
+                } else {
+                    high = mid - 1;
+                }
+            }
+            require!(low > 0, SipzyError::SlippageExceeded);
+            fill = low;
+            quote = quote_buy(pool, fill)?;
+        }
         
-        // Calculate 1% creator fee
-        let (creator_fee, pool_deposit) = calculate_fee(total_cost)?;
+        record_order_id(&mut ctx.accounts.position, client_order_id)?;
+        let sol_spent = quote.total_cost;
+        process_buy(ctx.accounts, ctx.bumps.position, fill, amount, quote)?;
         
-        // Transfer SOL to pool (99%)
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.trader.to_account_info(),
-                    to: ctx.accounts.pool.to_account_info(),
-                },
-            ),
-            pool_deposit,
-        )?;
+        Ok(BuyResult {
+            tokens_bought: fill,
+            sol_spent,
+        })
+    }
+
+    /// Spend as much of `exact_cost` as possible without exceeding it
+    /// Buys the largest amount (at most `max_tokens`, capped by max_supply) whose
+    /// total cost fits the budget: linear pools start from the closed-form
+    /// inverse of the curve integral, exponential pools binary-search it
+    pub fn buy_exact_cost(
+        ctx: Context<Trade>,
+        exact_cost: u64,
+        max_tokens: u64,
+        client_order_id: u64,
+    ) -> Result<BuyResult> {
+        require!(exact_cost > 0 && max_tokens > 0, SipzyError::InvalidAmount);
         
-        // Transfer 1% fee to creator wallet
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.trader.to_account_info(),
-                    to: ctx.accounts.creator_wallet.to_account_info(),
-                },
-            ),
-            creator_fee,
-        )?;
+        let pool = &ctx.accounts.pool;
+        let mut max_amount = max_tokens;
+        if pool.max_supply > 0 {
+            max_amount = max_amount.min(pool.max_supply.saturating_sub(pool.total_supply));
+            require!(max_amount > 0, SipzyError::MaxSupplyExceeded);
+        }
         
-        // Update pool state
-        let pool = &mut ctx.accounts.pool;
-        pool.reserve_sol = pool.reserve_sol
-            .checked_add(pool_deposit)
-            .ok_or(SipzyError::Overflow)?;
-        pool.total_supply = end_supply;
+        let fill = max_affordable_amount(pool, exact_cost, max_amount)?;
+        require!(fill > 0, SipzyError::SlippageExceeded);
+        let quote = quote_buy(pool, fill)?;
         
-        emit!(TokensTraded {
-            pool: pool.key(),
-            trader: ctx.accounts.trader.key(),
-            trade_type: TradeType::Buy,
-            amount,
-            sol_amount: total_cost,
-            fee: creator_fee,
-            new_supply: pool.total_supply,
-            new_reserve: pool.reserve_sol,
-        });
+        record_order_id(&mut ctx.accounts.position, client_order_id)?;
+        let sol_spent = quote.total_cost;
+        process_buy(ctx.accounts, ctx.bumps.position, fill, max_tokens, quote)?;
         
-        Ok(())
+        Ok(BuyResult {
+            tokens_bought: fill,
+            sol_spent,
+        })
+    }
+
+    /// Buy `amount` tokens under the pool's default_slippage_bps instead of a
+    /// client-supplied limit
+    /// The quote is taken in the same transaction as the buy, so the limit is
+    /// set against the pre-trade spot price: amount at spot (plus buy tax) ×
+    /// (10000 + default_slippage_bps) / 10000, failing with SlippageExceeded
+    /// above it. That bounds the buy's own price impact but not a price already
+    /// moved by earlier transactions; clients that need that pass their own
+    /// max_sol_cost to buy_with_limit
+    pub fn buy_tokens_auto_slippage(
+        ctx: Context<Trade>,
+        amount: u64,
+        client_order_id: u64,
+    ) -> Result<BuyResult> {
+        let pool = &ctx.accounts.pool;
+        let quote = quote_buy(pool, amount)?;
+        require!(
+            quote.total_cost <= auto_slippage_limit(pool, amount)?,
+            SipzyError::SlippageExceeded
+        );
+        
+        record_order_id(&mut ctx.accounts.position, client_order_id)?;
+        let sol_spent = quote.total_cost;
+        process_buy(ctx.accounts, ctx.bumps.position, amount, amount, quote)?;
+        
+        Ok(BuyResult {
+            tokens_bought: amount,
+            sol_spent,
+        })
     }
 
     /// Sell tokens back to any pool type
-    /// Burns tokens and returns SOL from reserve
-    /// Deducts 1% fee to creator_wallet
-    pub fn sell_tokens(ctx: Context<Trade>, amount: u64) -> Result<()> {
-        require!(amount > 0, SipzyError::InvalidAmount);
-        require!(ctx.accounts.pool.is_active, SipzyError::PoolInactive);
+    /// Burns tokens from the trader's HolderPosition and returns SOL from reserve
+    /// Deducts the pool fee to creator_wallet
+    /// If a `recipient` account is passed, the net refund is paid to it instead of the trader
+    pub fn sell_tokens(ctx: Context<Trade>, amount: u64, client_order_id: u64) -> Result<()> {
+        record_order_id(&mut ctx.accounts.position, client_order_id)?;
+        process_sell(ctx.accounts, amount)?;
+        Ok(())
+    }
+
+    /// Sell just enough tokens to receive at least `sol_target` lamports (net of fee)
+    /// Binary-searches the smallest amount whose net refund reaches the target,
+    /// bounded by the trader's position, then sells it like sell_tokens
+    pub fn sell_for_sol(ctx: Context<Trade>, sol_target: u64) -> Result<SellResult> {
+        require!(sol_target > 0, SipzyError::InvalidAmount);
         
         let pool = &ctx.accounts.pool;
-        require!(pool.total_supply >= amount, SipzyError::InsufficientSupply);
+        let max_amount = ctx.accounts.position.balance.min(pool.total_supply);
+        require!(max_amount > 0, SipzyError::InsufficientBalanceForTarget);
         
-        let end_supply = pool.total_supply;
-        let start_supply = end_supply.checked_sub(amount).ok_or(SipzyError::Overflow)?;
+        let now = Clock::get()?.unix_timestamp;
+        let penalty_bps = sell_penalty_bps(pool, &ctx.accounts.position, now);
+        let discount_bps = loyalty_discount_bps(pool, &ctx.accounts.position, now);
+        let max_refund = calculate_sell_refund(pool, max_amount, penalty_bps, discount_bps)?.net_refund;
+        require!(max_refund >= sol_target, SipzyError::InsufficientBalanceForTarget);
         
-        // Calculate refund based on pool type (same formula as buy, in reverse)
-        let gross_refund = match pool.pool_type {
-            PoolType::Creator => calculate_linear_integral(
-                start_supply,
-                end_supply,
-                pool.base_price,
-                pool.curve_param,
-            )?,
-            PoolType::Stream => calculate_exponential_integral(
-                start_supply,
-                end_supply,
-                pool.base_price,
-                pool.curve_param,
-            )?,
-        };
+        // Net refund grows with amount, so the smallest sufficient amount is well defined
+        // (a reserve-backed pool switching to pro-rata pricing can break monotonicity;
+        // the search then still returns an amount that meets the target)
+        let mut low = 1;
+        let mut high = max_amount;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let net_refund = calculate_sell_refund(pool, mid, penalty_bps, discount_bps)?.net_refund;
+            if net_refund >= sol_target {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
         
-        // Calculate 1% fee on sell
-        let (creator_fee, net_refund) = calculate_fee(gross_refund)?;
+        let sol_received = process_sell(ctx.accounts, low)?;
         
-        require!(
-            pool.reserve_sol >= net_refund.checked_add(creator_fee).ok_or(SipzyError::Overflow)?,
-            SipzyError::InsufficientReserve
-        );
+        Ok(SellResult {
+            tokens_sold: low,
+            sol_received,
+        })
+    }
+
+    /// Redeem tokens of a graduated pool for a pro-rata share of its reserve
+    /// Pays reserve_sol × amount / total_supply with no curve math and no fee,
+    /// so every holder left after graduation exits at the same price. Locked
+    /// founder tokens and frozen positions can't redeem, as with sells
+    pub fn redeem_graduated(ctx: Context<Trade>, amount: u64) -> Result<()> {
+        let accounts = &mut *ctx.accounts;
+        let pool = &accounts.pool;
+        let position = &accounts.position;
+        require!(pool.is_graduated, SipzyError::PoolNotGraduated);
+        require!(amount > 0, SipzyError::InvalidAmount);
+        ensure_not_frozen(pool, position)?;
+        require!(position.balance >= amount, SipzyError::InsufficientBalance);
+        
+        let payout = u64::try_from(
+            pool.reserve_sol as u128 * amount as u128 / pool.total_supply as u128,
+        )
+        .map_err(|_| SipzyError::Overflow)?;
+        let founder_redeemed = if position.is_founder {
+            founder_tokens_in_sell(pool, position.balance, amount)?
+        } else {
+            0
+        };
         
-        // Transfer SOL from pool to seller (using lamport manipulation for PDA)
-        let pool_info = ctx.accounts.pool.to_account_info();
-        **pool_info.try_borrow_mut_lamports()? -= net_refund;
-        **ctx.accounts.trader.to_account_info().try_borrow_mut_lamports()? += net_refund;
+        let destination = match &accounts.recipient {
+            Some(recipient) => recipient.to_account_info(),
+            None => accounts.trader.to_account_info(),
+        };
+        pay_from_reserve(
+            &accounts.pool,
+            accounts.reserve_vault.as_ref(),
+            &accounts.system_program,
+            &destination,
+            payout,
+        )?;
         
-        // Transfer fee to creator
-        **pool_info.try_borrow_mut_lamports()? -= creator_fee;
-        **ctx.accounts.creator_wallet.to_account_info().try_borrow_mut_lamports()? += creator_fee;
+        let position = &mut accounts.position;
+        reduce_cost_basis(position, amount);
+        position.balance -= amount;
         
-        // Update pool state
-        let pool = &mut ctx.accounts.pool;
-        pool.reserve_sol = pool.reserve_sol
-            .checked_sub(net_refund)
-            .ok_or(SipzyError::Overflow)?
-            .checked_sub(creator_fee)
+        let pool = &mut accounts.pool;
+        pool.reserve_sol -= payout;
+        pool.net_deposited = pool.net_deposited
+            .checked_sub(payout)
+            .ok_or(SipzyError::ReserveAccountingMismatch)?;
+        check_reserve_accounting(pool)?;
+        pool.total_supply -= amount;
+        pool.founder_vested_sold = pool.founder_vested_sold
+            .checked_add(founder_redeemed)
             .ok_or(SipzyError::Overflow)?;
-        pool.total_supply = start_supply;
         
-        emit!(TokensTraded {
+        if let Some(top_holders) = accounts.top_holders.as_mut() {
+            update_top_holders(top_holders, position.owner, position.balance);
+        }
+        if let Some(stats) = accounts.global_stats.as_mut() {
+            record_reserve_change(stats, pool, pool.reserve_sol + payout);
+        }
+        
+        emit!(GraduatedRedemption {
             pool: pool.key(),
-            trader: ctx.accounts.trader.key(),
-            trade_type: TradeType::Sell,
+            seq: next_seq(pool),
+            holder: accounts.trader.key(),
             amount,
-            sol_amount: gross_refund,
-            fee: creator_fee,
+            sol_amount: payout,
             new_supply: pool.total_supply,
             new_reserve: pool.reserve_sol,
         });
@@ -280,452 +641,7003 @@ pub mod sipzy_vault {
         Ok(())
     }
 
+    /// Sell from several pools in one transaction
+    /// remaining_accounts holds one (pool, creator_wallet, position) triple per
+    /// entry in `amounts`, all writable. Each sell is validated and settled
+    /// independently exactly like sell_tokens; returns the total net SOL received.
+    /// There is no slot for a reserve vault, so vault pools must sell individually
+    pub fn sell_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SellBasket<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<u64> {
+        require!(!amounts.is_empty(), SipzyError::InvalidBasket);
+        require!(amounts.len() <= MAX_BASKET_SIZE, SipzyError::BatchTooLarge);
+        require!(
+            ctx.remaining_accounts.len() == amounts.len() * 3,
+            SipzyError::InvalidBasket
+        );
+        
+        let trader_info = ctx.accounts.trader.to_account_info();
+        let trader = trader_info.key();
+        let system = ctx.accounts.system_program.to_account_info();
+        let mut insurance_fund = load_insurance_fund(&ctx.accounts.insurance_fund)?;
+        let mut total_received: u64 = 0;
+        
+        for (accounts, &amount) in ctx.remaining_accounts.chunks(3).zip(amounts.iter()) {
+            let (pool_info, creator_wallet, position_info) = (&accounts[0], &accounts[1], &accounts[2]);
+            require!(
+                pool_info.is_writable && creator_wallet.is_writable && position_info.is_writable,
+                SipzyError::InvalidBasket
+            );
+            
+            let mut pool: Account<Pool> = Account::try_from(pool_info)?;
+            require_keys_eq!(creator_wallet.key(), pool.creator_wallet, SipzyError::InvalidCreatorWallet);
+            let mut position: Account<HolderPosition> = Account::try_from(position_info)?;
+            require!(
+                position.pool == pool.key() && position.owner == trader,
+                SipzyError::Unauthorized
+            );
+            
+            let received = execute_sell(
+                &mut pool,
+                &mut position,
+                trader,
+                &trader_info,
+                creator_wallet,
+                None,
+                None,
+                &system,
+                TradeTrackers {
+                    insurance_fund: insurance_fund.as_mut(),
+                    ..TradeTrackers::default()
+                },
+                amount,
+            )?;
+            total_received = total_received.checked_add(received).ok_or(SipzyError::Overflow)?;
+            
+            // Persist before the next entry, which may reference the same accounts
+            pool.exit(&crate::ID)?;
+            position.exit(&crate::ID)?;
+        }
+        save_insurance_fund(insurance_fund)?;
+        
+        Ok(total_received)
+    }
+
+    /// Sell `sell_amount` tokens of `from_pool` and spend the net proceeds on
+    /// `to_pool` in one transaction
+    /// The sell settles exactly like sell_tokens; the buy then takes the largest
+    /// amount whose total cost fits the proceeds, like buy_exact_cost, failing
+    /// with SlippageExceeded below `min_buy_amount`. Whatever the buy doesn't
+    /// spend stays with the trader. Both pools must settle in native SOL, and
+    /// the optional trackers aren't updated for either leg; both legs still pay
+    /// the insurance fund its share
+    pub fn swap_between_pools(
+        ctx: Context<SwapBetweenPools>,
+        sell_amount: u64,
+        min_buy_amount: u64,
+    ) -> Result<SwapResult> {
+        require!(sell_amount > 0 && min_buy_amount > 0, SipzyError::InvalidAmount);
+        let accounts = &mut *ctx.accounts;
+        require!(
+            accounts.from_pool.quote_mint.is_none() && accounts.to_pool.quote_mint.is_none(),
+            SipzyError::QuoteMintUnsupported
+        );
+        
+        let trader = accounts.trader.to_account_info();
+        let system = accounts.system_program.to_account_info();
+        let mut insurance_fund = load_insurance_fund(&accounts.insurance_fund)?;
+        let sol_received = execute_sell(
+            &mut accounts.from_pool,
+            &mut accounts.from_position,
+            trader.key(),
+            &trader,
+            &accounts.from_creator_wallet,
+            accounts.from_reserve_vault.as_ref(),
+            None,
+            &system,
+            TradeTrackers {
+                insurance_fund: insurance_fund.as_mut(),
+                ..TradeTrackers::default()
+            },
+            sell_amount,
+        )?;
+        
+        let to_pool = &accounts.to_pool;
+        let mut max_amount = u64::MAX - to_pool.total_supply;
+        if to_pool.max_supply > 0 {
+            max_amount = max_amount.min(to_pool.max_supply.saturating_sub(to_pool.total_supply));
+        }
+        let tokens_bought = max_affordable_amount(to_pool, sol_received, max_amount)?;
+        require!(
+            tokens_bought > 0 && tokens_bought >= min_buy_amount,
+            SipzyError::SlippageExceeded
+        );
+        let quote = quote_buy(to_pool, tokens_bought)?;
+        let sol_spent = quote.total_cost;
+        
+        execute_buy(
+            BuyAccounts {
+                pool: &mut accounts.to_pool,
+                position: &mut accounts.to_position,
+                trader,
+                creator_wallet: accounts.to_creator_wallet.to_account_info(),
+                reserve_vault: accounts.to_reserve_vault.as_ref(),
+                quote_accounts: None,
+                system_program: system,
+                trackers: TradeTrackers {
+                    insurance_fund: insurance_fund.as_mut(),
+                    ..TradeTrackers::default()
+                },
+            },
+            ctx.bumps.to_position,
+            tokens_bought,
+            tokens_bought,
+            quote,
+        )?;
+        save_insurance_fund(insurance_fund)?;
+        
+        Ok(SwapResult {
+            tokens_sold: sell_amount,
+            sol_received,
+            tokens_bought,
+            sol_spent,
+        })
+    }
+
     /// Get current token price (view function)
+    /// Returns the price of one whole token at the current supply; fails with
+    /// Overflow once the price exceeds u64, where a buy would fail too
     pub fn get_price(ctx: Context<GetPoolInfo>) -> Result<u64> {
         let pool = &ctx.accounts.pool;
-        let price = match pool.pool_type {
-            PoolType::Creator => calculate_linear_price(
-                pool.total_supply,
-                pool.base_price,
-                pool.curve_param,
-            ),
-            PoolType::Stream => calculate_exponential_price(
-                pool.total_supply,
-                pool.base_price,
-                pool.curve_param,
-            )?,
-        };
-        Ok(price)
+        calculate_spot_price(pool, curve_supply(pool)?)
+    }
+
+    /// Get the price of one whole token at a hypothetical supply (view function)
+    /// Uses the same curve math as get_price, for charts and what-if calculators.
+    /// A supply past the pool's max_supply fails with MaxSupplyExceeded
+    pub fn get_price_at(ctx: Context<GetPoolInfo>, supply: u64) -> Result<u64> {
+        let pool = &ctx.accounts.pool;
+        require!(within_max_supply(pool, supply), SipzyError::MaxSupplyExceeded);
+        calculate_spot_price(pool, supply)
+    }
+
+    /// Get current token price, saturating instead of failing (view function)
+    /// Returns u64::MAX when the true price exceeds u64 (e.g. a Stream pool at a
+    /// very high supply) so UIs can show "price too high to represent";
+    /// get_price stays strict for callers that need an exact value
+    pub fn get_price_saturating(ctx: Context<GetPoolInfo>) -> Result<u64> {
+        let pool = &ctx.accounts.pool;
+        spot_price_saturating(pool, curve_supply(pool)?)
+    }
+
+    /// Get the smallest supply whose spot price is at least `target_price` (view function)
+    /// Binary search over the spot price, so decimals and tick size are honoured
+    /// exactly for both curve types; powers "alert me at price X" features
+    pub fn get_supply_for_price(ctx: Context<GetPoolInfo>, target_price: u64) -> Result<u64> {
+        let pool = &ctx.accounts.pool;
+        require!(
+            spot_price_saturating(pool, u64::MAX)? >= target_price,
+            SipzyError::PriceUnreachable
+        );
+        
+        // Spot price never decreases with supply
+        let mut low = 0;
+        let mut high = u64::MAX;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if spot_price_saturating(pool, mid)? >= target_price {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        
+        Ok(low)
     }
 
     /// Get cost to buy a specific amount of tokens
     pub fn get_buy_cost(ctx: Context<GetPoolInfo>, amount: u64) -> Result<u64> {
         let pool = &ctx.accounts.pool;
-        let start = pool.total_supply;
+        let start = curve_supply(pool)?;
         let end = start.checked_add(amount).ok_or(SipzyError::Overflow)?;
         
-        let cost = match pool.pool_type {
-            PoolType::Creator => calculate_linear_integral(start, end, pool.base_price, pool.curve_param)?,
-            PoolType::Stream => calculate_exponential_integral(start, end, pool.base_price, pool.curve_param)?,
-        };
+        let cost = calculate_curve_cost(pool, start, end, Rounding::Up)?;
         
-        // Add fee
+        // Add fee, including any launch-window anti-sniping surcharge
+        let fee_bps = buy_fee_bps(pool, amount, Clock::get()?.unix_timestamp);
         let total_with_fee = cost
-            .checked_mul(10000 + FEE_BASIS_POINTS)
+            .checked_mul(10000 + fee_bps)
             .ok_or(SipzyError::Overflow)?
             .checked_div(10000)
             .ok_or(SipzyError::Overflow)?;
         
-        Ok(total_with_fee)
+        // Add the buy tax, which is paid into the reserve
+        total_with_fee
+            .checked_add(calculate_buy_tax(cost, pool.buy_tax_bps)?)
+            .ok_or(SipzyError::Overflow.into())
     }
 
-    /// Deactivate a pool (creator only)
-    pub fn deactivate_pool(ctx: Context<ManagePool>) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
-        pool.is_active = false;
+    /// Break get_buy_cost down into its parts (view function)
+    /// base_component is what `amount` would cost at base_price alone (the sum
+    /// of the base terms) and curve_component the rest of the curve integral:
+    /// the slope terms on a Creator pool, the compounded growth on a Stream
+    /// pool. With tick rounding the integral can dip below the base terms, in
+    /// which case base_component is capped at the integral. fee is the part of
+    /// those two that goes to the creator rather than the reserve, so it is not
+    /// charged on top: base_component + curve_component + buy_tax is what the
+    /// buy costs
+    pub fn get_buy_cost_components(
+        ctx: Context<GetPoolInfo>,
+        amount: u64,
+    ) -> Result<BuyCostComponents> {
+        let pool = &ctx.accounts.pool;
+        let start = curve_supply(pool)?;
+        let end = start.checked_add(amount).ok_or(SipzyError::Overflow)?;
         
-        emit!(PoolStatusChanged {
-            pool: pool.key(),
-            is_active: false,
-        });
+        let cost = calculate_curve_cost(pool, start, end, Rounding::Up)?;
+        let base_terms = pool.base_price as u128 * amount as u128 / token_unit(pool.decimals) as u128;
+        let base_component = base_terms.min(cost as u128) as u64;
         
-        Ok(())
+        let fee_bps = buy_fee_bps(pool, amount, Clock::get()?.unix_timestamp);
+        let (fee, _) = calculate_fee(cost, fee_bps, 0)?;
+        
+        Ok(BuyCostComponents {
+            base_component,
+            curve_component: cost - base_component,
+            fee,
+            buy_tax: calculate_buy_tax(cost, pool.buy_tax_bps)?,
+        })
     }
 
-    /// Reactivate a pool (creator only)
-    pub fn reactivate_pool(ctx: Context<ManagePool>) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
-        pool.is_active = true;
+    /// Get the round-trip cost of one whole token at the current supply (view function)
+    /// What a buyer pays for the next token minus what a seller nets for the
+    /// last one, so the fee on both sides plus the curve's slope over one token.
+    /// Loyalty discounts and early-sell penalties are not applied
+    pub fn get_spread(ctx: Context<GetPoolInfo>) -> Result<u64> {
+        let pool = &ctx.accounts.pool;
+        let unit = token_unit(pool.decimals);
         
-        emit!(PoolStatusChanged {
-            pool: pool.key(),
-            is_active: true,
-        });
+        let buy_start = curve_supply(pool)?;
+        let buy_end = buy_start.checked_add(unit).ok_or(SipzyError::Overflow)?;
+        let buy_cost = calculate_curve_cost(pool, buy_start, buy_end, Rounding::Up)?;
         
-        Ok(())
+        let sell_amount = unit.min(pool.total_supply);
+        let sell_refund = if sell_amount > 0 {
+            calculate_sell_refund(pool, sell_amount, 0, 0)?.net_refund
+        } else {
+            0
+        };
+        
+        Ok(buy_cost.saturating_sub(sell_refund))
     }
 
-    // ========================================================================
-    // LEGACY SUPPORT - Keep backward compatibility with existing pools
-    // ========================================================================
+    /// Get what buying the next whole token costs (view function)
+    /// The "ask" for order-book-style UIs: the curve cost of one token from the
+    /// current supply, which the creator fee is carved out of, plus any buy
+    /// tax, so it matches what a one-token buy charges. Loyalty discounts and
+    /// trading status are not applied
+    pub fn get_next_buy_price(ctx: Context<GetPoolInfo>) -> Result<u64> {
+        let pool = &ctx.accounts.pool;
+        let start = curve_supply(pool)?;
+        let end = start.checked_add(token_unit(pool.decimals)).ok_or(SipzyError::Overflow)?;
+        
+        let cost = calculate_curve_cost(pool, start, end, Rounding::Up)?;
+        cost.checked_add(calculate_buy_tax(cost, pool.buy_tax_bps)?)
+            .ok_or(SipzyError::Overflow.into())
+    }
 
-    /// Legacy initialize_pool (maps to Creator pool with youtube_id as channel)
-    pub fn initialize_pool(
-        ctx: Context<InitializeCreatorPool>,
-        youtube_id: String,
-        creator_wallet: Pubkey,
-    ) -> Result<()> {
-        initialize_creator_pool(
-            ctx,
-            youtube_id.clone(),
-            youtube_id, // Use ID as name for legacy
-            String::new(), // No metadata URI
-            None,
-            None,
-        )
+    /// Get what selling one whole token nets the seller (view function)
+    /// The "bid": the refund after fees for the last token on the curve, or
+    /// for whatever is left when less than a token is outstanding. Loyalty
+    /// discounts and early-sell penalties are not applied
+    pub fn get_next_sell_price(ctx: Context<GetPoolInfo>) -> Result<u64> {
+        let pool = &ctx.accounts.pool;
+        let amount = token_unit(pool.decimals).min(pool.total_supply);
+        if amount == 0 {
+            return Ok(0);
+        }
+        
+        Ok(calculate_sell_refund(pool, amount, 0, 0)?.net_refund)
     }
-}
 
-// ============================================================================
-// BONDING CURVE MATH
-// ============================================================================
+    /// Get the live curve parameters in one call (view function)
+    /// Enough for an off-chain chart to reproduce every point of the curve,
+    /// including the decimals and tick size that shape spot prices
+    pub fn get_curve(ctx: Context<GetPoolInfo>) -> Result<CurveInfo> {
+        let pool = &ctx.accounts.pool;
+        
+        Ok(CurveInfo {
+            pool_type: pool.pool_type,
+            base_price: pool.base_price,
+            curve_param: pool.curve_param,
+            total_supply: pool.total_supply,
+            decimals: pool.decimals,
+            tick_size: pool.tick_size,
+            price_ceiling: pool.price_ceiling,
+        })
+    }
 
-/// Calculate linear price: Price(n) = slope × n + base_price
-fn calculate_linear_price(supply: u64, base_price: u64, slope: u64) -> u64 {
-    base_price.saturating_add(supply.saturating_mul(slope))
-}
+    /// Get market cap and fully diluted valuation at the spot price (view function)
+    /// Market cap values total_supply and FDV values max_supply (0 when the pool
+    /// is uncapped). Computed in u128 and clamped to u64::MAX with `saturated`
+    /// set, so hot Stream pools still return a sortable value instead of an error
+    pub fn get_market_cap(ctx: Context<GetPoolInfo>) -> Result<MarketCap> {
+        let pool = &ctx.accounts.pool;
+        let spot_price = spot_price_saturating(pool, curve_supply(pool)?)?;
+        let unit = token_unit(pool.decimals);
+        let (market_cap, cap_saturated) = value_at_price(spot_price, pool.total_supply, unit);
+        let (fully_diluted_valuation, fdv_saturated) = value_at_price(spot_price, pool.max_supply, unit);
+        
+        Ok(MarketCap {
+            market_cap,
+            fully_diluted_valuation,
+            saturated: spot_price == u64::MAX || cap_saturated || fdv_saturated,
+        })
+    }
 
-/// Calculate integral of linear curve for buying/selling k tokens
-/// Cost = ∫[start to end] (slope × n + base) dn
-///      = slope × (end² - start²)/2 + base × (end - start)
-///      = slope × k × (start + end - 1)/2 + base × k  [where k = end - start]
-fn calculate_linear_integral(
-    start_supply: u64,
-    end_supply: u64,
-    base_price: u64,
-    slope: u64,
-) -> Result<u64> {
-    let amount = end_supply.checked_sub(start_supply).ok_or(SipzyError::Overflow)?;
-    if amount == 0 {
-        return Ok(0);
+    /// Get seconds since the pool was created (view function)
+    /// Uses the cluster clock so every client ranks by the same time source;
+    /// a created_at ahead of the clock reads as 0
+    pub fn get_pool_age(ctx: Context<GetPoolInfo>) -> Result<i64> {
+        let now = Clock::get()?.unix_timestamp;
+        Ok(now.saturating_sub(ctx.accounts.pool.created_at).max(0))
     }
-    
-    // Base cost = amount × base_price
-    let base_cost = amount.checked_mul(base_price).ok_or(SipzyError::Overflow)?;
-    
-    // Slope cost = slope × sum of indices from start to end-1
-    // Sum = amount × (first + last) / 2 where first=start, last=end-1
-    let first = start_supply;
-    let last = end_supply.checked_sub(1).ok_or(SipzyError::Overflow)?;
-    
-    let sum_indices = amount
-        .checked_mul(first.checked_add(last).ok_or(SipzyError::Overflow)?)
-        .ok_or(SipzyError::Overflow)?
-        .checked_div(2)
-        .ok_or(SipzyError::Overflow)?;
-    
-    let slope_cost = sum_indices.checked_mul(slope).ok_or(SipzyError::Overflow)?;
-    
-    base_cost.checked_add(slope_cost).ok_or(SipzyError::Overflow.into())
-}
 
-/// Calculate exponential price: Price(n) = base_price × (1 + growth_rate)^n
-/// growth_rate is in basis points (500 = 5% = 0.05)
-fn calculate_exponential_price(
-    supply: u64,
-    base_price: u64,
-    growth_rate_bps: u64,
-) -> Result<u64> {
-    // Convert to fixed-point: (1 + rate) = (10000 + growth_rate_bps) / 10000
-    // We use EXP_PRECISION for high precision
-    let rate_multiplier = 10000u128 + growth_rate_bps as u128; // e.g., 10500 for 5%
-    
-    // Calculate (rate_multiplier / 10000)^supply using iterative multiplication
-    // For large supplies, we need to be careful about overflow
-    let mut result: u128 = EXP_PRECISION;
-    let mut exp = supply;
-    let mut base: u128 = (rate_multiplier * EXP_PRECISION) / 10000;
-    
-    // Fast exponentiation using binary method
-    while exp > 0 {
-        if exp % 2 == 1 {
-            result = (result * base) / EXP_PRECISION;
+    /// Get a one-call summary of the pool (view function)
+    /// trading_status tells clients why trades would be rejected, see TradingStatus
+    pub fn get_pool_summary(ctx: Context<GetPoolSummary>) -> Result<PoolSummary> {
+        let pool = &ctx.accounts.pool;
+        let global_paused = globally_paused(&ctx.accounts.global_config);
+        
+        Ok(PoolSummary {
+            pool_type: pool.pool_type,
+            identifier: pool.identifier.clone(),
+            display_name: pool.display_name.clone(),
+            creator_wallet: pool.creator_wallet,
+            total_supply: pool.total_supply,
+            reserve_sol: pool.reserve_sol,
+            current_price: calculate_spot_price(pool, curve_supply(pool)?)?,
+            decimals: pool.decimals,
+            is_active: pool.is_active,
+            trading_status: trading_status(pool, global_paused, Clock::get()?.unix_timestamp) as u8,
+            total_buy_volume: pool.total_buy_volume,
+            total_sell_volume: pool.total_sell_volume,
+            lifetime_fees: pool.lifetime_fees,
+        })
+    }
+
+    /// Get the pool's key fields as one fixed-layout blob (view function)
+    /// For thin clients that would rather not track the IDL. Integers are
+    /// little-endian; the layout only changes together with the version byte,
+    /// and new fields are only ever appended.
+    ///
+    /// | offset | size | field                                           |
+    /// |--------|------|-------------------------------------------------|
+    /// | 0      | 1    | layout version (1)                              |
+    /// | 1      | 1    | pool_type (0 Creator, 1 Stream)                 |
+    /// | 2      | 1    | trading_status (see TradingStatus)              |
+    /// | 3      | 1    | decimals                                        |
+    /// | 4      | 1    | flags: 1 active, 2 graduated, 4 verified,       |
+    /// |        |      | 8 reserve-backed sells, 16 compliance           |
+    /// | 5      | 32   | creator_wallet                                  |
+    /// | 37     | 8    | total_supply                                    |
+    /// | 45     | 8    | reserve_sol                                     |
+    /// | 53     | 8    | base_price                                      |
+    /// | 61     | 8    | curve_param                                     |
+    /// | 69     | 8    | tick_size                                       |
+    /// | 77     | 2    | fee_bps                                         |
+    /// | 79     | 8    | max_supply (0 = uncapped)                       |
+    /// | 87     | 8    | spot price, saturating at u64::MAX              |
+    /// | 95     | 8    | expires_at (0 = never)                          |
+    /// | 103    | 8    | pause_until                                     |
+    pub fn get_pool_packed(ctx: Context<GetPoolSummary>) -> Result<Vec<u8>> {
+        let pool = &ctx.accounts.pool;
+        let now = Clock::get()?.unix_timestamp;
+        let global_paused = globally_paused(&ctx.accounts.global_config);
+        
+        let flags = pool.is_active as u8
+            | (pool.is_graduated as u8) << 1
+            | (pool.verified as u8) << 2
+            | (pool.reserve_backed_sells as u8) << 3
+            | (pool.compliance_enabled as u8) << 4;
+        
+        let mut packed = Vec::with_capacity(POOL_PACKED_LEN);
+        packed.extend_from_slice(&[
+            POOL_PACKED_VERSION,
+            pool.pool_type as u8,
+            trading_status(pool, global_paused, now) as u8,
+            pool.decimals,
+            flags,
+        ]);
+        packed.extend_from_slice(pool.creator_wallet.as_ref());
+        for value in [
+            pool.total_supply,
+            pool.reserve_sol,
+            pool.base_price,
+            pool.curve_param,
+            pool.tick_size,
+        ] {
+            packed.extend_from_slice(&value.to_le_bytes());
         }
-        base = (base * base) / EXP_PRECISION;
-        exp /= 2;
+        // fee_bps is capped at MAX_FEE_BPS, so it always fits in two bytes
+        packed.extend_from_slice(&(pool.fee_bps as u16).to_le_bytes());
+        packed.extend_from_slice(&pool.max_supply.to_le_bytes());
+        packed.extend_from_slice(&spot_price_saturating(pool, curve_supply(pool)?)?.to_le_bytes());
+        packed.extend_from_slice(&pool.expires_at.to_le_bytes());
+        packed.extend_from_slice(&pool.pause_until.to_le_bytes());
         
-        // Check for overflow
-        if result > u64::MAX as u128 * EXP_PRECISION {
-            return Err(SipzyError::Overflow.into());
+        Ok(packed)
+    }
+
+    /// Preview a buy or sell without executing it (view function)
+    /// Runs the same validation, curve, fee and reserve math as buy_tokens and
+    /// sell_tokens and returns the projected pool state. Sells are priced
+    /// without a holder, so loyalty discounts and early-sell penalties are not applied
+    pub fn simulate_trade(
+        ctx: Context<GetPoolInfo>,
+        trade_type: TradeType,
+        amount: u64,
+    ) -> Result<SimResult> {
+        let pool = &ctx.accounts.pool;
+        
+        let (sol_amount, fee, net_sol, new_supply, new_reserve, burned) = match trade_type {
+            TradeType::Buy | TradeType::PartialBuy => {
+                let quote = quote_buy(pool, amount)?;
+                let new_reserve = pool.reserve_sol
+                    .checked_add(quote.pool_deposit)
+                    .ok_or(SipzyError::Overflow)?;
+                (quote.total_cost, quote.creator_fee, quote.total_cost, quote.end_supply, new_reserve, 0)
+            }
+            TradeType::Sell | TradeType::PartialSell => {
+                let quote = quote_sell(pool, amount, 0, 0)?;
+                let new_reserve = pool.reserve_sol - quote.net_refund - quote.creator_fee;
+                (quote.gross_refund, quote.creator_fee, quote.net_refund, pool.total_supply - amount, new_reserve, quote.burned)
+            }
+        };
+        
+        let new_price = calculate_spot_price(pool, new_supply + pool.burned_supply + burned)?;
+        let price_impact_bps = price_impact_bps(calculate_spot_price(pool, curve_supply(pool)?)?, new_price);
+        
+        Ok(SimResult {
+            sol_amount,
+            fee,
+            net_sol,
+            new_supply,
+            new_reserve,
+            new_price,
+            price_impact_bps,
+        })
+    }
+
+    /// Get the average price per whole token of a trade of `amount` (view function)
+    /// What the trader actually pays (buys) or receives (sells) per token, fees
+    /// included, from the same quotes simulate_trade uses. Unlike the spot
+    /// price this reflects the size of the trade
+    pub fn get_effective_price(
+        ctx: Context<GetPoolInfo>,
+        amount: u64,
+        trade_type: TradeType,
+    ) -> Result<u64> {
+        let pool = &ctx.accounts.pool;
+        let sol_amount = match trade_type {
+            TradeType::Buy | TradeType::PartialBuy => quote_buy(pool, amount)?.total_cost,
+            TradeType::Sell | TradeType::PartialSell => quote_sell(pool, amount, 0, 0)?.net_refund,
+        };
+        
+        let price = sol_amount as u128 * token_unit(pool.decimals) as u128 / amount as u128;
+        u64::try_from(price).map_err(|_| SipzyError::Overflow.into())
+    }
+
+    /// Whether buying `amount` tokens would graduate the pool (view function)
+    /// Prices the buy with the same quote buy_tokens settles, so UIs can ask
+    /// for an extra confirmation before the trade that ends curve trading
+    pub fn would_graduate(ctx: Context<GetPoolInfo>, amount: u64) -> Result<bool> {
+        let pool = &ctx.accounts.pool;
+        if pool.graduation_threshold == 0 {
+            return Ok(false);
         }
+        
+        let quote = quote_buy(pool, amount)?;
+        let new_reserve = pool.reserve_sol
+            .checked_add(quote.pool_deposit)
+            .ok_or(SipzyError::Overflow)?;
+        Ok(new_reserve >= pool.graduation_threshold)
     }
-    
-    // Final price = base_price × result / EXP_PRECISION
-    let price = (base_price as u128 * result) / EXP_PRECISION;
-    
-    if price > u64::MAX as u128 {
-        return Err(SipzyError::Overflow.into());
+
+    /// Get how far the pool is toward graduation, in basis points (view function)
+    /// reserve_sol × 10000 / graduation_threshold, capped at 10000 once the
+    /// threshold is reached or the pool has graduated; 0 for a pool with no
+    /// threshold, which never graduates
+    pub fn get_graduation_progress(ctx: Context<GetPoolInfo>) -> Result<u64> {
+        let pool = &ctx.accounts.pool;
+        if pool.is_graduated {
+            return Ok(10_000);
+        }
+        if pool.graduation_threshold == 0 {
+            return Ok(0);
+        }
+        
+        let progress = pool.reserve_sol as u128 * 10_000 / pool.graduation_threshold as u128;
+        Ok(progress.min(10_000) as u64)
     }
-    
-    Ok(price as u64)
-}
 
-/// Calculate integral of exponential curve for buying/selling
-/// Cost = ∑(base_price × r^i) for i from start to end-1
-/// This is a geometric series: base_price × (r^end - r^start) / (r - 1)
-fn calculate_exponential_integral(
-    start_supply: u64,
-    end_supply: u64,
-    base_price: u64,
-    growth_rate_bps: u64,
-) -> Result<u64> {
-    let amount = end_supply.checked_sub(start_supply).ok_or(SipzyError::Overflow)?;
-    if amount == 0 {
-        return Ok(0);
+    /// Get the reserve backing each whole token (view function)
+    /// reserve_sol × 10^decimals / total_supply, rounded down; 0 before any
+    /// tokens exist. Compare with the spot price: a wide gap means late buyers
+    /// paid well above what the reserve holds for them
+    pub fn get_reserve_per_token(ctx: Context<GetPoolInfo>) -> Result<u64> {
+        let pool = &ctx.accounts.pool;
+        if pool.total_supply == 0 {
+            return Ok(0);
+        }
+        
+        let per_token = pool.reserve_sol as u128 * token_unit(pool.decimals) as u128
+            / pool.total_supply as u128;
+        u64::try_from(per_token).map_err(|_| error!(SipzyError::Overflow))
     }
-    
-    // For small amounts, use summation to avoid precision issues
-    if amount <= 100 {
-        let mut total: u128 = 0;
-        for i in start_supply..end_supply {
-            let price = calculate_exponential_price(i, base_price, growth_rate_bps)? as u128;
-            total = total.checked_add(price).ok_or(SipzyError::Overflow)?;
+
+    /// Get a holder's position with cost basis and PnL (view function)
+    /// average_cost is lamports per whole token bought; unrealized_pnl values the
+    /// whole balance at the current spot price against the remaining cost basis
+    /// (founder-allocation tokens carry no cost)
+    pub fn get_position(ctx: Context<GetPosition>, holder: Pubkey) -> Result<PositionSummary> {
+        let pool = &ctx.accounts.pool;
+        let position = &ctx.accounts.position;
+        let unit = token_unit(pool.decimals) as u128;
+        
+        let average_cost = if position.tokens_bought == 0 {
+            0
+        } else {
+            (position.cost_basis_lamports as u128 * unit / position.tokens_bought as u128) as u64
+        };
+        let spot_price = calculate_spot_price(pool, curve_supply(pool)?)?;
+        let spot_value = spot_price as u128 * position.balance as u128 / unit;
+        
+        Ok(PositionSummary {
+            holder,
+            balance: position.balance,
+            cost_basis_lamports: position.cost_basis_lamports,
+            tokens_bought: position.tokens_bought,
+            average_cost,
+            spot_price,
+            unrealized_pnl: spot_value as i128 - position.cost_basis_lamports as i128,
+        })
+    }
+
+    /// Get the pool's top holders, largest balance first (view function)
+    pub fn get_top_holders(ctx: Context<GetTopHolders>) -> Result<Vec<HolderEntry>> {
+        let top_holders = &ctx.accounts.top_holders;
+        Ok(top_holders.entries[..top_holders.count as usize].to_vec())
+    }
+
+    /// Get the leaderboard rank a holder with `balance` would have (view function)
+    /// 1 is the largest holder; ties share a rank. Returns UNRANKED (u32::MAX)
+    /// for a zero balance or one that would not make a full board
+    pub fn get_holder_rank(ctx: Context<GetTopHolders>, balance: u64) -> Result<u32> {
+        let top_holders = &ctx.accounts.top_holders;
+        let entries = &top_holders.entries[..top_holders.count as usize];
+        let rank = entries.iter().filter(|entry| entry.balance > balance).count() + 1;
+        
+        if balance == 0 || rank > MAX_TOP_HOLDERS {
+            return Ok(UNRANKED);
         }
-        if total > u64::MAX as u128 {
-            return Err(SipzyError::Overflow.into());
+        Ok(rank as u32)
+    }
+
+    /// Get the Stream pools a creator launched under `channel_id` (view function)
+    /// Read from the creator's registry, which indexes every Stream pool under
+    /// its parent channel, so a channel page needs no off-chain scan. Closed
+    /// pools drop out of the registry and so out of this list
+    pub fn get_channel_streams(ctx: Context<GetChannelStreams>, channel_id: String) -> Result<Vec<Pubkey>> {
+        Ok(ctx.accounts.registry.streams
+            .iter()
+            .filter(|stream| stream.channel_id == channel_id)
+            .map(|stream| stream.pool)
+            .collect())
+    }
+
+    /// Get the number of live Creator and Stream pools and the SOL they lock (view function)
+    /// Read from the GlobalStats running totals, so a dashboard needs no scan
+    /// over every pool. Counts cover pools created since the stats account
+    /// was, and total_reserve follows trades that passed it
+    pub fn get_pool_type_distribution(ctx: Context<GetGlobalStats>) -> Result<PoolTypeDistribution> {
+        let stats = &ctx.accounts.global_stats;
+        
+        Ok(PoolTypeDistribution {
+            creator_pools: stats.creator_pools,
+            stream_pools: stats.stream_pools,
+            total_reserve: stats.total_reserve,
+        })
+    }
+
+    /// Get the absolute gap between the pool's sell-side liability and its reserve (view function)
+    /// Returns sell integral of the entire supply, less the founder allocation's
+    /// reserve_debt, minus reserve_sol, in lamports; positive means
+    /// undercollateralized by that amount, negative means surplus
+    pub fn get_circulating_vs_reserve_imbalance(ctx: Context<GetPoolInfo>) -> Result<i128> {
+        let pool = &ctx.accounts.pool;
+        let liability = backed_liability(pool)?;
+        
+        Ok(liability as i128 - pool.reserve_sol as i128)
+    }
+
+    /// Deactivate a pool (creator only)
+    /// Also emits a PoolClosedStats summary for archival
+    pub fn deactivate_pool(ctx: Context<ManagePool>) -> Result<()> {
+        set_pool_active(&mut ctx.accounts.pool, false);
+        Ok(())
+    }
+
+    /// Reactivate a pool (creator only)
+    /// Not while the program is globally paused, nor after an emergency
+    /// withdrawal until the admin calls clear_emergency_withdrawal
+    pub fn reactivate_pool(ctx: Context<ReactivatePool>) -> Result<()> {
+        ensure_can_reactivate(&ctx.accounts.pool, &ctx.accounts.global_config)?;
+        set_pool_active(&mut ctx.accounts.pool, true);
+        Ok(())
+    }
+
+    /// Activate or deactivate several pools at once (creator only)
+    /// remaining_accounts holds the pools, all writable and all owned by the
+    /// signing creator; each is flipped exactly as deactivate_pool /
+    /// reactivate_pool would, emitting its own PoolStatusChanged
+    pub fn set_active_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SetActiveBatch<'info>>,
+        is_active: bool,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), SipzyError::EmptyBatch);
+        require!(
+            ctx.remaining_accounts.len() <= MAX_ACTIVE_BATCH_SIZE,
+            SipzyError::BatchTooLarge
+        );
+        
+        let creator = ctx.accounts.creator.key();
+        for pool_info in ctx.remaining_accounts {
+            require!(pool_info.is_writable, ErrorCode::ConstraintMut);
+            let mut pool: Account<Pool> = Account::try_from(pool_info)?;
+            require_keys_eq!(pool.creator_wallet, creator, SipzyError::Unauthorized);
+            if is_active {
+                ensure_can_reactivate(&pool, &ctx.accounts.global_config)?;
+            }
+            
+            set_pool_active(&mut pool, is_active);
+            
+            // Persist before the next entry, which may be the same pool
+            pool.exit(&crate::ID)?;
         }
-        return Ok(total as u64);
+        
+        Ok(())
     }
-    
-    // For larger amounts, use geometric series formula
-    // Sum = base × (r^end - r^start) / (r - 1)
-    let r_bps = 10000u128 + growth_rate_bps as u128;
-    
-    // Calculate r^start and r^end
-    let r_start = exp_power(r_bps, start_supply, 10000)?;
-    let r_end = exp_power(r_bps, end_supply, 10000)?;
-    
-    // Numerator: base_price × (r^end - r^start)
-    let diff = r_end.checked_sub(r_start).ok_or(SipzyError::Overflow)?;
-    let numerator = (base_price as u128)
-        .checked_mul(diff)
-        .ok_or(SipzyError::Overflow)?;
-    
-    // Denominator: r - 1 = growth_rate_bps / 10000
-    // To avoid division by small number, we multiply numerator by 10000 first
-    let denominator = growth_rate_bps as u128;
-    
-    if denominator == 0 {
-        // If no growth rate, it's just constant price
-        return Ok(base_price.checked_mul(amount).ok_or(SipzyError::Overflow)?);
+
+    /// Close a pool with no circulating supply (creator only)
+    /// Removes the pool from the creator's registry and returns its rent
+    /// (and any dust left in the reserve) to the creator
+    pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        // Closing would strand quote tokens left in the reserve token account
+        require!(
+            pool.quote_mint.is_none() || pool.reserve_sol == 0,
+            SipzyError::QuoteMintUnsupported
+        );
+        
+        // The pool's own lamports go to the creator when it closes; a reserve
+        // vault is emptied too, rent included, so it is deleted with it
+        if pool.reserve_vault_enabled {
+            let vault_balance = reserve_account(pool, ctx.accounts.reserve_vault.as_ref())?.lamports();
+            pay_from_reserve(
+                pool,
+                ctx.accounts.reserve_vault.as_ref(),
+                &ctx.accounts.system_program,
+                &ctx.accounts.creator.to_account_info(),
+                vault_balance,
+            )?;
+        }
+        
+        remove_from_registry(&mut ctx.accounts.registry, pool.key());
+        if let Some(stats) = ctx.accounts.global_stats.as_deref_mut() {
+            record_pool_closed(stats, pool);
+        }
+        
+        // The account is closed, so the final sequence number isn't stored
+        emit!(PoolClosed {
+            pool: pool.key(),
+            seq: pool.seq.wrapping_add(1),
+            creator_wallet: pool.creator_wallet,
+            residual_reserve: pool.reserve_sol,
+        });
+        
+        Ok(())
     }
-    
-    let result = numerator
-        .checked_mul(10000)
-        .ok_or(SipzyError::Overflow)?
-        .checked_div(denominator)
-        .ok_or(SipzyError::Overflow)?
-        .checked_div(EXP_PRECISION)
-        .ok_or(SipzyError::Overflow)?;
-    
-    if result > u64::MAX as u128 {
-        return Err(SipzyError::Overflow.into());
+
+    /// Mint a founder allocation to the creator (once, before any trading)
+    /// Increases supply and the creator's HolderPosition without moving SOL.
+    /// The curve cost of the minted tokens is recorded as reserve_debt, since
+    /// the reserve holds nothing to back them. Vesting, if configured with
+    /// set_founder_vesting, starts now.
+    pub fn mint_founder_allocation(ctx: Context<MintFounderAllocation>, amount: u64) -> Result<()> {
+        require!(amount > 0, SipzyError::InvalidAmount);
+        
+        let pool = &ctx.accounts.pool;
+        require!(!pool.founder_minted, SipzyError::FounderAllocationMinted);
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        require!(within_max_supply(pool, amount), SipzyError::MaxSupplyExceeded);
+        
+        let implied_cost = calculate_curve_cost(pool, 0, amount, Rounding::Down)?;
+        
+        let position = &mut ctx.accounts.position;
+        open_position(
+            position,
+            ctx.accounts.pool.key(),
+            ctx.accounts.creator.key(),
+            ctx.bumps.position,
+        );
+        let now = Clock::get()?.unix_timestamp;
+        credit_position(position, amount, now)?;
+        position.is_founder = true;
+        
+        let pool = &mut ctx.accounts.pool;
+        pool.total_supply = amount;
+        pool.reserve_debt = implied_cost;
+        pool.founder_minted = true;
+        pool.founder_allocation = amount;
+        pool.founder_vest_start = now;
+        
+        emit!(FounderAllocation {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            creator_wallet: pool.creator_wallet,
+            amount,
+            reserve_debt: implied_cost,
+        });
+        
+        Ok(())
     }
-    
-    Ok(result as u64)
-}
 
-/// Helper: Calculate (base/scale)^exp with high precision
-fn exp_power(base: u128, exp: u64, scale: u128) -> Result<u128> {
-    let mut result: u128 = EXP_PRECISION;
-    let mut b: u128 = (base * EXP_PRECISION) / scale;
-    let mut e = exp;
-    
-    while e > 0 {
-        if e % 2 == 1 {
-            result = (result * b) / EXP_PRECISION;
+    /// Opt a pool into on-chain price snapshots (creator pays the rent)
+    /// Trades that pass the snapshot account record (timestamp, price, volume)
+    /// at most once per `interval_secs` into a 96-entry ring buffer
+    pub fn initialize_price_snapshot(
+        ctx: Context<InitializePriceSnapshot>,
+        interval_secs: i64,
+    ) -> Result<()> {
+        require!(interval_secs > 0, SipzyError::InvalidInterval);
+        
+        let price_snapshot = &mut ctx.accounts.price_snapshot;
+        price_snapshot.pool = ctx.accounts.pool.key();
+        price_snapshot.interval_secs = interval_secs;
+        price_snapshot.last_snapshot_ts = 0;
+        price_snapshot.pending_volume = 0;
+        price_snapshot.head = 0;
+        price_snapshot.count = 0;
+        price_snapshot.entries = [SnapshotEntry::default(); MAX_SNAPSHOTS];
+        price_snapshot.bump = ctx.bumps.price_snapshot;
+        
+        Ok(())
+    }
+
+    /// Opt a pool into an on-chain leaderboard of its top 10 holders (creator pays the rent)
+    /// Trades that pass the leaderboard account re-rank the trader by their
+    /// new balance; refresh_top_holder re-ranks any holder permissionlessly
+    pub fn initialize_top_holders(ctx: Context<InitializeTopHolders>) -> Result<()> {
+        let top_holders = &mut ctx.accounts.top_holders;
+        top_holders.pool = ctx.accounts.pool.key();
+        top_holders.count = 0;
+        top_holders.entries = [HolderEntry::default(); MAX_TOP_HOLDERS];
+        top_holders.bump = ctx.bumps.top_holders;
+        
+        Ok(())
+    }
+
+    /// Re-rank a holder on the leaderboard from their current position
+    /// Lets anyone correct an entry left stale by a trade that didn't pass
+    /// the leaderboard account
+    pub fn refresh_top_holder(ctx: Context<RefreshTopHolder>) -> Result<()> {
+        let position = &ctx.accounts.position;
+        update_top_holders(&mut ctx.accounts.top_holders, position.owner, position.balance);
+        Ok(())
+    }
+
+    /// Creator buyback: buy `amount` tokens off the curve and burn them immediately
+    /// The creator pays the curve cost into the reserve, but supply ends where it
+    /// started, so the reserve grows and raises the floor for existing holders.
+    /// No fee is charged since it would be paid to the creator themselves.
+    pub fn creator_buyback(ctx: Context<CreatorBuyback>, amount: u64) -> Result<()> {
+        require!(amount > 0, SipzyError::InvalidAmount);
+        require!(ctx.accounts.pool.is_active, SipzyError::PoolInactive);
+        
+        let pool = &ctx.accounts.pool;
+        let start_point = curve_supply(pool)?;
+        let end_point = start_point.checked_add(amount).ok_or(SipzyError::Overflow)?;
+        let sol_spent = calculate_curve_cost(pool, start_point, end_point, Rounding::Up)?;
+        
+        let new_reserve = pool.reserve_sol.checked_add(sol_spent).ok_or(SipzyError::Overflow)?;
+        if pool.reserve_cap > 0 {
+            require!(new_reserve <= pool.reserve_cap, SipzyError::ReserveCapReached);
         }
-        b = (b * b) / EXP_PRECISION;
-        e /= 2;
+        
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.creator.to_account_info(),
+                    to: reserve_account(&ctx.accounts.pool, ctx.accounts.reserve_vault.as_ref())?,
+                },
+            ),
+            sol_spent,
+        )?;
+        
+        let pool = &mut ctx.accounts.pool;
+        pool.reserve_sol = new_reserve;
+        pool.net_deposited = pool.net_deposited
+            .checked_add(sol_spent)
+            .ok_or(SipzyError::Overflow)?;
+        check_reserve_accounting(pool)?;
+        pool.total_buyback_sol = pool.total_buyback_sol
+            .checked_add(sol_spent)
+            .ok_or(SipzyError::Overflow)?;
+        
+        emit!(Buyback {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            creator_wallet: pool.creator_wallet,
+            amount,
+            sol_spent,
+            new_reserve: pool.reserve_sol,
+        });
+        
+        Ok(())
     }
-    
-    Ok(result)
-}
 
-/// Calculate fee (1% = 100 basis points)
-fn calculate_fee(amount: u64) -> Result<(u64, u64)> {
-    let fee = amount
-        .checked_mul(FEE_BASIS_POINTS)
-        .ok_or(SipzyError::Overflow)?
-        .checked_div(10000)
-        .ok_or(SipzyError::Overflow)?;
-    let net = amount.checked_sub(fee).ok_or(SipzyError::Overflow)?;
-    Ok((fee, net))
+    /// Propose a new creator wallet (step 1 of 2, creator only)
+    /// The new wallet may be a keypair or a PDA (e.g. a DAO treasury vault);
+    /// proposing Pubkey::default() cancels a pending rotation
+    pub fn propose_creator_wallet(
+        ctx: Context<ManagePool>,
+        new_creator_wallet: Pubkey,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(
+            new_creator_wallet != pool.creator_wallet,
+            SipzyError::InvalidCreatorWallet
+        );
+        pool.pending_creator_wallet = new_creator_wallet;
+        
+        emit!(CreatorWalletProposed {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            current_creator_wallet: pool.creator_wallet,
+            pending_creator_wallet: new_creator_wallet,
+        });
+        
+        Ok(())
+    }
+
+    /// Accept a proposed creator wallet (step 2 of 2, signed by the new wallet)
+    /// A PDA recipient accepts by having its owning program sign via invoke_signed.
+    /// Fees reach PDAs like any other wallet: buys use a system transfer and sells
+    /// credit lamports directly, neither of which needs the recipient to sign.
+    /// The pool moves from the old creator's registry to the new one.
+    pub fn accept_creator_wallet(ctx: Context<AcceptCreatorWallet>) -> Result<()> {
+        let pool_key = ctx.accounts.pool.key();
+        let new_creator_wallet = ctx.accounts.new_creator_wallet.key();
+        
+        // A pool converted into a Stream pool has no parent channel to list
+        let stream_channel = (ctx.accounts.pool.pool_type == PoolType::Stream)
+            .then(|| ctx.accounts.pool.parent_identifier.clone())
+            .filter(|channel_id| !channel_id.is_empty());
+        remove_from_registry(&mut ctx.accounts.old_registry, pool_key);
+        register_pool(
+            &mut ctx.accounts.new_registry,
+            new_creator_wallet,
+            pool_key,
+            stream_channel,
+            ctx.bumps.new_registry,
+        );
+        
+        let pool = &mut ctx.accounts.pool;
+        let previous_creator_wallet = pool.creator_wallet;
+        pool.creator_wallet = new_creator_wallet;
+        pool.pending_creator_wallet = Pubkey::default();
+        // The ownership attestation named the previous wallet
+        pool.verified = false;
+        
+        emit!(CreatorWalletChanged {
+            pool: pool_key,
+            seq: next_seq(pool),
+            previous_creator_wallet,
+            new_creator_wallet,
+        });
+        
+        Ok(())
+    }
+
+    /// Update the pool's metadata URI (creator only)
+    /// The pool account is resized to fit the new URI; the creator pays the
+    /// extra rent when it grows and is refunded the difference when it shrinks
+    pub fn update_metadata_uri(ctx: Context<UpdateMetadataUri>, metadata_uri: String) -> Result<()> {
+        set_metadata_uri(
+            &mut ctx.accounts.pool,
+            &ctx.accounts.creator.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            metadata_uri,
+        )
+    }
+
+    /// Lock the pool's metadata permanently (creator only)
+    /// A one-way commitment for collectible drops: afterwards
+    /// update_metadata_uri, update_metadata_format and upgrade_legacy_pool
+    /// fail with MetadataFrozen
+    pub fn freeze_metadata(ctx: Context<ManagePool>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(!pool.metadata_frozen, SipzyError::MetadataFrozen);
+        pool.metadata_frozen = true;
+        
+        emit!(MetadataFrozen {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            metadata_uri: pool.metadata_uri.clone(),
+        });
+        
+        Ok(())
+    }
+
+    /// Declare how the metadata URI resolves and update it to match (creator only)
+    /// See MetadataFormat; the URI must have the declared shape or the update
+    /// fails with InvalidMetadataFormat. Later update_metadata_uri calls are
+    /// checked against the same format
+    pub fn update_metadata_format(
+        ctx: Context<UpdateMetadataUri>,
+        metadata_format: u8,
+        metadata_uri: String,
+    ) -> Result<()> {
+        require!(
+            metadata_format <= MetadataFormat::ArweaveTxId as u8,
+            SipzyError::InvalidMetadataFormat
+        );
+        ctx.accounts.pool.metadata_format = metadata_format;
+        set_metadata_uri(
+            &mut ctx.accounts.pool,
+            &ctx.accounts.creator.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            metadata_uri,
+        )
+    }
+
+    /// Set how long the founder allocation takes to vest, linearly (creator only)
+    /// Must be set before the allocation is minted; 0 means no vesting
+    pub fn set_founder_vesting(ctx: Context<ManagePool>, duration_secs: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(!pool.founder_minted, SipzyError::FounderAllocationMinted);
+        pool.founder_vest_duration = duration_secs;
+        
+        emit!(FounderVestingUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            duration_secs,
+        });
+        
+        Ok(())
+    }
+
+    /// Set the reserve level at which the pool graduates (creator only, before trading)
+    /// A graduated pool stops selling tokens off the curve; holders can still sell
+    /// back into the reserve. 0 disables graduation
+    pub fn set_graduation_threshold(ctx: Context<ManagePool>, graduation_threshold: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        pool.graduation_threshold = graduation_threshold;
+        
+        emit!(GraduationThresholdUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            graduation_threshold,
+        });
+        
+        Ok(())
+    }
+
+    /// Sweep the reserve left behind after graduation to the creator (creator only)
+    /// Only once every token has been sold back; moves reserve_sol and zeroes
+    /// it without closing the pool. Lamports sent to the pool outside the
+    /// reserve are not part of reserve_sol and stay where they are
+    pub fn sweep_residual_reserve(ctx: Context<SweepResidualReserve>) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        require!(pool.is_graduated, SipzyError::PoolNotGraduated);
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        
+        let amount = pool.reserve_sol;
+        pay_from_reserve(
+            pool,
+            ctx.accounts.reserve_vault.as_ref(),
+            &ctx.accounts.system_program,
+            &ctx.accounts.creator.to_account_info(),
+            amount,
+        )?;
+        
+        let pool = &mut ctx.accounts.pool;
+        pool.net_deposited = pool.net_deposited
+            .checked_sub(amount)
+            .ok_or(SipzyError::ReserveAccountingMismatch)?;
+        pool.reserve_sol = 0;
+        
+        emit!(ResidualSwept {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            creator_wallet: pool.creator_wallet,
+            amount,
+        });
+        
+        Ok(())
+    }
+
+    /// Set the pool's trading fee (creator only, before trading)
+    /// Stream pools launched with their parent Creator pool start from its fee.
+    /// Never above fee_ceiling_bps, so a fee lowered with decrease_fee stays
+    /// down even if the supply is later sold back to zero
+    pub fn set_fee_bps(ctx: Context<ManagePool>, fee_bps: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        require!(fee_bps <= MAX_FEE_BPS, SipzyError::InvalidFee);
+        require!(fee_bps <= pool.fee_ceiling_bps, SipzyError::FeeAboveCeiling);
+        pool.fee_bps = fee_bps;
+        
+        emit!(FeeUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            fee_bps,
+        });
+        
+        Ok(())
+    }
+
+    /// Lower the pool's trading fee at any time (creator only)
+    /// A credible commitment for holders: unlike set_fee_bps, which only works
+    /// before trading, this can run once the pool is live, and it also lowers
+    /// fee_ceiling_bps, so set_fee_bps can never raise the fee back
+    pub fn decrease_fee(ctx: Context<ManagePool>, new_fee_bps: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(new_fee_bps < pool.fee_bps, SipzyError::FeeNotDecreased);
+        pool.fee_bps = new_fee_bps;
+        pool.fee_ceiling_bps = new_fee_bps;
+        
+        emit!(FeeUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            fee_bps: new_fee_bps,
+        });
+        
+        Ok(())
+    }
+
+    /// Waive the pool fee on trades of at most `fee_free_threshold` tokens
+    /// (creator only)
+    /// Lets creators subsidise small supporters while still earning on large
+    /// trades. The threshold is per trade, so a large trade split into small
+    /// ones also goes fee-free; pair it with set_rate_limit to bound that.
+    /// Launch surcharges and early-sell penalties still apply. 0 disables it
+    pub fn set_fee_free_threshold(ctx: Context<ManagePool>, fee_free_threshold: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.fee_free_threshold = fee_free_threshold;
+        
+        emit!(FeeFreeThresholdUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            fee_free_threshold,
+        });
+        
+        Ok(())
+    }
+
+    /// Set the buy tax (creator only, before trading)
+    /// Unlike the fee, which leaves the pool, the tax is charged on top of the
+    /// curve cost and kept in reserve_sol without minting anything for it, so
+    /// every buy adds backing and lifts the floor for holders
+    pub fn set_buy_tax(ctx: Context<ManagePool>, buy_tax_bps: u16) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        require!(buy_tax_bps <= MAX_BUY_TAX_BPS, SipzyError::InvalidBuyTax);
+        pool.buy_tax_bps = buy_tax_bps;
+        
+        emit!(BuyTaxUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            buy_tax_bps,
+        });
+        
+        Ok(())
+    }
+
+    /// Set the reserve ratio buys top the pool back up to (creator only)
+    /// After each buy the reserve should cover `min_reserve_ratio_bps` of what
+    /// selling the whole supply would pay out. When it would fall short, the
+    /// shortfall is taken out of that buy's creator fee and deposited into the
+    /// reserve instead, up to the whole fee; the buyer pays the same either way.
+    /// The buy tax already counts towards the reserve. 0 disables the top-up
+    pub fn set_min_reserve_ratio(ctx: Context<ManagePool>, min_reserve_ratio_bps: u16) -> Result<()> {
+        require!(min_reserve_ratio_bps <= 10_000, SipzyError::InvalidReserveRatio);
+        let pool = &mut ctx.accounts.pool;
+        pool.min_reserve_ratio_bps = min_reserve_ratio_bps;
+        
+        emit!(MinReserveRatioUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            min_reserve_ratio_bps,
+        });
+        
+        Ok(())
+    }
+
+    /// Set the share of each sell that is burned (creator only, before trading)
+    /// Sold tokens still leave total_supply, but `burn_on_sell_bps` of them
+    /// are recorded in burned_supply instead of being returned to the curve:
+    /// the seller is refunded only for the rest, and every later trade prices
+    /// from total_supply + burned_supply, so sells push the price up
+    pub fn set_burn_on_sell(ctx: Context<ManagePool>, burn_on_sell_bps: u16) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        require!(burn_on_sell_bps <= MAX_BURN_ON_SELL_BPS, SipzyError::InvalidBurnRate);
+        pool.burn_on_sell_bps = burn_on_sell_bps;
+        
+        emit!(BurnOnSellUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            burn_on_sell_bps,
+        });
+        
+        Ok(())
+    }
+
+    /// Configure a launch-window anti-sniping fee (creator only, before trading)
+    /// Buys pay an extra `snipe_fee_bps` that decays linearly to zero over
+    /// `window_secs` from now, so the earliest buyers fund the creator rather
+    /// than capturing the launch. A zero window disables it
+    pub fn set_snipe_fee(
+        ctx: Context<ManagePool>,
+        snipe_fee_bps: u64,
+        window_secs: u64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        require!(snipe_fee_bps <= MAX_SNIPE_FEE_BPS, SipzyError::InvalidFee);
+        
+        let window = i64::try_from(window_secs).map_err(|_| SipzyError::Overflow)?;
+        pool.snipe_fee_bps = snipe_fee_bps;
+        pool.snipe_window_secs = window_secs;
+        pool.launch_end = Clock::get()?
+            .unix_timestamp
+            .checked_add(window)
+            .ok_or(SipzyError::Overflow)?;
+        
+        emit!(SnipeFeeUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            snipe_fee_bps,
+            window_secs,
+            launch_end: pool.launch_end,
+        });
+        
+        Ok(())
+    }
+
+    /// Configure an early-sell penalty (creator only, before trading)
+    /// Sells pay up to `max_sell_penalty_bps` on top of the fee, decaying
+    /// linearly to zero as the position's holding time reaches `window_secs`.
+    /// Unlike a cooldown, flippers can still exit, just at a cost. A zero
+    /// window disables it
+    pub fn set_sell_penalty(
+        ctx: Context<ManagePool>,
+        max_sell_penalty_bps: u64,
+        window_secs: u64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        require!(max_sell_penalty_bps <= MAX_SELL_PENALTY_BPS, SipzyError::InvalidFee);
+        pool.max_sell_penalty_bps = max_sell_penalty_bps;
+        pool.penalty_window_secs = window_secs;
+        
+        emit!(SellPenaltyUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            max_sell_penalty_bps,
+            window_secs,
+        });
+        
+        Ok(())
+    }
+
+    /// Set when a Stream pool stops accepting buys (0 clears it)
+    /// Stream coins track a single event; once expired, holders can still
+    /// sell out, after which the pool can be closed
+    pub fn set_expiry(ctx: Context<ManagePool>, expires_at: i64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.pool_type == PoolType::Stream, SipzyError::NotStreamPool);
+        require!(
+            expires_at == 0 || expires_at > Clock::get()?.unix_timestamp,
+            SipzyError::InvalidExpiry
+        );
+        pool.expires_at = expires_at;
+        
+        emit!(ExpirySet {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            expires_at,
+        });
+        
+        Ok(())
+    }
+
+    /// Cap the spot price move any single buy may cause (creator only)
+    /// Impact is measured as in simulate_trade's price_impact_bps; a buy past
+    /// `max_price_impact_bps` fails with ImpactTooHigh before any SOL moves,
+    /// protecting every holder from one trade distorting the price. 0 removes
+    /// the cap
+    pub fn set_max_price_impact(ctx: Context<ManagePool>, max_price_impact_bps: u16) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.max_price_impact_bps = max_price_impact_bps;
+        
+        emit!(PriceImpactCapUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            max_price_impact_bps,
+        });
+        
+        Ok(())
+    }
+
+    /// Limit how much SOL buys may spend per `window_secs` (creator only)
+    /// Caps how fast the price can be pumped without halting trading: a buy
+    /// that would take the window's notional past `max_notional_per_window`
+    /// fails with RateLimitExceeded until the window rolls over. Setting the
+    /// limit starts a fresh window; 0 for both disables it
+    pub fn set_rate_limit(
+        ctx: Context<ManagePool>,
+        max_notional_per_window: u64,
+        window_secs: i64,
+    ) -> Result<()> {
+        require!(
+            (max_notional_per_window == 0) == (window_secs == 0) && window_secs >= 0,
+            SipzyError::InvalidInterval
+        );
+        let pool = &mut ctx.accounts.pool;
+        pool.max_notional_per_window = max_notional_per_window;
+        pool.window_secs = window_secs;
+        pool.window_notional = 0;
+        pool.window_start = Clock::get()?.unix_timestamp;
+        
+        emit!(RateLimitUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            max_notional_per_window,
+            window_secs,
+        });
+        
+        Ok(())
+    }
+
+    /// Make each wallet wait `buy_cooldown_secs` between buys (creator only)
+    /// Slows bots hammering a launch without blocking concurrent buyers, as
+    /// the cooldown is tracked per HolderPosition; a buy inside it fails with
+    /// BuyCooldownActive. Sells are unaffected. 0 disables it
+    pub fn set_buy_cooldown(ctx: Context<ManagePool>, buy_cooldown_secs: i64) -> Result<()> {
+        require!(
+            (0..=MAX_BUY_COOLDOWN_SECS).contains(&buy_cooldown_secs),
+            SipzyError::InvalidBuyCooldown
+        );
+        let pool = &mut ctx.accounts.pool;
+        pool.buy_cooldown_secs = buy_cooldown_secs;
+        
+        emit!(BuyCooldownUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            buy_cooldown_secs,
+        });
+        
+        Ok(())
+    }
+
+    /// Set the slippage buy_tokens_auto_slippage allows (creator only)
+    /// Up to MAX_SLIPPAGE_BPS; 0 admits only buys that cost no more than they
+    /// would at the spot price
+    pub fn set_default_slippage(ctx: Context<ManagePool>, default_slippage_bps: u16) -> Result<()> {
+        require!(default_slippage_bps <= MAX_SLIPPAGE_BPS, SipzyError::InvalidSlippage);
+        let pool = &mut ctx.accounts.pool;
+        pool.default_slippage_bps = default_slippage_bps;
+        
+        emit!(DefaultSlippageUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            default_slippage_bps,
+        });
+        
+        Ok(())
+    }
+
+    /// Restrict buys to a whitelist until `whitelist_until` (creator only, before trading)
+    /// The whitelist is a merkle root over sha256(wallet) leaves, stored in the
+    /// pool's Whitelist PDA so large presale lists cost one hash on-chain.
+    /// Before the deadline a wallet must prove membership once with
+    /// prove_whitelisted or its buys fail with NotWhitelisted; after it buying
+    /// is open to everyone. Sells are unaffected
+    pub fn set_whitelist(
+        ctx: Context<SetWhitelist>,
+        merkle_root: [u8; 32],
+        whitelist_until: i64,
+    ) -> Result<()> {
+        require!(ctx.accounts.pool.total_supply == 0, SipzyError::SupplyNotZero);
+        
+        let whitelist = &mut ctx.accounts.whitelist;
+        whitelist.pool = ctx.accounts.pool.key();
+        whitelist.merkle_root = merkle_root;
+        whitelist.bump = ctx.bumps.whitelist;
+        
+        let pool = &mut ctx.accounts.pool;
+        pool.whitelist_until = whitelist_until;
+        
+        emit!(WhitelistUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            merkle_root,
+            whitelist_until,
+        });
+        
+        Ok(())
+    }
+
+    /// Prove the trader is on the pool's whitelist (trader signs)
+    /// `proof` is the sibling path from sha256(trader) to the merkle root, with
+    /// each pair hashed in sorted order. Success marks the trader's
+    /// HolderPosition (created if needed), so later buys need no proof
+    pub fn prove_whitelisted(ctx: Context<ProveWhitelisted>, proof: Vec<[u8; 32]>) -> Result<()> {
+        require!(proof.len() <= MAX_MERKLE_PROOF_LEN, SipzyError::NotWhitelisted);
+        let leaf = hashv(&[ctx.accounts.trader.key().as_ref()]).to_bytes();
+        require!(
+            verify_merkle_proof(&ctx.accounts.whitelist.merkle_root, leaf, &proof),
+            SipzyError::NotWhitelisted
+        );
+        
+        let position = &mut ctx.accounts.position;
+        open_position(
+            position,
+            ctx.accounts.pool.key(),
+            ctx.accounts.trader.key(),
+            ctx.bumps.position,
+        );
+        position.whitelisted = true;
+        
+        Ok(())
+    }
+
+    /// Accept sells only between `sells_open_from` and `sells_open_until` (creator only, before trading)
+    /// For timed events such as "redemption only in the final hour of the
+    /// stream"; a sell outside the window fails with SellWindowClosed while
+    /// buys carry on. Either bound may be 0 for no limit on that side. Fixed
+    /// before trading since it decides when holders can exit
+    pub fn set_sell_window(
+        ctx: Context<ManagePool>,
+        sells_open_from: i64,
+        sells_open_until: i64,
+    ) -> Result<()> {
+        require!(
+            sells_open_until == 0 || sells_open_from < sells_open_until,
+            SipzyError::InvalidSellWindow
+        );
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        pool.sells_open_from = sells_open_from;
+        pool.sells_open_until = sells_open_until;
+        
+        emit!(SellWindowUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            sells_open_from,
+            sells_open_until,
+        });
+        
+        Ok(())
+    }
+
+    /// Turn the per-trade TokensTraded event on or off (creator only)
+    /// A log-cost lever for high-frequency pools without real-time
+    /// subscribers: with events off, trades only update state and indexers
+    /// poll the pool instead. Lifecycle events such as PoolCreated,
+    /// PoolGraduated and PoolClosed are emitted either way
+    pub fn set_emit_events(ctx: Context<ManagePool>, emit_events: bool) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.emit_events = emit_events;
+        
+        emit!(EmitEventsUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            emit_events,
+        });
+        
+        Ok(())
+    }
+
+    /// Halt buys and sells for `secs` seconds, after which trading resumes on
+    /// its own (creator only)
+    /// A circuit breaker for volatile moments, e.g. around a live stream. A new
+    /// pause replaces the current one, so 0 lifts it early; at most 1 day
+    pub fn pause_trading_for(ctx: Context<ManagePool>, secs: i64) -> Result<()> {
+        require!(
+            (0..=MAX_PAUSE_SECS).contains(&secs),
+            SipzyError::InvalidPauseDuration
+        );
+        let pool = &mut ctx.accounts.pool;
+        let now = Clock::get()?.unix_timestamp;
+        pool.pause_until = now + secs;
+        
+        emit!(TradingPauseSet {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            paused_at: now,
+            pause_until: pool.pause_until,
+        });
+        
+        Ok(())
+    }
+
+    /// Price the pool in an SPL token such as USDC instead of SOL (creator only, before trading)
+    /// Creates the quote reserve token account at ["quote_reserve", pool],
+    /// owned by itself. From then on buys and sells move quote tokens between
+    /// it and the trader's and creator's token accounts with the curve math
+    /// unchanged, just in the token's units. Trades must pass quote_reserve,
+    /// trader_quote_account, creator_quote_account and token_program.
+    /// Reserve paths that only handle lamports (reserve vault, buyback,
+    /// redemption, sweeps, insurance, emergency withdrawal, basket sells)
+    /// reject such pools with QuoteMintUnsupported. One-way.
+    pub fn set_quote_mint(ctx: Context<SetQuoteMint>) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        require!(pool.quote_mint.is_none(), SipzyError::QuoteMintAlreadySet);
+        require!(!pool.reserve_vault_enabled, SipzyError::QuoteMintUnsupported);
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        require!(pool.reserve_sol == 0, SipzyError::QuoteMintUnsupported);
+        
+        let quote_mint = ctx.accounts.quote_mint.key();
+        let pool = &mut ctx.accounts.pool;
+        pool.quote_mint = Some(quote_mint);
+        pool.quote_reserve_bump = ctx.bumps.quote_reserve;
+        
+        emit!(QuoteMintSet {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            quote_mint,
+            quote_reserve: ctx.accounts.quote_reserve.key(),
+        });
+        
+        Ok(())
+    }
+
+    /// Hold the pool's reserve in a dedicated vault PDA (creator only, before trading)
+    /// The vault is a system account at ["reserve", pool], so the pool account
+    /// only carries state: buys deposit into the vault and sells and every
+    /// other payout draw from it, and those instructions must pass it as
+    /// `reserve_vault`. The creator funds its rent-exempt minimum, which stays
+    /// outside reserve_sol; any reserve already held moves across. One-way.
+    pub fn enable_reserve_vault(ctx: Context<EnableReserveVault>) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        require!(!pool.reserve_vault_enabled, SipzyError::ReserveVaultEnabled);
+        require!(pool.quote_mint.is_none(), SipzyError::QuoteMintUnsupported);
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        
+        let vault_info = ctx.accounts.reserve_vault.to_account_info();
+        let rent_top_up = Rent::get()?.minimum_balance(0).saturating_sub(vault_info.lamports());
+        if rent_top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.creator.to_account_info(),
+                        to: vault_info.clone(),
+                    },
+                ),
+                rent_top_up,
+            )?;
+        }
+        transfer_from_pool(&ctx.accounts.pool.to_account_info(), &vault_info, pool.reserve_sol)?;
+        
+        let pool = &mut ctx.accounts.pool;
+        pool.reserve_vault_enabled = true;
+        pool.reserve_vault_bump = ctx.bumps.reserve_vault;
+        
+        emit!(ReserveVaultEnabled {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            reserve_vault: vault_info.key(),
+            moved_reserve: pool.reserve_sol,
+        });
+        
+        Ok(())
+    }
+
+    /// Price sells against the reserve when the curve would drain it
+    /// A sell whose curve refund exceeds RESERVE_BACKED_SELL_BPS of the reserve
+    /// is paid `reserve_sol × amount / total_supply` instead, so every holder can
+    /// always exit. Fixed before trading since it changes what holders get back
+    pub fn set_reserve_backed_sells(ctx: Context<ManagePool>, enabled: bool) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        pool.reserve_backed_sells = enabled;
+        
+        emit!(ReserveBackedSellsUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            enabled,
+        });
+        
+        Ok(())
+    }
+
+    /// Opt the pool into compliance controls (authority only)
+    /// Can only be switched on before trading so buyers know up front that
+    /// positions may be frozen; switching it off lifts all freezes
+    pub fn set_compliance_enabled(ctx: Context<AuthorityManagePool>, enabled: bool) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        if enabled {
+            require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        }
+        pool.compliance_enabled = enabled;
+        
+        emit!(ComplianceUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            enabled,
+        });
+        
+        Ok(())
+    }
+
+    /// Freeze a holder's position so it can neither buy nor sell (authority only)
+    /// Creates the position if the holder has none yet, so an address can be
+    /// blocked before its first buy
+    pub fn freeze_position(ctx: Context<FreezePosition>, holder: Pubkey) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+        open_position(position, ctx.accounts.pool.key(), holder, ctx.bumps.position);
+        position.frozen = true;
+        
+        emit!(PositionFrozen {
+            pool: ctx.accounts.pool.key(),
+            seq: next_seq(&mut ctx.accounts.pool),
+            holder,
+        });
+        
+        Ok(())
+    }
+
+    /// Lift a freeze placed by freeze_position (authority only)
+    pub fn unfreeze_position(ctx: Context<FreezePosition>, holder: Pubkey) -> Result<()> {
+        ctx.accounts.position.frozen = false;
+        
+        emit!(PositionUnfrozen {
+            pool: ctx.accounts.pool.key(),
+            seq: next_seq(&mut ctx.accounts.pool),
+            holder,
+        });
+        
+        Ok(())
+    }
+
+    /// Set the loyalty fee-discount tiers for sells (creator only)
+    /// A holder who has held for at least `min_hold_secs` (amount-weighted) pays
+    /// `discount_bps` less of the sell fee, e.g. 30 days → 5000 halves it.
+    /// Unused entries are left as zero
+    pub fn set_loyalty_tiers(
+        ctx: Context<ManagePool>,
+        tiers: [LoyaltyTier; MAX_LOYALTY_TIERS],
+    ) -> Result<()> {
+        require!(
+            tiers.iter().all(|tier| tier.discount_bps <= 10000),
+            SipzyError::InvalidFeeDiscount
+        );
+        
+        let pool = &mut ctx.accounts.pool;
+        pool.loyalty_tiers = tiers;
+        
+        emit!(LoyaltyTiersUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            tiers,
+        });
+        
+        Ok(())
+    }
+
+    /// Block sells in the same slot as the holder's last buy (creator only)
+    /// Closes off atomic buy-then-sell arbitrage against the curve without
+    /// imposing a longer cooldown
+    pub fn set_same_slot_sell_block(ctx: Context<ManagePool>, enabled: bool) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.same_slot_sell_block = enabled;
+        
+        emit!(SameSlotSellBlockUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            enabled,
+        });
+        
+        Ok(())
+    }
+
+    /// Set the maximum SOL the bonding-curve reserve may hold (creator only)
+    /// A cap of 0 removes the limit. Buys that would push the reserve past
+    /// the cap are rejected; sells are unaffected.
+    pub fn set_reserve_cap(ctx: Context<ManagePool>, reserve_cap: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.reserve_cap = reserve_cap;
+        
+        emit!(ReserveCapUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            reserve_cap,
+        });
+        
+        Ok(())
+    }
+
+    /// Set token decimals (creator only, before any tokens are in circulation)
+    /// Amounts and supply are then counted in base units of 10^-decimals tokens,
+    /// so fractional tokens can be bought while the curve is still priced per whole token
+    pub fn set_decimals(ctx: Context<ManagePool>, decimals: u8) -> Result<()> {
+        require!(decimals <= MAX_DECIMALS, SipzyError::InvalidDecimals);
+        
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        pool.decimals = decimals;
+        
+        emit!(DecimalsUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            decimals,
+        });
+        
+        Ok(())
+    }
+
+    /// Set the spot price tick size (creator only, before any tokens are in circulation)
+    /// Spot prices are rounded down to a multiple of tick_size; for Stream pools
+    /// every per-token price in the integral is rounded too, so buys and sells
+    /// see the same ticked curve. Ticked Stream trades are limited to 100 whole
+    /// tokens each. A tick size of 0 keeps exact pricing.
+    pub fn set_tick_size(ctx: Context<ManagePool>, tick_size: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        // A tick above the base price would round early tokens down to zero
+        require!(tick_size <= pool.base_price, SipzyError::InvalidTickSize);
+        pool.tick_size = tick_size;
+        
+        emit!(TickSizeUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            tick_size,
+        });
+        
+        Ok(())
+    }
+
+    /// Set the exponential curve's fixed-point precision (creator only, Stream
+    /// pools, before any tokens are in circulation)
+    /// 10^9 rounds growth rates of a few bps noticeably; 10^12 or 10^15 price
+    /// them more finely. The instruction count is the same in every mode, but
+    /// intermediates carry 3 or 6 more digits, so the supply and base price a
+    /// pool can reach before the math overflows shrink accordingly
+    pub fn set_exp_precision(ctx: Context<ManagePool>, precision_exp: u8) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.pool_type == PoolType::Stream, SipzyError::NotStreamPool);
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        exp_precision(precision_exp)?;
+        pool.precision_exp = precision_exp;
+        
+        emit!(ExpPrecisionUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            precision_exp,
+        });
+        
+        Ok(())
+    }
+
+    /// Flatten a Stream pool's curve at `price_ceiling` (creator only, before
+    /// any tokens are in circulation)
+    /// The price grows exponentially until it reaches the ceiling and then
+    /// stays there for every further token, so hot pools remain tradeable
+    /// instead of overflowing. Must be at least base_price; 0 removes it
+    pub fn set_price_ceiling(ctx: Context<ManagePool>, price_ceiling: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.pool_type == PoolType::Stream, SipzyError::NotStreamPool);
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        require!(
+            price_ceiling == 0 || price_ceiling >= pool.base_price,
+            SipzyError::InvalidPriceCeiling
+        );
+        pool.price_ceiling = price_ceiling;
+        
+        emit!(PriceCeilingUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            price_ceiling,
+        });
+        
+        Ok(())
+    }
+
+    /// Switch a pool created with the wrong type (creator only, before any
+    /// tokens are in circulation)
+    /// Nobody holds tokens yet, so nobody is repriced. `new_curve_param` must
+    /// fit the new type. A base price still at the old type's default moves to
+    /// the new type's default; a custom one is kept. The PDA is unchanged, so
+    /// the pool keeps its address, and the creator's registry follows the type:
+    /// a pool leaving Stream drops out of its channel's stream list, while one
+    /// becoming a Stream pool has no parent channel to be listed under
+    pub fn convert_pool_type(
+        ctx: Context<ConvertPoolType>,
+        new_type: PoolType,
+        new_curve_param: u64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        require!(new_type != pool.pool_type, SipzyError::PoolTypeUnchanged);
+        check_curve_param(new_type, new_curve_param)?;
+        
+        let previous_type = pool.pool_type;
+        let (old_default, new_default) = match new_type {
+            PoolType::Stream => (DEFAULT_CREATOR_BASE_PRICE, DEFAULT_STREAM_BASE_PRICE),
+            PoolType::Creator => (DEFAULT_STREAM_BASE_PRICE, DEFAULT_CREATOR_BASE_PRICE),
+        };
+        if pool.base_price == old_default {
+            pool.base_price = new_default;
+        }
+        pool.pool_type = new_type;
+        pool.curve_param = new_curve_param;
+        
+        if previous_type == PoolType::Stream {
+            // The ceiling only flattens the exponential curve
+            pool.price_ceiling = 0;
+            pool.parent_identifier = String::new();
+            ctx.accounts.registry.streams.retain(|stream| stream.pool != pool.key());
+        }
+        if let Some(stats) = ctx.accounts.global_stats.as_deref_mut() {
+            record_pool_type_change(stats, previous_type, new_type);
+        }
+        
+        emit!(PoolTypeConverted {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            previous_type,
+            new_type,
+            base_price: pool.base_price,
+            curve_param: new_curve_param,
+        });
+        
+        Ok(())
+    }
+
+    /// Cap the token supply (creator only, before any tokens are in circulation)
+    /// A max supply of 0 leaves the supply uncapped. Once trading starts the
+    /// cap can only be raised, with increase_max_supply
+    pub fn set_max_supply(ctx: Context<ManagePool>, max_supply: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.total_supply == 0, SipzyError::SupplyNotZero);
+        pool.max_supply = max_supply;
+        
+        emit!(MaxSupplyUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            max_supply,
+        });
+        
+        Ok(())
+    }
+
+    /// Raise a capped supply, e.g. for a sequel drop (creator only)
+    /// The cap never moves down, so holders can rely on the scarcity it promised
+    pub fn increase_max_supply(ctx: Context<ManagePool>, new_max: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        // An uncapped pool has nothing to increase; capping it now would shrink it
+        require!(
+            pool.max_supply > 0 && new_max > pool.max_supply && new_max >= pool.total_supply,
+            SipzyError::InvalidMaxSupply
+        );
+        pool.max_supply = new_max;
+        
+        emit!(MaxSupplyUpdated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            max_supply: new_max,
+        });
+        
+        Ok(())
+    }
+
+    // ========================================================================
+    // GLOBAL CONFIG - Program-wide settings
+    // ========================================================================
+
+    /// Create the program-wide config (program upgrade authority only)
+    /// The signer becomes the config admin
+    pub fn initialize_global_config(ctx: Context<InitializeGlobalConfig>, oracle: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        config.admin = ctx.accounts.admin.key();
+        config.oracle = oracle;
+        config.paused = false;
+        config.bump = ctx.bumps.global_config;
+        
+        emit!(GlobalConfigUpdated {
+            admin: config.admin,
+            oracle,
+        });
+        
+        Ok(())
+    }
+
+    /// Replace the trusted attestation oracle (config admin only)
+    pub fn set_oracle(ctx: Context<UpdateGlobalConfig>, oracle: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        config.oracle = oracle;
+        
+        emit!(GlobalConfigUpdated {
+            admin: config.admin,
+            oracle,
+        });
+        
+        Ok(())
+    }
+
+    /// Enter or leave the program-wide emergency state (config admin only)
+    /// While paused no pool can be traded or reactivated, and the admin may
+    /// rescue pool reserves with emergency_withdraw_reserve
+    pub fn set_global_pause(ctx: Context<UpdateGlobalConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.global_config.paused = paused;
+        
+        emit!(GlobalPauseSet { paused });
+        
+        Ok(())
+    }
+
+    /// Move reserve lamports out of a pool to a recovery address (config admin only)
+    /// For a discovered exploit: the admin pauses globally with set_global_pause,
+    /// then rescues each affected pool's reserve for redistribution to holders.
+    ///
+    /// Trust assumptions: this lets the config admin (initially the program's
+    /// upgrade authority) move any pool's reserve to any address, so
+    /// holders trust that key exactly as they trust the upgrade authority. It is
+    /// only usable while globally paused, deactivates the pool so no trade runs
+    /// against the reduced reserve, accumulates the amount in
+    /// pool.emergency_withdrawn and emits EmergencyWithdrawal for every move.
+    /// The pool stays inactive until the admin calls clear_emergency_withdrawal
+    pub fn emergency_withdraw_reserve(ctx: Context<EmergencyWithdrawReserve>, amount: u64) -> Result<()> {
+        require!(ctx.accounts.global_config.paused, SipzyError::NotGloballyPaused);
+        require!(amount > 0, SipzyError::InvalidAmount);
+        
+        let pool = &ctx.accounts.pool;
+        require!(amount <= pool.reserve_sol, SipzyError::InsufficientReserve);
+        
+        pay_from_reserve(
+            &ctx.accounts.pool,
+            ctx.accounts.reserve_vault.as_ref(),
+            &ctx.accounts.system_program,
+            &ctx.accounts.destination.to_account_info(),
+            amount,
+        )?;
+        
+        let pool = &mut ctx.accounts.pool;
+        pool.reserve_sol -= amount;
+        pool.net_deposited = pool.net_deposited
+            .checked_sub(amount)
+            .ok_or(SipzyError::ReserveAccountingMismatch)?;
+        pool.emergency_withdrawn = pool.emergency_withdrawn.saturating_add(amount);
+        if pool.is_active {
+            set_pool_active(pool, false);
+        }
+        
+        emit!(EmergencyWithdrawal {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            admin: ctx.accounts.admin.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            remaining_reserve: pool.reserve_sol,
+            total_withdrawn: pool.emergency_withdrawn,
+        });
+        
+        Ok(())
+    }
+
+    /// Let a rescued pool be reactivated again (config admin only)
+    /// Called once the rescued reserve has been returned to holders; resets
+    /// pool.emergency_withdrawn so the creator can call reactivate_pool
+    pub fn clear_emergency_withdrawal(ctx: Context<ClearEmergencyWithdrawal>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let cleared = pool.emergency_withdrawn;
+        pool.emergency_withdrawn = 0;
+        
+        emit!(EmergencyWithdrawalCleared {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            admin: ctx.accounts.admin.key(),
+            cleared,
+        });
+        
+        Ok(())
+    }
+
+    /// Snapshot the pool's holders for an airdrop of `reward` lamports (creator only)
+    /// Records total_supply and the time under the next snapshot ID and
+    /// escrows the reward in the HolderSnapshot PDA. Holders then claim
+    /// their pro-rata share with claim_airdrop for `claim_window_secs`; what
+    /// is left unclaimed returns to the creator via close_holder_snapshot.
+    /// A new snapshot waits for the previous claim window to end
+    pub fn snapshot_holders(
+        ctx: Context<SnapshotHolders>,
+        reward: u64,
+        claim_window_secs: i64,
+    ) -> Result<()> {
+        require!(reward > 0, SipzyError::InvalidAmount);
+        require!(
+            (1..=MAX_AIRDROP_CLAIM_WINDOW_SECS).contains(&claim_window_secs),
+            SipzyError::InvalidClaimWindow
+        );
+        
+        let clock = Clock::get()?;
+        let pool = &ctx.accounts.pool;
+        require!(pool.total_supply > 0, SipzyError::NoHolders);
+        require!(
+            clock.unix_timestamp > pool.airdrop_claim_until,
+            SipzyError::AirdropClaimOpen
+        );
+        
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.creator.to_account_info(),
+                    to: ctx.accounts.snapshot.to_account_info(),
+                },
+            ),
+            reward,
+        )?;
+        
+        let claim_until = clock.unix_timestamp.saturating_add(claim_window_secs);
+        let pool = &mut ctx.accounts.pool;
+        pool.holder_snapshot_count += 1;
+        pool.airdrop_claim_until = claim_until;
+        
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.pool = pool.key();
+        snapshot.snapshot_id = pool.holder_snapshot_count;
+        snapshot.total_supply = pool.total_supply;
+        snapshot.taken_at = clock.unix_timestamp;
+        snapshot.taken_slot = clock.slot;
+        snapshot.claim_until = claim_until;
+        snapshot.reward = reward;
+        snapshot.claimed = 0;
+        snapshot.bump = ctx.bumps.snapshot;
+        
+        emit!(HolderSnapshotTaken {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            snapshot_id: snapshot.snapshot_id,
+            total_supply: snapshot.total_supply,
+            reward,
+            claim_until,
+        });
+        
+        Ok(())
+    }
+
+    /// Claim a holder snapshot's airdrop: reward × balance / snapshot total_supply
+    /// The holder registers their balance themselves within the claim window.
+    /// Balances only grow through buys, so a position that hasn't bought since
+    /// the snapshot's slot holds at most what it held then; one that has
+    /// can't claim (BoughtAfterSnapshot). Each position claims once
+    pub fn claim_airdrop(ctx: Context<ClaimAirdrop>, snapshot_id: u64) -> Result<()> {
+        let snapshot = &ctx.accounts.snapshot;
+        let position = &ctx.accounts.position;
+        require!(
+            Clock::get()?.unix_timestamp <= snapshot.claim_until,
+            SipzyError::AirdropClaimClosed
+        );
+        require!(snapshot_id > position.last_airdrop_claimed, SipzyError::AirdropAlreadyClaimed);
+        ensure_not_frozen(&ctx.accounts.pool, position)?;
+        let balance = snapshot_balance(position, snapshot)?;
+        
+        let amount = u64::try_from(
+            snapshot.reward as u128 * balance as u128 / snapshot.total_supply as u128,
+        )
+        .map_err(|_| SipzyError::Overflow)?;
+        transfer_from_pool(
+            &ctx.accounts.snapshot.to_account_info(),
+            &ctx.accounts.holder.to_account_info(),
+            amount,
+        )?;
+        
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.claimed = snapshot.claimed.checked_add(amount).ok_or(SipzyError::Overflow)?;
+        ctx.accounts.position.last_airdrop_claimed = snapshot_id;
+        
+        let pool = &mut ctx.accounts.pool;
+        emit!(AirdropClaimed {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            snapshot_id,
+            holder: ctx.accounts.holder.key(),
+            balance,
+            amount,
+        });
+        
+        Ok(())
+    }
+
+    /// Close a holder snapshot once its claim window has ended (creator only)
+    /// The unclaimed reward and the account's rent return to the creator
+    pub fn close_holder_snapshot(ctx: Context<CloseHolderSnapshot>, snapshot_id: u64) -> Result<()> {
+        let snapshot = &ctx.accounts.snapshot;
+        require!(
+            Clock::get()?.unix_timestamp > snapshot.claim_until,
+            SipzyError::AirdropClaimOpen
+        );
+        let unclaimed = snapshot.reward - snapshot.claimed;
+        
+        let pool = &mut ctx.accounts.pool;
+        emit!(HolderSnapshotClosed {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            snapshot_id,
+            unclaimed,
+        });
+        
+        Ok(())
+    }
+
+    /// Create the protocol-wide insurance fund (config admin only)
+    /// Every SOL trade from then on pays it `insurance_fee_bps` of its creator
+    /// fee; claim_insurance spends it on undercollateralized pools
+    pub fn initialize_insurance_fund(
+        ctx: Context<InitializeInsuranceFund>,
+        insurance_fee_bps: u16,
+    ) -> Result<()> {
+        require!(insurance_fee_bps <= 10_000, SipzyError::InvalidInsuranceFee);
+        
+        let fund = &mut ctx.accounts.insurance_fund;
+        fund.insurance_fee_bps = insurance_fee_bps;
+        fund.total_contributed = 0;
+        fund.total_claimed = 0;
+        fund.bump = ctx.bumps.insurance_fund;
+        
+        emit!(InsuranceFeeUpdated { insurance_fee_bps });
+        
+        Ok(())
+    }
+
+    /// Change the insurance fund's share of creator fees (config admin only)
+    pub fn set_insurance_fee(ctx: Context<UpdateInsuranceFund>, insurance_fee_bps: u16) -> Result<()> {
+        require!(insurance_fee_bps <= 10_000, SipzyError::InvalidInsuranceFee);
+        ctx.accounts.insurance_fund.insurance_fee_bps = insurance_fee_bps;
+        
+        emit!(InsuranceFeeUpdated { insurance_fee_bps });
+        
+        Ok(())
+    }
+
+    /// Top up an undercollateralized pool's reserve from the insurance fund (config admin only)
+    /// `shortfall` may not exceed the pool's provable gap, the sell value of its
+    /// whole supply less reserve_debt minus reserve_sol (see
+    /// get_circulating_vs_reserve_imbalance), so unpaid founder tokens are never
+    /// funded, nor what the fund holds above its rent-exempt minimum
+    pub fn claim_insurance(ctx: Context<ClaimInsurance>, shortfall: u64) -> Result<()> {
+        require!(shortfall > 0, SipzyError::InvalidAmount);
+        
+        let pool = &ctx.accounts.pool;
+        let liability = backed_liability(pool)?;
+        require!(
+            shortfall <= liability.saturating_sub(pool.reserve_sol),
+            SipzyError::ClaimExceedsShortfall
+        );
+        
+        let fund_info = ctx.accounts.insurance_fund.to_account_info();
+        let available = fund_info
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(fund_info.data_len()));
+        require!(shortfall <= available, SipzyError::InsufficientInsuranceFund);
+        
+        transfer_from_pool(
+            &fund_info,
+            &reserve_account(pool, ctx.accounts.reserve_vault.as_ref())?,
+            shortfall,
+        )?;
+        
+        let fund = &mut ctx.accounts.insurance_fund;
+        fund.total_claimed = fund.total_claimed.saturating_add(shortfall);
+        
+        let pool = &mut ctx.accounts.pool;
+        pool.reserve_sol = pool.reserve_sol.checked_add(shortfall).ok_or(SipzyError::Overflow)?;
+        pool.net_deposited = pool.net_deposited.checked_add(shortfall).ok_or(SipzyError::Overflow)?;
+        
+        emit!(InsuranceClaimed {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            amount: shortfall,
+            new_reserve: pool.reserve_sol,
+            total_claimed: fund.total_claimed,
+        });
+        
+        Ok(())
+    }
+
+    /// Create the protocol-wide pool counters (config admin only)
+    /// Inits, closes and trades that pass the account keep it current; pools
+    /// priced in an SPL quote token are counted but add nothing to total_reserve
+    pub fn initialize_global_stats(ctx: Context<InitializeGlobalStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.global_stats;
+        stats.creator_pools = 0;
+        stats.stream_pools = 0;
+        stats.total_reserve = 0;
+        stats.bump = ctx.bumps.global_stats;
+        
+        Ok(())
+    }
+
+    /// Mark a pool as verified by the oracle's ownership attestation (anyone)
+    /// The preceding instruction must be an Ed25519 program verification of the
+    /// oracle's signature over OWNERSHIP_ATTESTATION_PREFIX || identifier ||
+    /// creator_wallet. Pool creation stays permissionless; UIs can warn on
+    /// unverified pools to blunt identifier squatting
+    pub fn verify_pool(ctx: Context<VerifyPool>) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let message = [
+            OWNERSHIP_ATTESTATION_PREFIX,
+            pool.identifier.as_bytes(),
+            pool.creator_wallet.as_ref(),
+        ]
+        .concat();
+        verify_oracle_signature(
+            &ctx.accounts.instructions.to_account_info(),
+            &ctx.accounts.global_config.oracle,
+            &message,
+        )?;
+        
+        let pool = &mut ctx.accounts.pool;
+        pool.verified = true;
+        
+        emit!(PoolVerified {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            identifier: pool.identifier.clone(),
+            creator_wallet: pool.creator_wallet,
+        });
+        
+        Ok(())
+    }
+
+    // ========================================================================
+    // MULTISIG - Team-controlled pools
+    // ========================================================================
+
+    /// Create a multisig that can act as a pool's creator wallet
+    /// Pass the multisig PDA as `creator_wallet` when initializing a pool; its
+    /// creator actions then go through propose_action / approve_proposal /
+    /// execute_proposal and need `threshold` of `signers` to approve. Any
+    /// creator-signed instruction (fees, launch settings, wallet rotation,
+    /// close) runs through an Invoke proposal
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(
+            !signers.is_empty()
+                && signers.len() <= MAX_MULTISIG_SIGNERS
+                && threshold >= 1
+                && threshold as usize <= signers.len(),
+            SipzyError::InvalidMultisig
+        );
+        for (i, signer) in signers.iter().enumerate() {
+            require!(!signers[..i].contains(signer), SipzyError::InvalidMultisig);
+        }
+        
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.base = ctx.accounts.base.key();
+        multisig.signers = signers;
+        multisig.threshold = threshold;
+        multisig.proposal_count = 0;
+        multisig.bump = ctx.bumps.multisig;
+        
+        emit!(MultisigCreated {
+            multisig: multisig.key(),
+            signers: multisig.signers.clone(),
+            threshold,
+        });
+        
+        Ok(())
+    }
+
+    /// Propose a creator action on a multisig-controlled pool (multisig signer only)
+    /// The proposer's approval is recorded with the proposal
+    pub fn propose_action(ctx: Context<ProposeAction>, action: ProposalAction) -> Result<()> {
+        match &action {
+            ProposalAction::UpdateMetadataUri { metadata_uri } => {
+                require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, SipzyError::MetadataUriTooLong);
+            }
+            ProposalAction::Invoke { data, accounts } => {
+                require!(
+                    data.len() <= MAX_PROPOSAL_DATA_LEN && accounts.len() <= MAX_PROPOSAL_ACCOUNTS,
+                    SipzyError::ProposalTooLarge
+                );
+            }
+            _ => {}
+        }
+        
+        let multisig = &mut ctx.accounts.multisig;
+        let signer_index = multisig_signer_index(multisig, &ctx.accounts.proposer.key())?;
+        
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.multisig = multisig.key();
+        proposal.pool = ctx.accounts.pool.key();
+        proposal.index = multisig.proposal_count;
+        proposal.action = action;
+        proposal.approvals = 1 << signer_index;
+        proposal.executed = false;
+        proposal.bump = ctx.bumps.proposal;
+        
+        multisig.proposal_count = multisig.proposal_count
+            .checked_add(1)
+            .ok_or(SipzyError::Overflow)?;
+        
+        emit!(ActionProposed {
+            multisig: proposal.multisig,
+            proposal: proposal.key(),
+            pool: proposal.pool,
+            index: proposal.index,
+            proposer: ctx.accounts.proposer.key(),
+        });
+        
+        Ok(())
+    }
+
+    /// Approve a pending proposal (multisig signer only, idempotent)
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        let signer_index = multisig_signer_index(&ctx.accounts.multisig, &ctx.accounts.signer.key())?;
+        
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.executed, SipzyError::ProposalAlreadyExecuted);
+        proposal.approvals |= 1 << signer_index;
+        
+        emit!(ProposalApproved {
+            proposal: proposal.key(),
+            signer: ctx.accounts.signer.key(),
+            approvals: proposal.approvals.count_ones() as u8,
+        });
+        
+        Ok(())
+    }
+
+    /// Execute a proposal that has reached the multisig's threshold (anyone)
+    /// The executor pays any extra rent a metadata update needs and receives
+    /// the refund when the URI shrinks. An Invoke proposal calls back into
+    /// this program with the multisig PDA signing as the creator;
+    /// remaining_accounts must hold every account it lists
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
+    ) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(!proposal.executed, SipzyError::ProposalAlreadyExecuted);
+        require!(
+            proposal.approvals.count_ones() >= ctx.accounts.multisig.threshold as u32,
+            SipzyError::ThresholdNotMet
+        );
+        
+        match proposal.action.clone() {
+            ProposalAction::Deactivate => set_pool_active(&mut ctx.accounts.pool, false),
+            ProposalAction::Reactivate => {
+                ensure_can_reactivate(&ctx.accounts.pool, &ctx.accounts.global_config)?;
+                set_pool_active(&mut ctx.accounts.pool, true);
+            }
+            ProposalAction::UpdateMetadataUri { metadata_uri } => set_metadata_uri(
+                &mut ctx.accounts.pool,
+                &ctx.accounts.executor.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                metadata_uri,
+            )?,
+            ProposalAction::Withdraw { destination, amount } => {
+                // Fees earned by the pool accrue to the multisig account
+                let dest = ctx.accounts.destination
+                    .as_ref()
+                    .filter(|dest| dest.key() == destination)
+                    .ok_or(SipzyError::InvalidDestination)?;
+                let multisig_info = ctx.accounts.multisig.to_account_info();
+                let rent_minimum = Rent::get()?.minimum_balance(multisig_info.data_len());
+                require!(
+                    multisig_info.lamports().saturating_sub(amount) >= rent_minimum,
+                    SipzyError::InsufficientMultisigBalance
+                );
+                transfer_from_pool(&multisig_info, &dest.to_account_info(), amount)?;
+            }
+            ProposalAction::Invoke { data, accounts } => {
+                let instruction = Instruction {
+                    program_id: crate::ID,
+                    accounts: accounts.iter().map(ProposalAccount::to_meta).collect(),
+                    data,
+                };
+                let multisig = &ctx.accounts.multisig;
+                let bump = [multisig.bump];
+                let seeds: &[&[u8]] = &[b"multisig", multisig.base.as_ref(), &bump];
+                let mut account_infos = ctx.remaining_accounts.to_vec();
+                account_infos.push(multisig.to_account_info());
+                account_infos.push(ctx.accounts.pool.to_account_info());
+                account_infos.push(ctx.accounts.executor.to_account_info());
+                invoke_signed(&instruction, &account_infos, &[seeds])?;
+                
+                // Pick up what the instruction wrote so exit doesn't overwrite
+                // it; a pool it closed is no longer ours to reload
+                ctx.accounts.multisig.reload()?;
+                if ctx.accounts.pool.to_account_info().owner == &crate::ID {
+                    ctx.accounts.pool.reload()?;
+                }
+            }
+        }
+        
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.executed = true;
+        
+        emit!(ProposalExecuted {
+            proposal: proposal.key(),
+            pool: proposal.pool,
+            executor: ctx.accounts.executor.key(),
+        });
+        
+        Ok(())
+    }
+
+    // ========================================================================
+    // LEGACY SUPPORT - Keep backward compatibility with existing pools
+    // ========================================================================
+
+    /// Legacy initialize_pool (maps to Creator pool with youtube_id as channel)
+    /// Always generation 0, with its own accounts context so the legacy
+    /// instruction data still deserializes. `creator_wallet` becomes
+    /// pool.creator_wallet and must match the creator_wallet account, which
+    /// keys the registry
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        youtube_id: String,
+        creator_wallet: Pubkey,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.creator_wallet.key(),
+            creator_wallet,
+            SipzyError::InvalidCreatorWallet
+        );
+        
+        create_creator_pool(
+            &mut ctx.accounts.pool,
+            &mut ctx.accounts.registry,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            ctx.accounts.global_stats.as_deref_mut().map(|stats| &mut **stats),
+            CreatorPoolInit {
+                channel_id: youtube_id.clone(),
+                channel_name: youtube_id, // Use ID as name for legacy
+                metadata_uri: String::new(), // No metadata URI
+                base_price: None,
+                slope: None,
+                generation: 0,
+                creator_wallet,
+                authority: ctx.accounts.authority.key(),
+                pool_bump: ctx.bumps.pool,
+                registry_bump: ctx.bumps.registry,
+            },
+        )
+    }
+
+    /// Backfill the display name and metadata URI of a pool created with
+    /// initialize_pool (creator only)
+    /// Only while both still hold their legacy defaults (display_name equal to
+    /// the identifier, no metadata URI), so it can't overwrite curated fields.
+    /// Lengths are validated as on init; the creator pays rent for the URI
+    pub fn upgrade_legacy_pool(
+        ctx: Context<UpdateMetadataUri>,
+        display_name: String,
+        metadata_uri: String,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(
+            pool.display_name == pool.identifier && pool.metadata_uri.is_empty(),
+            SipzyError::NotLegacyPool
+        );
+        require!(display_name.len() <= 64, SipzyError::NameTooLong);
+        pool.display_name = display_name;
+        
+        set_metadata_uri(
+            pool,
+            &ctx.accounts.creator.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            metadata_uri,
+        )?;
+        
+        emit!(LegacyPoolUpgraded {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            display_name: pool.display_name.clone(),
+            metadata_uri: pool.metadata_uri.clone(),
+        });
+        
+        Ok(())
+    }
+}
+
+// ============================================================================
+// TRADE EXECUTION
+// ============================================================================
+
+/// Settle a quoted buy of `amount` tokens (out of `requested_amount`): take
+/// the cost from the trader, pay the fee to the creator and credit the position
+fn process_buy(
+    accounts: &mut Trade,
+    position_bump: u8,
+    amount: u64,
+    requested_amount: u64,
+    quote: BuyQuote,
+) -> Result<()> {
+    let quote_accounts = trade_quote_accounts(accounts)?;
+    let mut insurance_fund = load_insurance_fund(&accounts.insurance_fund)?;
+    execute_buy(
+        BuyAccounts {
+            pool: &mut accounts.pool,
+            position: &mut accounts.position,
+            trader: accounts.trader.to_account_info(),
+            creator_wallet: accounts.creator_wallet.to_account_info(),
+            reserve_vault: accounts.reserve_vault.as_ref(),
+            quote_accounts,
+            system_program: accounts.system_program.to_account_info(),
+            trackers: TradeTrackers {
+                price_snapshot: accounts.price_snapshot.as_deref_mut().map(|snapshot| &mut **snapshot),
+                top_holders: accounts.top_holders.as_deref_mut().map(|board| &mut **board),
+                insurance_fund: insurance_fund.as_mut(),
+                global_stats: accounts.global_stats.as_deref_mut().map(|stats| &mut **stats),
+            },
+        },
+        position_bump,
+        amount,
+        requested_amount,
+        quote,
+    )?;
+    save_insurance_fund(insurance_fund)
+}
+
+/// Accounts a buy settles against, borrowed from whichever instruction runs it
+struct BuyAccounts<'a, 'info> {
+    pool: &'a mut Account<'info, Pool>,
+    position: &'a mut Account<'info, HolderPosition>,
+    trader: AccountInfo<'info>,
+    creator_wallet: AccountInfo<'info>,
+    reserve_vault: Option<&'a SystemAccount<'info>>,
+    quote_accounts: Option<QuoteAccounts<'info>>,
+    system_program: AccountInfo<'info>,
+    trackers: TradeTrackers<'a, 'info>,
+}
+
+/// SPL token accounts a trade on a quote-mint pool settles through
+struct QuoteAccounts<'info> {
+    reserve: AccountInfo<'info>,
+    trader_account: AccountInfo<'info>,
+    creator_account: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+/// The quote-token accounts a Trade passed, validated against the pool
+/// (None for a native SOL pool, which ignores them)
+fn trade_quote_accounts<'info>(accounts: &Trade<'info>) -> Result<Option<QuoteAccounts<'info>>> {
+    let Some(quote_mint) = accounts.pool.quote_mint else {
+        return Ok(None);
+    };
+    let (Some(reserve), Some(trader_account), Some(creator_account), Some(token_program)) = (
+        &accounts.quote_reserve,
+        &accounts.trader_quote_account,
+        &accounts.creator_quote_account,
+        &accounts.token_program,
+    ) else {
+        return err!(SipzyError::QuoteAccountsMissing);
+    };
+    require_keys_eq!(trader_account.mint, quote_mint, SipzyError::InvalidQuoteMint);
+    require_keys_eq!(creator_account.mint, quote_mint, SipzyError::InvalidQuoteMint);
+    require_keys_eq!(
+        creator_account.owner,
+        accounts.pool.creator_wallet,
+        SipzyError::InvalidCreatorWallet
+    );
+    
+    Ok(Some(QuoteAccounts {
+        reserve: reserve.to_account_info(),
+        trader_account: trader_account.to_account_info(),
+        creator_account: creator_account.to_account_info(),
+        token_program: token_program.to_account_info(),
+    }))
+}
+
+/// Move `amount` quote tokens with an SPL transfer signed by `authority`
+/// (`signer_seeds` when the authority is the reserve PDA)
+fn transfer_quote<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            token::Transfer {
+                from: from.clone(),
+                to: to.clone(),
+                authority: authority.clone(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+/// Settle a quoted buy: take payment, credit the position and update the pool
+/// Shared by the trade instructions and initialize_creator_pool_and_buy
+fn execute_buy(
+    accounts: BuyAccounts,
+    position_bump: u8,
+    amount: u64,
+    requested_amount: u64,
+    quote: BuyQuote,
+) -> Result<()> {
+    let BuyAccounts {
+        pool,
+        position,
+        trader,
+        creator_wallet,
+        reserve_vault,
+        quote_accounts,
+        system_program: system,
+        trackers,
+    } = accounts;
+    let BuyQuote {
+        end_supply,
+        total_cost,
+        creator_fee,
+        buy_tax,
+        reserve_top_up,
+        pool_deposit,
+    } = quote;
+    
+    ensure_not_frozen(pool, position)?;
+    require!(
+        pool.quote_mint.is_none() || quote_accounts.is_some(),
+        SipzyError::QuoteAccountsMissing
+    );
+    let clock = Clock::get()?;
+    require!(
+        clock.unix_timestamp >= pool.whitelist_until || position.whitelisted,
+        SipzyError::NotWhitelisted
+    );
+    require!(
+        position.last_buy_ts == 0
+            || clock.unix_timestamp >= position.last_buy_ts.saturating_add(pool.buy_cooldown_secs),
+        SipzyError::BuyCooldownActive
+    );
+    
+    // Split the insurance fund's share (SOL only) out of the creator fee
+    let insurance_fund = trackers.insurance_fund.filter(|_| quote_accounts.is_none());
+    let total_fee = creator_fee;
+    let insurance_fee = insurance_share(insurance_fund.as_deref().map(|fund| &fund.fund), total_fee)?;
+    let creator_fee = total_fee - insurance_fee;
+    
+    if let Some(quote) = &quote_accounts {
+        // Quote-mint pool: the same amounts move as SPL tokens
+        transfer_quote(&quote.token_program, &quote.trader_account, &quote.reserve, &trader, pool_deposit, &[])?;
+        transfer_quote(&quote.token_program, &quote.trader_account, &quote.creator_account, &trader, creator_fee, &[])?;
+    } else {
+        // Transfer SOL to the reserve (cost less fee)
+        system_program::transfer(
+            CpiContext::new(
+                system.clone(),
+                system_program::Transfer {
+                    from: trader.clone(),
+                    to: reserve_account(pool, reserve_vault)?,
+                },
+            ),
+            pool_deposit,
+        )?;
+        
+        if let Some(fund) = insurance_fund {
+            system_program::transfer(
+                CpiContext::new(
+                    system.clone(),
+                    system_program::Transfer {
+                        from: trader.clone(),
+                        to: fund.info.clone(),
+                    },
+                ),
+                insurance_fee,
+            )?;
+            record_insurance_contribution(&mut fund.fund, pool, insurance_fee);
+        }
+        
+        // Transfer fee to creator wallet
+        system_program::transfer(
+            CpiContext::new(
+                system,
+                system_program::Transfer {
+                    from: trader.clone(),
+                    to: creator_wallet,
+                },
+            ),
+            creator_fee,
+        )?;
+    }
+    
+    // Credit the trader's position (created on first buy)
+    open_position(position, pool.key(), trader.key(), position_bump);
+    credit_position(position, amount, clock.unix_timestamp)?;
+    position.last_buy_slot = clock.slot;
+    position.last_buy_ts = clock.unix_timestamp;
+    position.cost_basis_lamports = position.cost_basis_lamports
+        .checked_add(total_cost)
+        .ok_or(SipzyError::Overflow)?;
+    position.tokens_bought = position.tokens_bought
+        .checked_add(amount)
+        .ok_or(SipzyError::Overflow)?;
+    
+    // Update pool state
+    let reserve_before = pool.reserve_sol;
+    pool.reserve_sol = pool.reserve_sol
+        .checked_add(pool_deposit)
+        .ok_or(SipzyError::Overflow)?;
+    pool.net_deposited = pool.net_deposited
+        .checked_add(pool_deposit)
+        .ok_or(SipzyError::Overflow)?;
+    check_reserve_accounting(pool)?;
+    pool.total_supply = end_supply;
+    pool.total_buy_volume = pool.total_buy_volume.saturating_add(total_cost);
+    if pool.max_notional_per_window > 0 {
+        if pool.window_start + pool.window_secs <= clock.unix_timestamp {
+            pool.window_start = clock.unix_timestamp;
+            pool.window_notional = 0;
+        }
+        pool.window_notional = pool.window_notional.saturating_add(total_cost);
+    }
+    pool.lifetime_fees = pool.lifetime_fees.saturating_add(creator_fee);
+    
+    if let Some(price_snapshot) = trackers.price_snapshot {
+        record_snapshot(price_snapshot, pool, total_cost)?;
+    }
+    if let Some(stats) = trackers.global_stats {
+        record_reserve_change(stats, pool, reserve_before);
+    }
+    if let Some(top_holders) = trackers.top_holders {
+        update_top_holders(top_holders, position.owner, position.balance);
+    }
+    
+    // Graduate once the reserve reaches the threshold (0 = never)
+    if pool.graduation_threshold > 0 && pool.reserve_sol >= pool.graduation_threshold {
+        pool.is_graduated = true;
+        emit!(PoolGraduated {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            total_supply: pool.total_supply,
+            reserve_sol: pool.reserve_sol,
+        });
+    }
+    
+    // Pools that opted out of trade events leave indexers to poll state
+    if pool.emit_events {
+        emit!(TokensTraded {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            trader: trader.key(),
+            trade_type: if amount < requested_amount { TradeType::PartialBuy } else { TradeType::Buy },
+            amount,
+            requested_amount,
+            filled_amount: amount,
+            sol_amount: total_cost,
+            fee: total_fee,
+            creator_fee,
+            protocol_fee: insurance_fee,
+            referral_fee: 0,
+            buy_tax,
+            burned: 0,
+            reserve_top_up,
+            new_supply: pool.total_supply,
+            new_reserve: pool.reserve_sol,
+            pricing: SellPricing::Curve,
+            decimals: pool.decimals,
+        });
+    }
+    
+    Ok(())
+}
+
+/// Sell `amount` tokens from the trader's position, paying the net refund to
+/// the recipient (or trader) and the fee to the creator. Returns the net refund.
+fn process_sell(accounts: &mut Trade, amount: u64) -> Result<u64> {
+    let refund_destination = match &accounts.recipient {
+        Some(recipient) => recipient.to_account_info(),
+        None => accounts.trader.to_account_info(),
+    };
+    let creator_wallet = accounts.creator_wallet.to_account_info();
+    let quote_accounts = trade_quote_accounts(accounts)?;
+    let mut insurance_fund = load_insurance_fund(&accounts.insurance_fund)?;
+    
+    let received = execute_sell(
+        &mut accounts.pool,
+        &mut accounts.position,
+        accounts.trader.key(),
+        &refund_destination,
+        &creator_wallet,
+        accounts.reserve_vault.as_ref(),
+        quote_accounts,
+        &accounts.system_program.to_account_info(),
+        TradeTrackers {
+            price_snapshot: accounts.price_snapshot.as_deref_mut().map(|snapshot| &mut **snapshot),
+            top_holders: accounts.top_holders.as_deref_mut().map(|board| &mut **board),
+            insurance_fund: insurance_fund.as_mut(),
+            global_stats: accounts.global_stats.as_deref_mut().map(|stats| &mut **stats),
+        },
+        amount,
+    )?;
+    save_insurance_fund(insurance_fund)?;
+    
+    Ok(received)
+}
+
+/// Optional accounts a trade updates when they are present
+#[derive(Default)]
+struct TradeTrackers<'a, 'info> {
+    price_snapshot: Option<&'a mut PriceSnapshot>,
+    top_holders: Option<&'a mut TopHolders>,
+    insurance_fund: Option<&'a mut LoadedInsuranceFund<'info>>,
+    global_stats: Option<&'a mut GlobalStats>,
+}
+
+/// Sell `amount` tokens from `position` back into `pool`, paying the net refund
+/// to `refund_destination` and the fee to `creator_wallet`
+/// Shared by the single-pool sells and sell_basket; returns the net refund
+#[allow(clippy::too_many_arguments)]
+fn execute_sell<'info>(
+    pool: &mut Account<'info, Pool>,
+    position: &mut Account<'info, HolderPosition>,
+    trader: Pubkey,
+    refund_destination: &AccountInfo<'info>,
+    creator_wallet: &AccountInfo<'info>,
+    reserve_vault: Option<&SystemAccount<'info>>,
+    quote_accounts: Option<QuoteAccounts<'info>>,
+    system: &AccountInfo<'info>,
+    trackers: TradeTrackers<'_, 'info>,
+    amount: u64,
+) -> Result<u64> {
+    // Calculate refund based on pool type (same formula as buy, in reverse),
+    // with the holder's early-sell penalty and loyalty discount applied to the fee
+    let now = Clock::get()?.unix_timestamp;
+    let penalty_bps = sell_penalty_bps(pool, position, now);
+    let discount_bps = loyalty_discount_bps(pool, position, now);
+    let SellQuote { gross_refund, creator_fee, net_refund, burned, pricing } =
+        quote_sell(pool, amount, penalty_bps, discount_bps)?;
+    
+    ensure_not_frozen(pool, position)?;
+    require!(position.balance >= amount, SipzyError::InsufficientBalance);
+    require!(
+        pool.quote_mint.is_none() || quote_accounts.is_some(),
+        SipzyError::QuoteAccountsMissing
+    );
+    if pool.same_slot_sell_block {
+        require!(
+            position.last_buy_slot != Clock::get()?.slot,
+            SipzyError::SameSlotTradeBlocked
+        );
+    }
+    
+    let founder_sold = if position.is_founder {
+        founder_tokens_in_sell(pool, position.balance, amount)?
+    } else {
+        0
+    };
+    
+    let start_supply = pool.total_supply - amount;
+    
+    // The insurance fund's share (SOL only) comes out of the creator's fee
+    let insurance_fund = trackers.insurance_fund.filter(|_| quote_accounts.is_none());
+    let insurance_fee = insurance_share(insurance_fund.as_deref().map(|fund| &fund.fund), creator_fee)?;
+    
+    if let Some(quote) = &quote_accounts {
+        // Quote-mint pool: the reserve token account signs for itself and
+        // refunds the trader's token account
+        let pool_key = pool.key();
+        let bump = [pool.quote_reserve_bump];
+        let seeds: &[&[u8]] = &[b"quote_reserve", pool_key.as_ref(), &bump];
+        transfer_quote(&quote.token_program, &quote.reserve, &quote.trader_account, &quote.reserve, net_refund, &[seeds])?;
+        transfer_quote(&quote.token_program, &quote.reserve, &quote.creator_account, &quote.reserve, creator_fee, &[seeds])?;
+    } else {
+        // Transfer SOL from the reserve to seller or recipient and fee to
+        // creator. pay_from_reserve never holds two borrows at once, so this
+        // stays correct when the destination and creator_wallet are the same
+        // account (e.g. a creator selling their own tokens).
+        pay_from_reserve(pool, reserve_vault, system, refund_destination, net_refund)?;
+        pay_from_reserve(pool, reserve_vault, system, creator_wallet, creator_fee - insurance_fee)?;
+        if let Some(fund) = insurance_fund {
+            pay_from_reserve(pool, reserve_vault, system, &fund.info, insurance_fee)?;
+            record_insurance_contribution(&mut fund.fund, pool, insurance_fee);
+        }
+    }
+    
+    // Debit the trader's position, releasing the sold share of its cost basis
+    reduce_cost_basis(position, amount);
+    position.balance = position.balance
+        .checked_sub(amount)
+        .ok_or(SipzyError::InsufficientBalance)?;
+    
+    // Update pool state
+    let reserve_before = pool.reserve_sol;
+    pool.reserve_sol = pool.reserve_sol
+        .checked_sub(net_refund)
+        .ok_or(SipzyError::Overflow)?
+        .checked_sub(creator_fee)
+        .ok_or(SipzyError::Overflow)?;
+    pool.net_deposited = pool.net_deposited
+        .checked_sub(net_refund)
+        .and_then(|deposited| deposited.checked_sub(creator_fee))
+        .ok_or(SipzyError::ReserveAccountingMismatch)?;
+    check_reserve_accounting(pool)?;
+    pool.total_supply = start_supply;
+    pool.burned_supply = pool.burned_supply
+        .checked_add(burned)
+        .ok_or(SipzyError::Overflow)?;
+    pool.founder_vested_sold = pool.founder_vested_sold
+        .checked_add(founder_sold)
+        .ok_or(SipzyError::Overflow)?;
+    pool.total_sell_volume = pool.total_sell_volume.saturating_add(gross_refund);
+    pool.lifetime_fees = pool.lifetime_fees.saturating_add(creator_fee - insurance_fee);
+    
+    if let Some(price_snapshot) = trackers.price_snapshot {
+        record_snapshot(price_snapshot, pool, gross_refund)?;
+    }
+    if let Some(stats) = trackers.global_stats {
+        record_reserve_change(stats, pool, reserve_before);
+    }
+    if let Some(top_holders) = trackers.top_holders {
+        update_top_holders(top_holders, position.owner, position.balance);
+    }
+    
+    // Pools that opted out of trade events leave indexers to poll state
+    if pool.emit_events {
+        emit!(TokensTraded {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            trader,
+            trade_type: TradeType::Sell,
+            amount,
+            requested_amount: amount,
+            filled_amount: amount,
+            sol_amount: gross_refund,
+            fee: creator_fee,
+            creator_fee: creator_fee - insurance_fee,
+            protocol_fee: insurance_fee,
+            referral_fee: 0,
+            buy_tax: 0,
+            burned,
+            reserve_top_up: 0,
+            new_supply: pool.total_supply,
+            new_reserve: pool.reserve_sol,
+            pricing,
+            decimals: pool.decimals,
+        });
+    }
+    
+    Ok(net_refund)
+}
+
+/// Founder-allocation tokens consumed by a founder selling `amount` from `balance`
+/// Tokens the founder bought are sold first; the founder-allocation remainder
+/// must fit within what has vested and not yet been sold
+fn founder_tokens_in_sell(pool: &Pool, balance: u64, amount: u64) -> Result<u64> {
+    let founder_held = pool.founder_allocation.saturating_sub(pool.founder_vested_sold);
+    let bought_held = balance.saturating_sub(founder_held);
+    let founder_part = amount.saturating_sub(bought_held);
+    
+    let unlocked = founder_vested_amount(pool, Clock::get()?.unix_timestamp)
+        .saturating_sub(pool.founder_vested_sold);
+    require!(founder_part <= unlocked, SipzyError::FounderTokensLocked);
+    
+    Ok(founder_part)
+}
+
+/// Founder-allocation tokens vested at `now`, linear from founder_vest_start
+fn founder_vested_amount(pool: &Pool, now: i64) -> u64 {
+    if pool.founder_vest_duration == 0 {
+        return pool.founder_allocation;
+    }
+    let elapsed = (now.saturating_sub(pool.founder_vest_start).max(0) as u64)
+        .min(pool.founder_vest_duration);
+    (pool.founder_allocation as u128 * elapsed as u128 / pool.founder_vest_duration as u128) as u64
+}
+
+/// Activate or deactivate a pool; deactivation also emits a PoolClosedStats
+/// summary for archival
+fn set_pool_active(pool: &mut Account<Pool>, is_active: bool) {
+    pool.is_active = is_active;
+    
+    emit!(PoolStatusChanged {
+        pool: pool.key(),
+        seq: next_seq(pool),
+        is_active,
+    });
+    
+    if !is_active {
+        emit!(PoolClosedStats {
+            pool: pool.key(),
+            seq: next_seq(pool),
+            final_supply: pool.total_supply,
+            final_reserve: pool.reserve_sol,
+            total_buy_volume: pool.total_buy_volume,
+            total_sell_volume: pool.total_sell_volume,
+            lifetime_fees: pool.lifetime_fees,
+        });
+    }
+}
+
+/// Why the pool would currently reject trades, most fundamental reason first
+fn trading_status(pool: &Pool, global_paused: bool, now: i64) -> TradingStatus {
+    if global_paused {
+        return TradingStatus::GloballyPaused;
+    }
+    if !pool.is_active {
+        return TradingStatus::Inactive;
+    }
+    if pool.is_graduated {
+        return TradingStatus::Graduated;
+    }
+    if is_paused(pool, now) {
+        return TradingStatus::Paused;
+    }
+    if is_expired(pool, now) {
+        return TradingStatus::Expired;
+    }
+    if !sell_window_open(pool, now) {
+        return TradingStatus::SellsClosed;
+    }
+    TradingStatus::Open
+}
+
+/// Invariant: the reserve can never hold more than buyers put in net of what
+/// sellers took out. Tracked independently of reserve_sol so a slip in the
+/// trade math surfaces as an error instead of a silent drift
+fn check_reserve_accounting(pool: &Pool) -> Result<()> {
+    require!(
+        pool.reserve_sol <= pool.net_deposited,
+        SipzyError::ReserveAccountingMismatch
+    );
+    Ok(())
+}
+
+/// Whether `supply` fits under the pool's max_supply (0 = uncapped)
+fn within_max_supply(pool: &Pool, supply: u64) -> bool {
+    pool.max_supply == 0 || supply <= pool.max_supply
+}
+
+/// Buy notional already spent in the rate-limit window containing `now`
+/// (0 once the window has rolled over)
+fn current_window_notional(pool: &Pool, now: i64) -> u64 {
+    if pool.window_start + pool.window_secs <= now {
+        0
+    } else {
+        pool.window_notional
+    }
+}
+
+/// Whether the program admin has halted all pools with set_global_pause
+/// The config PDA may not have been created yet, which halts nothing
+fn globally_paused(global_config: &AccountInfo) -> bool {
+    global_config.owner == &crate::ID
+        && global_config.try_borrow_data().is_ok_and(|data| {
+            GlobalConfig::try_deserialize(&mut &data[..]).is_ok_and(|config| config.paused)
+        })
+}
+
+/// Refuse to reopen a pool while trading is halted program-wide, or once
+/// its reserve was rescued and the admin has not cleared it
+fn ensure_can_reactivate(pool: &Pool, global_config: &AccountInfo) -> Result<()> {
+    require!(!globally_paused(global_config), SipzyError::GloballyPaused);
+    require!(pool.emergency_withdrawn == 0, SipzyError::EmergencyWithdrawalPending);
+    Ok(())
+}
+
+/// Sell value of the whole supply that the reserve is expected to back
+/// The founder allocation was minted without SOL, so its reserve_debt is not
+/// a shortfall
+fn backed_liability(pool: &Pool) -> Result<u64> {
+    let liability = calculate_curve_cost(pool, pool.burned_supply, curve_supply(pool)?, Rounding::Down)?;
+    Ok(liability.saturating_sub(pool.reserve_debt))
+}
+
+/// Whether a creator's timed pause is still running
+fn is_paused(pool: &Pool, now: i64) -> bool {
+    now < pool.pause_until
+}
+
+/// Whether `now` falls inside the pool's sell window
+fn sell_window_open(pool: &Pool, now: i64) -> bool {
+    now >= pool.sells_open_from && (pool.sells_open_until == 0 || now < pool.sells_open_until)
+}
+
+/// Whether a Stream pool has passed its expiry (expires_at 0 = never)
+fn is_expired(pool: &Pool, now: i64) -> bool {
+    pool.expires_at > 0 && now >= pool.expires_at
+}
+
+/// Result of pricing a sell against the current pool state
+struct SellQuote {
+    /// Value of the tokens sold, fee included
+    gross_refund: u64,
+    creator_fee: u64,
+    /// SOL paid out to the seller
+    net_refund: u64,
+    /// Sold tokens burned rather than returned to the curve
+    burned: u64,
+    pricing: SellPricing,
+}
+
+/// Result of pricing a buy against the current pool state
+struct BuyQuote {
+    end_supply: u64,
+    /// SOL the buyer pays, fee and buy tax included
+    total_cost: u64,
+    creator_fee: u64,
+    /// Part of pool_deposit beyond the curve cost, kept as extra backing
+    buy_tax: u64,
+    /// Creator fee diverted into pool_deposit to hold the minimum reserve ratio
+    reserve_top_up: u64,
+    /// Portion of total_cost that goes into the reserve
+    pool_deposit: u64,
+}
+
+/// Validate and price a buy of `amount` tokens without touching any account
+/// Shared by buy_tokens and simulate_trade so previews match execution exactly
+fn quote_buy(pool: &Pool, amount: u64) -> Result<BuyQuote> {
+    require!(amount > 0, SipzyError::InvalidAmount);
+    require!(pool.is_active, SipzyError::PoolInactive);
+    require!(!pool.is_graduated, SipzyError::PoolGraduated);
+    let now = Clock::get()?.unix_timestamp;
+    require!(!is_paused(pool, now), SipzyError::TradingPaused);
+    require!(!is_expired(pool, now), SipzyError::PoolExpired);
+    
+    let end_supply = pool.total_supply.checked_add(amount).ok_or(SipzyError::Overflow)?;
+    require!(within_max_supply(pool, end_supply), SipzyError::MaxSupplyExceeded);
+    
+    // Tokens burned on sells keep their place on the curve, so pricing starts
+    // past them
+    let start_point = curve_supply(pool)?;
+    let end_point = start_point.checked_add(amount).ok_or(SipzyError::Overflow)?;
+    
+    // Keep any single buy on an exponential curve from rocketing the price
+    // past what later sellers could redeem against the reserve. A linear
+    // price only grows in proportion to the tokens bought, and a zero
+    // starting price has no multiple to compare against
+    let start_price = calculate_spot_price(pool, start_point)? as u128;
+    let end_price = calculate_spot_price(pool, end_point)? as u128;
+    if pool.pool_type == PoolType::Stream && start_price > 0 {
+        require!(
+            end_price <= start_price * MAX_PRICE_MULTIPLE as u128,
+            SipzyError::PriceMoveTooLarge
+        );
+    }
+    if pool.max_price_impact_bps > 0 {
+        require!(
+            price_impact_bps(start_price as u64, end_price as u64) <= pool.max_price_impact_bps as u64,
+            SipzyError::ImpactTooHigh
+        );
+    }
+    
+    // Calculate total cost based on pool type
+    let curve_cost = calculate_curve_cost(pool, start_point, end_point, Rounding::Up)?;
+    
+    // Calculate the creator fee (fee_bps, 1% by default, plus any launch surcharge)
+    let fee_bps = buy_fee_bps(pool, amount, now);
+    let (mut creator_fee, curve_deposit) = calculate_fee(curve_cost, fee_bps, 0)?;
+    
+    // The buy tax is charged on top and goes straight into the reserve
+    let buy_tax = calculate_buy_tax(curve_cost, pool.buy_tax_bps)?;
+    let total_cost = curve_cost.checked_add(buy_tax).ok_or(SipzyError::Overflow)?;
+    let mut pool_deposit = curve_deposit.checked_add(buy_tax).ok_or(SipzyError::Overflow)?;
+    
+    // Divert as much of the fee as needed (and available) to keep the reserve
+    // at min_reserve_ratio_bps of the full sell value after this buy
+    let mut reserve_top_up = 0;
+    if pool.min_reserve_ratio_bps > 0 {
+        let full_sell_value = calculate_curve_cost(pool, pool.burned_supply, end_point, Rounding::Down)?;
+        let required = full_sell_value as u128 * pool.min_reserve_ratio_bps as u128 / 10_000;
+        let new_reserve = pool.reserve_sol as u128 + pool_deposit as u128;
+        reserve_top_up = (required.saturating_sub(new_reserve) as u64).min(creator_fee);
+        creator_fee -= reserve_top_up;
+        pool_deposit += reserve_top_up;
+    }
+    
+    // Enforce the optional per-window buy notional (0 = unlimited)
+    if pool.max_notional_per_window > 0 {
+        let window_total = current_window_notional(pool, now)
+            .checked_add(total_cost)
+            .ok_or(SipzyError::Overflow)?;
+        require!(
+            window_total <= pool.max_notional_per_window,
+            SipzyError::RateLimitExceeded
+        );
+    }
+    
+    // Enforce the optional reserve cap (0 = uncapped)
+    if pool.reserve_cap > 0 {
+        let new_reserve = pool.reserve_sol
+            .checked_add(pool_deposit)
+            .ok_or(SipzyError::Overflow)?;
+        require!(new_reserve <= pool.reserve_cap, SipzyError::ReserveCapReached);
+    }
+    
+    Ok(BuyQuote {
+        end_supply,
+        total_cost,
+        creator_fee,
+        buy_tax,
+        reserve_top_up,
+        pool_deposit,
+    })
+}
+
+/// Largest amount in [0, max_amount] whose buy total cost fits in `budget`
+/// Total cost grows with amount, so a binary search finds it exactly; on
+/// linear pools a closed-form estimate first narrows the range to a few units
+fn max_affordable_amount(pool: &Pool, budget: u64, max_amount: u64) -> Result<u64> {
+    let mut low = 0;
+    let mut high = max_amount;
+    
+    if pool.pool_type == PoolType::Creator {
+        // Budget left for the curve once the buy tax on top is accounted for
+        let curve_budget = budget as u128 * 10_000 / (10_000 + pool.buy_tax_bps as u128);
+        if let Some(estimate) = linear_amount_for_cost(pool, curve_budget) {
+            let near_low = estimate.saturating_sub(EXACT_COST_SLACK).min(max_amount);
+            let near_high = estimate.saturating_add(EXACT_COST_SLACK).min(max_amount);
+            if fits_budget(pool, budget, near_low)? {
+                low = near_low;
+            }
+            if near_high < max_amount && !fits_budget(pool, budget, near_high + 1)? {
+                high = near_high;
+            }
+        }
+    }
+    
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if fits_budget(pool, budget, mid)? {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+/// Whether a buy of `amount` costs at most `budget`
+/// Limits that only tighten as the amount grows (price move, reserve cap, rate
+/// limit, overflow) count as "too expensive" so the search can step past them
+fn fits_budget(pool: &Pool, budget: u64, amount: u64) -> Result<bool> {
+    if amount == 0 {
+        return Ok(true);
+    }
+    match quote_buy(pool, amount) {
+        Ok(quote) => Ok(quote.total_cost <= budget),
+        Err(err)
+            if err == SipzyError::Overflow.into()
+                || err == SipzyError::PriceMoveTooLarge.into()
+                || err == SipzyError::ImpactTooHigh.into()
+                || err == SipzyError::ReserveCapReached.into()
+                || err == SipzyError::RateLimitExceeded.into() =>
+        {
+            Ok(false)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Base units a linear pool sells for `curve_budget` lamports of curve cost
+/// Solves m·a² + 2(b·u + m·S)·a = 2·C·u² for a, with base price b, slope m,
+/// token unit u and curve point S. None if the terms overflow u128
+fn linear_amount_for_cost(pool: &Pool, curve_budget: u128) -> Option<u64> {
+    let unit = token_unit(pool.decimals) as u128;
+    let base = pool.base_price as u128;
+    let slope = pool.curve_param as u128;
+    let start = curve_supply(pool).ok()? as u128;
+    
+    if slope == 0 {
+        let amount = curve_budget.checked_mul(unit)?.checked_div(base)?;
+        return u64::try_from(amount).ok();
+    }
+    
+    let lead = base.checked_mul(unit)?.checked_add(slope.checked_mul(start)?)?;
+    let discriminant = lead
+        .checked_mul(lead)?
+        .checked_add(slope.checked_mul(2)?.checked_mul(curve_budget)?.checked_mul(unit)?.checked_mul(unit)?)?;
+    let amount = (integer_sqrt(discriminant) - lead) / slope;
+    Some(u64::try_from(amount).unwrap_or(u64::MAX))
+}
+
+/// Floor square root by Newton's method
+fn integer_sqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n;
+    let mut y = x / 2 + 1;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+/// Buy fee for `amount` tokens at `now`: the pool fee plus the anti-sniping
+/// surcharge, which decays linearly from snipe_fee_bps to zero over the window
+/// ending at launch_end
+fn buy_fee_bps(pool: &Pool, amount: u64, now: i64) -> u64 {
+    let fee_bps = pool_fee_bps(pool, amount);
+    if pool.snipe_window_secs == 0 {
+        return fee_bps;
+    }
+    let remaining = (pool.launch_end.saturating_sub(now).max(0) as u64).min(pool.snipe_window_secs);
+    let surcharge = pool.snipe_fee_bps as u128 * remaining as u128 / pool.snipe_window_secs as u128;
+    fee_bps + surcharge as u64
+}
+
+/// The pool fee on a trade of `amount` tokens, waived up to fee_free_threshold
+fn pool_fee_bps(pool: &Pool, amount: u64) -> u64 {
+    if amount <= pool.fee_free_threshold {
+        0
+    } else {
+        pool.fee_bps
+    }
+}
+
+/// Validate and price a sell of `amount` tokens at the pool level, returning
+/// (gross refund, fee, net refund); holder-specific checks stay with the caller
+fn quote_sell(
+    pool: &Pool,
+    amount: u64,
+    penalty_bps: u64,
+    fee_discount_bps: u16,
+) -> Result<SellQuote> {
+    require!(amount > 0, SipzyError::InvalidAmount);
+    require!(pool.is_active, SipzyError::PoolInactive);
+    let now = Clock::get()?.unix_timestamp;
+    require!(!is_paused(pool, now), SipzyError::TradingPaused);
+    require!(sell_window_open(pool, now), SipzyError::SellWindowClosed);
+    
+    let quote = calculate_sell_refund(pool, amount, penalty_bps, fee_discount_bps)?;
+    require!(
+        pool.reserve_sol >= quote.net_refund.checked_add(quote.creator_fee).ok_or(SipzyError::Overflow)?,
+        SipzyError::InsufficientReserve
+    );
+    
+    Ok(quote)
+}
+
+/// Most a buy_tokens_auto_slippage buy of `amount` may cost: its cost at the
+/// current spot price, buy tax included, plus default_slippage_bps of that
+fn auto_slippage_limit(pool: &Pool, amount: u64) -> Result<u64> {
+    let spot_price = calculate_spot_price(pool, curve_supply(pool)?)?;
+    let at_spot = u64::try_from(
+        spot_price as u128 * amount as u128 / token_unit(pool.decimals) as u128,
+    )
+    .map_err(|_| SipzyError::Overflow)?;
+    let at_spot = at_spot
+        .checked_add(calculate_buy_tax(at_spot, pool.buy_tax_bps)?)
+        .ok_or(SipzyError::Overflow)?;
+    
+    let limit = at_spot as u128 * (10_000 + pool.default_slippage_bps as u128) / 10_000;
+    Ok(limit.min(u64::MAX as u128) as u64)
+}
+
+/// Account holding a pool's SOL reserve: its reserve vault once enabled, else
+/// the pool itself; quote-mint pools hold no SOL reserve and are rejected
+fn reserve_account<'info>(
+    pool: &Account<'info, Pool>,
+    reserve_vault: Option<&SystemAccount<'info>>,
+) -> Result<AccountInfo<'info>> {
+    require!(pool.quote_mint.is_none(), SipzyError::QuoteMintUnsupported);
+    if !pool.reserve_vault_enabled {
+        return Ok(pool.to_account_info());
+    }
+    let vault = reserve_vault.ok_or(SipzyError::ReserveVaultMissing)?;
+    Ok(vault.to_account_info())
+}
+
+/// Pay `lamports` out of a pool's reserve
+/// The pool is program-owned and debited directly; the reserve vault is a
+/// system account, so it pays through a transfer signed with its seeds
+fn pay_from_reserve<'info>(
+    pool: &Account<'info, Pool>,
+    reserve_vault: Option<&SystemAccount<'info>>,
+    system: &AccountInfo<'info>,
+    dest: &AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    let reserve = reserve_account(pool, reserve_vault)?;
+    if !pool.reserve_vault_enabled {
+        return transfer_from_pool(&reserve, dest, lamports);
+    }
+    
+    let pool_key = pool.key();
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system.clone(),
+            system_program::Transfer {
+                from: reserve,
+                to: dest.clone(),
+            },
+            &[&[b"reserve", pool_key.as_ref(), &[pool.reserve_vault_bump]]],
+        ),
+        lamports,
+    )
+}
+
+/// Move lamports out of a program-owned pool account
+/// The pool is debited and the destination credited in separate short-lived
+/// borrows, so the destination may alias any other account in the instruction
+fn transfer_from_pool(pool_info: &AccountInfo, dest: &AccountInfo, lamports: u64) -> Result<()> {
+    {
+        let mut pool_lamports = pool_info.try_borrow_mut_lamports()?;
+        **pool_lamports = pool_lamports
+            .checked_sub(lamports)
+            .ok_or(SipzyError::InsufficientReserve)?;
+    }
+    credit_lamports(dest, lamports)
+}
+
+/// Advance the pool's event sequence and return the number for the event
+/// being emitted
+fn next_seq(pool: &mut Pool) -> u64 {
+    pool.seq = pool.seq.wrapping_add(1);
+    pool.seq
+}
+
+/// Add lamports to an account with an overflow check
+fn credit_lamports(account: &AccountInfo, lamports: u64) -> Result<()> {
+    let mut balance = account.try_borrow_mut_lamports()?;
+    **balance = balance.checked_add(lamports).ok_or(SipzyError::Overflow)?;
+    Ok(())
+}
+
+/// Gross refund, fee, and net refund for selling `amount` tokens at the current supply
+fn calculate_sell_refund(
+    pool: &Pool,
+    amount: u64,
+    penalty_bps: u64,
+    fee_discount_bps: u16,
+) -> Result<SellQuote> {
+    require!(pool.total_supply >= amount, SipzyError::InsufficientSupply);
+    let end_supply = pool.total_supply;
+    
+    // The burned share stays on the curve: the seller is refunded for the
+    // rest, priced from the top of the curve down
+    let burned = (amount as u128 * pool.burn_on_sell_bps as u128 / 10_000) as u64;
+    let end_point = curve_supply(pool)?;
+    let start_point = end_point - (amount - burned);
+    
+    let mut gross_refund = calculate_curve_cost(pool, start_point, end_point, Rounding::Down)?;
+    let mut pricing = SellPricing::Curve;
+    
+    // Reserve-backed pools cap what one sell can draw at curve price; past the
+    // threshold the seller gets their pro-rata share of the reserve instead
+    if pool.reserve_backed_sells && end_supply > 0 {
+        let threshold = (pool.reserve_sol as u128) * (RESERVE_BACKED_SELL_BPS as u128) / 10_000;
+        if (gross_refund as u128) > threshold {
+            let pro_rata = (pool.reserve_sol as u128) * (amount as u128) / (end_supply as u128);
+            gross_refund = u64::try_from(pro_rata).map_err(|_| SipzyError::Overflow)?;
+            pricing = SellPricing::ReserveProRata;
+        }
+    }
+    
+    let (creator_fee, net_refund) =
+        calculate_fee(gross_refund, pool_fee_bps(pool, amount) + penalty_bps, fee_discount_bps)?;
+    
+    Ok(SellQuote { gross_refund, creator_fee, net_refund, burned, pricing })
+}
+
+/// Point on the curve the next token trades at: the supply plus every token
+/// burned on sells
+fn curve_supply(pool: &Pool) -> Result<u64> {
+    Ok(pool.total_supply
+        .checked_add(pool.burned_supply)
+        .ok_or(SipzyError::Overflow)?)
+}
+
+// ============================================================================
+// BONDING CURVE MATH
+// ============================================================================
+
+/// Rounding direction for curve costs that fall between whole lamports
+#[derive(Clone, Copy)]
+enum Rounding {
+    /// Charging a buyer: round up so the pool is never underpaid
+    Up,
+    /// Refunding a seller: round down so the pool never overpays
+    Down,
+}
+
+/// Reject a curve parameter shaped for the other pool type
+/// A zero growth rate is a flat curve, not an exponential one, and one past
+/// MAX_STREAM_GROWTH_BPS is almost certainly a lamport slope. Likewise a slope
+/// in the growth-rate range is almost certainly bps meant for a Stream pool;
+/// 0 stays allowed for a flat linear curve
+fn check_curve_param(pool_type: PoolType, curve_param: u64) -> Result<()> {
+    let in_range = match pool_type {
+        PoolType::Stream => (1..=MAX_STREAM_GROWTH_BPS).contains(&curve_param),
+        PoolType::Creator => curve_param == 0 || curve_param > MAX_STREAM_GROWTH_BPS,
+    };
+    require!(in_range, SipzyError::CurveParamOutOfRange);
+    Ok(())
+}
+
+/// Number of base units in one whole token (10^decimals)
+fn token_unit(decimals: u8) -> u64 {
+    10u64.pow(decimals as u32)
+}
+
+/// Price of whole token `n` on the pool's curve
+fn whole_token_price(pool: &Pool, n: u64) -> Result<u64> {
+    match pool.pool_type {
+        PoolType::Creator => calculate_linear_price_checked(n, pool.base_price, pool.curve_param),
+        PoolType::Stream => Ok(round_to_tick(
+            calculate_exponential_price(
+                n,
+                pool.base_price,
+                pool.curve_param,
+                exp_precision(pool.precision_exp)?,
+                pool.price_ceiling,
+            )?,
+            pool.tick_size,
+        )),
+    }
+}
+
+/// Cost of whole tokens [start, end) on the pool's curve
+fn whole_token_integral(pool: &Pool, start: u64, end: u64) -> Result<u64> {
+    match pool.pool_type {
+        PoolType::Creator => calculate_linear_integral(start, end, pool.base_price, pool.curve_param),
+        PoolType::Stream => calculate_exponential_integral(
+            start,
+            end,
+            pool.base_price,
+            pool.curve_param,
+            pool.tick_size,
+            exp_precision(pool.precision_exp)?,
+            pool.price_ceiling,
+        ),
+    }
+}
+
+/// Spot price of one whole token at `supply` (in base units)
+/// Between whole-token points the price is linearly interpolated, so
+/// fractional supply moves the price smoothly instead of in whole-token steps
+fn calculate_spot_price(pool: &Pool, supply: u64) -> Result<u64> {
+    let unit = token_unit(pool.decimals);
+    let whole = supply / unit;
+    let fraction = supply % unit;
+    
+    let price = whole_token_price(pool, whole)?;
+    if fraction == 0 {
+        return Ok(round_to_tick(price, pool.tick_size));
+    }
+    
+    let next_price = whole_token_price(pool, whole.checked_add(1).ok_or(SipzyError::Overflow)?)?;
+    let step = (next_price.saturating_sub(price) as u128) * fraction as u128 / unit as u128;
+    
+    Ok(round_to_tick(price + step as u64, pool.tick_size))
+}
+
+/// Spot price at `supply`, or u64::MAX where the true price exceeds u64
+fn spot_price_saturating(pool: &Pool, supply: u64) -> Result<u64> {
+    match calculate_spot_price(pool, supply) {
+        Err(err) if err == SipzyError::Overflow.into() => Ok(u64::MAX),
+        result => result,
+    }
+}
+
+/// Round a spot price down to a multiple of `tick_size` (0 = exact)
+fn round_to_tick(price: u64, tick_size: u64) -> u64 {
+    if tick_size == 0 {
+        return price;
+    }
+    price - price % tick_size
+}
+
+/// Cost of base units [start_supply, end_supply) on the pool's curve
+/// Each base unit costs 1/unit of the interpolated spot price at its position.
+/// With zero decimals this is exactly the whole-token integral.
+fn calculate_curve_cost(
+    pool: &Pool,
+    start_supply: u64,
+    end_supply: u64,
+    rounding: Rounding,
+) -> Result<u64> {
+    if pool.decimals == 0 {
+        return whole_token_integral(pool, start_supply, end_supply);
+    }
+    
+    let amount = end_supply.checked_sub(start_supply).ok_or(SipzyError::Overflow)?;
+    if amount == 0 {
+        return Ok(0);
+    }
+    
+    let unit = token_unit(pool.decimals);
+    let unit_wide = unit as u128;
+    let first_whole = start_supply / unit;
+    let last_whole = end_supply / unit;
+    let head_offset = start_supply % unit;
+    let tail_offset = end_supply % unit;
+    
+    // Accumulate unit² × cost so partial tokens stay exact until the final division
+    let mut scaled: u128 = 0;
+    
+    if first_whole == last_whole {
+        scaled = partial_token_cost(pool, first_whole, head_offset, tail_offset, unit)?;
+    } else {
+        let mut full_start = first_whole;
+        if head_offset > 0 {
+            scaled = partial_token_cost(pool, first_whole, head_offset, unit, unit)?;
+            full_start += 1;
+        }
+        
+        if last_whole > full_start {
+            // Σ over whole tokens q of (unit² × p_q + unit(unit-1)/2 × (p_{q+1} - p_q)),
+            // where the second term telescopes to the rise across the whole range
+            let sum = whole_token_integral(pool, full_start, last_whole)? as u128;
+            let rise = whole_token_price(pool, last_whole)?
+                .saturating_sub(whole_token_price(pool, full_start)?) as u128;
+            let full_cost = sum
+                .checked_mul(unit_wide * unit_wide)
+                .ok_or(SipzyError::Overflow)?
+                .checked_add(rise * (unit_wide * (unit_wide - 1) / 2))
+                .ok_or(SipzyError::Overflow)?;
+            scaled = scaled.checked_add(full_cost).ok_or(SipzyError::Overflow)?;
+        }
+        
+        if tail_offset > 0 {
+            let tail_cost = partial_token_cost(pool, last_whole, 0, tail_offset, unit)?;
+            scaled = scaled.checked_add(tail_cost).ok_or(SipzyError::Overflow)?;
+        }
+    }
+    
+    let denominator = unit_wide * unit_wide;
+    let cost = match rounding {
+        Rounding::Down => scaled / denominator,
+        Rounding::Up => scaled.div_ceil(denominator),
+    };
+    
+    u64::try_from(cost).map_err(|_| SipzyError::Overflow.into())
+}
+
+/// unit² × cost of base units [from, to) within whole token `whole`
+/// = Σ (unit × p_q + (p_{q+1} - p_q) × offset) for offset in [from, to)
+fn partial_token_cost(pool: &Pool, whole: u64, from: u64, to: u64, unit: u64) -> Result<u128> {
+    let count = (to - from) as u128;
+    let price = whole_token_price(pool, whole)?;
+    let next_price = whole_token_price(pool, whole.checked_add(1).ok_or(SipzyError::Overflow)?)?;
+    let rise = next_price.saturating_sub(price) as u128;
+    
+    // Sum of offsets from..to-1 (always an integer)
+    let offset_sum = count * (from as u128 + to as u128 - 1) / 2;
+    
+    (unit as u128 * price as u128)
+        .checked_mul(count)
+        .ok_or(SipzyError::Overflow)?
+        .checked_add(rise.checked_mul(offset_sum).ok_or(SipzyError::Overflow)?)
+        .ok_or(SipzyError::Overflow.into())
+}
+
+/// Calculate linear price: Price(n) = slope × n + base_price
+/// Fails with Overflow past u64, as the integral does, so get_price and buys
+/// agree on when a pool has outgrown u64; display views that want a number
+/// anyway go through spot_price_saturating
+fn calculate_linear_price_checked(supply: u64, base_price: u64, slope: u64) -> Result<u64> {
+    supply
+        .checked_mul(slope)
+        .and_then(|rise| base_price.checked_add(rise))
+        .ok_or(SipzyError::Overflow.into())
+}
+
+/// Calculate integral of linear curve for buying/selling k tokens
+/// Cost = ∫[start to end] (slope × n + base) dn
+///      = slope × (end² - start²)/2 + base × (end - start)
+///      = slope × k × (start + end - 1)/2 + base × k  [where k = end - start]
+fn calculate_linear_integral(
+    start_supply: u64,
+    end_supply: u64,
+    base_price: u64,
+    slope: u64,
+) -> Result<u64> {
+    let amount = end_supply.checked_sub(start_supply).ok_or(SipzyError::Overflow)?;
+    if amount == 0 {
+        return Ok(0);
+    }
+    
+    // Base cost = amount × base_price
+    let base_cost = amount.checked_mul(base_price).ok_or(SipzyError::Overflow)?;
+    
+    // Slope cost = slope × sum of indices from start to end-1
+    // Sum = amount × (first + last) / 2 where first=start, last=end-1
+    // amount > 0 already implies end_supply >= 1; guard it explicitly so a bad
+    // range surfaces as InvalidAmount rather than a misleading Overflow
+    require!(end_supply >= 1, SipzyError::InvalidAmount);
+    let first = start_supply;
+    let last = end_supply - 1;
+    
+    let sum_indices = amount
+        .checked_mul(first.checked_add(last).ok_or(SipzyError::Overflow)?)
+        .ok_or(SipzyError::Overflow)?
+        .checked_div(2)
+        .ok_or(SipzyError::Overflow)?;
+    
+    let slope_cost = sum_indices.checked_mul(slope).ok_or(SipzyError::Overflow)?;
+    
+    base_cost.checked_add(slope_cost).ok_or(SipzyError::Overflow.into())
+}
+
+/// Calculate exponential price: Price(n) = base_price × (1 + growth_rate)^n
+/// growth_rate is in basis points (500 = 5% = 0.05). A nonzero `price_ceiling`
+/// clamps the price, including where the unclamped price would overflow
+fn calculate_exponential_price(
+    supply: u64,
+    base_price: u64,
+    growth_rate_bps: u64,
+    precision: u128,
+    price_ceiling: u64,
+) -> Result<u64> {
+    // Convert to fixed-point: (1 + rate) = (10000 + growth_rate_bps) / 10000
+    let rate_multiplier = 10000u128 + growth_rate_bps as u128; // e.g., 10500 for 5%
+    
+    // Final price = base_price × (rate_multiplier / 10000)^supply, with the
+    // power in `precision` fixed point; None once it exceeds u64
+    let price = exp_power(rate_multiplier, supply, 10000, precision)
+        .ok()
+        .and_then(|growth| (base_price as u128).checked_mul(growth))
+        .map(|scaled| scaled / precision)
+        .filter(|price| *price <= u64::MAX as u128);
+    
+    match price {
+        Some(price) if price_ceiling > 0 => Ok((price as u64).min(price_ceiling)),
+        Some(price) => Ok(price as u64),
+        None if price_ceiling > 0 => Ok(price_ceiling),
+        None => Err(SipzyError::Overflow.into()),
+    }
+}
+
+/// First whole token in [start, end) priced at the ceiling, or `end` if none is
+/// Prices never decrease with supply, so a binary search finds the boundary
+fn plateau_start(
+    start: u64,
+    end: u64,
+    base_price: u64,
+    growth_rate_bps: u64,
+    precision: u128,
+    price_ceiling: u64,
+) -> Result<u64> {
+    let mut low = start;
+    let mut high = end;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if calculate_exponential_price(mid, base_price, growth_rate_bps, precision, price_ceiling)?
+            >= price_ceiling
+        {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    
+    Ok(low)
+}
+
+/// Calculate integral of exponential curve for buying/selling
+/// Cost = ∑(base_price × r^i) for i from start to end-1
+/// With a nonzero tick_size each term is rounded down to the tick, which has
+/// no closed form, so ticked integrals are limited to MAX_SUMMATION_TERMS
+/// tokens and MAX_SUMMATION_WORK.
+/// With a nonzero price_ceiling the curve is exponential up to the plateau
+/// and flat at the ceiling from there on
+fn calculate_exponential_integral(
+    start_supply: u64,
+    end_supply: u64,
+    base_price: u64,
+    growth_rate_bps: u64,
+    tick_size: u64,
+    precision: u128,
+    price_ceiling: u64,
+) -> Result<u64> {
+    let amount = end_supply.checked_sub(start_supply).ok_or(SipzyError::Overflow)?;
+    if amount == 0 {
+        return Ok(0);
+    }
+    
+    // Price the flat region as ceiling × tokens and integrate only the
+    // exponential region below it
+    if price_ceiling > 0 {
+        let plateau = plateau_start(
+            start_supply,
+            end_supply,
+            base_price,
+            growth_rate_bps,
+            precision,
+            price_ceiling,
+        )?;
+        if plateau < end_supply {
+            let flat_cost = round_to_tick(price_ceiling, tick_size) as u128
+                * (end_supply - plateau) as u128;
+            let curve_cost = calculate_exponential_integral(
+                start_supply,
+                plateau,
+                base_price,
+                growth_rate_bps,
+                tick_size,
+                precision,
+                0,
+            )?;
+            let total = flat_cost + curve_cost as u128;
+            if total > u64::MAX as u128 {
+                return Err(SipzyError::Overflow.into());
+            }
+            return Ok(total as u64);
+        }
+    }
+    
+    // For small amounts, use summation to avoid precision issues, unless the
+    // per-price exponentiation at this supply would make it too expensive
+    let supply_bits = u64::from(u64::BITS - end_supply.leading_zeros());
+    let summable = amount <= MAX_SUMMATION_TERMS
+        && amount.saturating_mul(supply_bits) <= MAX_SUMMATION_WORK;
+    
+    // Tick rounding is per token, so only summation prices it exactly
+    if tick_size > 0 {
+        require!(summable, SipzyError::BatchTooLarge);
+    }
+    
+    if summable {
+        let mut total: u128 = 0;
+        for i in start_supply..end_supply {
+            let price = round_to_tick(
+                calculate_exponential_price(i, base_price, growth_rate_bps, precision, price_ceiling)?,
+                tick_size,
+            ) as u128;
+            total = total.checked_add(price).ok_or(SipzyError::Overflow)?;
+        }
+        if total > u64::MAX as u128 {
+            return Err(SipzyError::Overflow.into());
+        }
+        return Ok(total as u64);
+    }
+    
+    if growth_rate_bps == 0 {
+        // If no growth rate, it's just constant price (init rejects this, but
+        // keep the integral well defined regardless)
+        return Ok(base_price.checked_mul(amount).ok_or(SipzyError::Overflow)?);
+    }
+    
+    // For larger amounts, walk the range in EXP_CHUNK_TERMS-sized chunks.
+    // Each chunk is a geometric series anchored at its own first price:
+    //   chunk cost = price(first) × (r^k - 1) / (r - 1)
+    // and the anchor is advanced by r^EXP_CHUNK_TERMS between chunks. r^end
+    // is never formed, and the fixed-point products go through mul_fp, so
+    // every intermediate stays within a constant factor of the running total
+    // at any precision and Overflow means the cost itself exceeds u64.
+    let r_bps = 10000u128 + growth_rate_bps as u128;
+    let chunk_growth = exp_power(r_bps, EXP_CHUNK_TERMS, 10000, precision)?;
+    let chunk_factor = geometric_factor(chunk_growth, growth_rate_bps, precision)?;
+    
+    // price(start) in `precision` fixed point
+    let mut price_fp = (base_price as u128)
+        .checked_mul(exp_power(r_bps, start_supply, 10000, precision)?)
+        .ok_or(SipzyError::Overflow)?;
+    let mut remaining = amount;
+    let mut total: u128 = 0;
+    
+    while remaining > 0 {
+        // A single token above u64::MAX already makes the cost unpayable
+        if price_fp / precision > u64::MAX as u128 {
+            return Err(SipzyError::Overflow.into());
+        }
+        
+        let terms = remaining.min(EXP_CHUNK_TERMS);
+        let factor = if terms == EXP_CHUNK_TERMS {
+            chunk_factor
+        } else {
+            geometric_factor(
+                exp_power(r_bps, terms, 10000, precision)?,
+                growth_rate_bps,
+                precision,
+            )?
+        };
+        
+        let chunk_cost = mul_fp(price_fp, factor, precision)? / precision;
+        total = total.checked_add(chunk_cost).ok_or(SipzyError::Overflow)?;
+        if total > u64::MAX as u128 {
+            return Err(SipzyError::Overflow.into());
+        }
+        
+        remaining -= terms;
+        if remaining > 0 {
+            price_fp = mul_fp(price_fp, chunk_growth, precision)?;
+        }
+    }
+    
+    Ok(total as u64)
+}
+
+/// Fixed-point scale for a pool's precision_exp (10^9, 10^12 or 10^15)
+fn exp_precision(precision_exp: u8) -> Result<u128> {
+    match precision_exp {
+        9 | 12 | 15 => Ok(10u128.pow(precision_exp as u32)),
+        _ => Err(SipzyError::InvalidExpPrecision.into()),
+    }
+}
+
+/// Helper: geometric series factor (r^k - 1) / (r - 1) in `precision` fixed point,
+/// given r^k in fixed point and r - 1 = growth_rate_bps / 10000
+fn geometric_factor(r_pow_fp: u128, growth_rate_bps: u64, precision: u128) -> Result<u128> {
+    Ok(r_pow_fp
+        .checked_sub(precision)
+        .ok_or(SipzyError::Overflow)?
+        .checked_mul(10000)
+        .ok_or(SipzyError::Overflow)?
+        / growth_rate_bps as u128)
+}
+
+/// Helper: a × b / precision for fixed-point a and b, rounded down
+/// Splits both into whole and fractional parts so no partial product needs
+/// more than the result's own magnitude × precision; a plain a × b overflows
+/// u128 at ordinary prices once precision is 10^15
+fn mul_fp(a: u128, b: u128, precision: u128) -> Result<u128> {
+    let (a_whole, a_frac) = (a / precision, a % precision);
+    let (b_whole, b_frac) = (b / precision, b % precision);
+    a_whole
+        .checked_mul(b_whole)
+        .and_then(|whole| whole.checked_mul(precision))
+        .and_then(|sum| sum.checked_add(a_whole.checked_mul(b_frac)?))
+        .and_then(|sum| sum.checked_add(a_frac.checked_mul(b_whole)?))
+        .and_then(|sum| sum.checked_add(a_frac * b_frac / precision))
+        .ok_or(SipzyError::Overflow.into())
+}
+
+/// Helper: Calculate (base/scale)^exp with high precision
+/// The base is only squared while higher exponent bits remain, so an
+/// Overflow here means the full power itself does not fit in u128
+fn exp_power(base: u128, exp: u64, scale: u128, precision: u128) -> Result<u128> {
+    let mut result: u128 = precision;
+    let mut b: u128 = base.checked_mul(precision).ok_or(SipzyError::Overflow)? / scale;
+    let mut e = exp;
+    
+    while e > 0 {
+        if e % 2 == 1 {
+            result = result.checked_mul(b).ok_or(SipzyError::Overflow)? / precision;
+        }
+        e /= 2;
+        if e > 0 {
+            b = b.checked_mul(b).ok_or(SipzyError::Overflow)? / precision;
+        }
+    }
+    
+    Ok(result)
+}
+
+/// Spot price move from `before` to `after` in basis points of `before`,
+/// saturating at u64::MAX; a move away from a zero price counts as unbounded
+fn price_impact_bps(before: u64, after: u64) -> u64 {
+    let moved = before.abs_diff(after) as u128;
+    if moved == 0 {
+        return 0;
+    }
+    if before == 0 {
+        return u64::MAX;
+    }
+    u64::try_from(moved * 10000 / before as u128).unwrap_or(u64::MAX)
+}
+
+/// Value of `supply` base units at `price` lamports per whole token, clamped to
+/// u64::MAX; the flag reports whether it was clamped
+fn value_at_price(price: u64, supply: u64, unit: u64) -> (u64, bool) {
+    let value = price as u128 * supply as u128 / unit as u128;
+    match u64::try_from(value) {
+        Ok(value) => (value, false),
+        Err(_) => (u64::MAX, true),
+    }
+}
+
+/// Buy tax at `buy_tax_bps` of the curve cost, rounded down
+fn calculate_buy_tax(curve_cost: u64, buy_tax_bps: u16) -> Result<u64> {
+    let tax = curve_cost as u128 * buy_tax_bps as u128 / 10000;
+    u64::try_from(tax).map_err(|_| SipzyError::Overflow.into())
+}
+
+/// The insurance fund a trade pays into, if the admin has created it
+/// Every trade instruction takes the seed-checked PDA, so no trader can leave
+/// it out. Before initialize_insurance_fund it is an empty system account, and
+/// a 0 insurance_fee_bps has nothing to collect, so both skip the fund
+fn load_insurance_fund<'info>(info: &AccountInfo<'info>) -> Result<Option<LoadedInsuranceFund<'info>>> {
+    if info.owner != &crate::ID {
+        return Ok(None);
+    }
+    let fund = InsuranceFund::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    if fund.insurance_fee_bps == 0 {
+        return Ok(None);
+    }
+    Ok(Some(LoadedInsuranceFund { info: info.clone(), fund }))
+}
+
+/// Persist the fund's contribution total after a trade that loaded it
+fn save_insurance_fund(loaded: Option<LoadedInsuranceFund>) -> Result<()> {
+    if let Some(loaded) = loaded {
+        let mut data = loaded.info.try_borrow_mut_data()?;
+        loaded.fund.try_serialize(&mut &mut data[..])?;
+    }
+    Ok(())
+}
+
+/// The insurance fund PDA and its state, held for the length of a trade
+struct LoadedInsuranceFund<'info> {
+    info: AccountInfo<'info>,
+    fund: InsuranceFund,
+}
+
+/// The insurance fund's share of a trade's creator fee (0 without a fund)
+fn insurance_share(fund: Option<&InsuranceFund>, creator_fee: u64) -> Result<u64> {
+    match fund {
+        Some(fund) => Ok(calculate_fee(creator_fee, fund.insurance_fee_bps as u64, 0)?.0),
+        None => Ok(0),
+    }
+}
+
+/// Count a share already paid into the insurance fund and log it for audit
+fn record_insurance_contribution(fund: &mut InsuranceFund, pool: &mut Account<Pool>, amount: u64) {
+    fund.total_contributed = fund.total_contributed.saturating_add(amount);
+    emit!(InsuranceContributed {
+        pool: pool.key(),
+        seq: next_seq(pool),
+        amount,
+        total_contributed: fund.total_contributed,
+    });
+}
+
+/// Count a newly created pool in the global stats
+fn record_pool_created(stats: &mut GlobalStats, pool_type: PoolType) {
+    match pool_type {
+        PoolType::Creator => stats.creator_pools = stats.creator_pools.saturating_add(1),
+        PoolType::Stream => stats.stream_pools = stats.stream_pools.saturating_add(1),
+    }
+}
+
+/// Move a converted pool between the per-type counts
+/// Saturating, since the pool may predate the stats account
+fn record_pool_type_change(stats: &mut GlobalStats, from: PoolType, to: PoolType) {
+    match from {
+        PoolType::Creator => stats.creator_pools = stats.creator_pools.saturating_sub(1),
+        PoolType::Stream => stats.stream_pools = stats.stream_pools.saturating_sub(1),
+    }
+    record_pool_created(stats, to);
+}
+
+/// Drop a closing pool and its residual reserve from the global stats
+/// Saturating, since the pool may predate the stats account
+fn record_pool_closed(stats: &mut GlobalStats, pool: &Pool) {
+    match pool.pool_type {
+        PoolType::Creator => stats.creator_pools = stats.creator_pools.saturating_sub(1),
+        PoolType::Stream => stats.stream_pools = stats.stream_pools.saturating_sub(1),
+    }
+    if pool.quote_mint.is_none() {
+        stats.total_reserve = stats.total_reserve.saturating_sub(pool.reserve_sol);
+    }
+}
+
+/// Move the global locked-SOL total by a native pool's reserve change
+fn record_reserve_change(stats: &mut GlobalStats, pool: &Pool, reserve_before: u64) {
+    if pool.quote_mint.is_some() {
+        return;
+    }
+    stats.total_reserve = stats.total_reserve
+        .saturating_add(pool.reserve_sol)
+        .saturating_sub(reserve_before);
+}
+
+/// Calculate fee at `fee_bps` (100 = 1%), less `discount_bps` of the fee itself
+fn calculate_fee(amount: u64, fee_bps: u64, discount_bps: u16) -> Result<(u64, u64)> {
+    let full_fee = amount
+        .checked_mul(fee_bps)
+        .ok_or(SipzyError::Overflow)?
+        .checked_div(10000)
+        .ok_or(SipzyError::Overflow)?;
+    let discount = full_fee
+        .checked_mul(discount_bps as u64)
+        .ok_or(SipzyError::Overflow)?
+        .checked_div(10000)
+        .ok_or(SipzyError::Overflow)?;
+    let fee = full_fee.checked_sub(discount).ok_or(SipzyError::Overflow)?;
+    let net = amount.checked_sub(fee).ok_or(SipzyError::Overflow)?;
+    Ok((fee, net))
+}
+
+// ============================================================================
+// METADATA
+// ============================================================================
+
+/// Creator pool PDA seed for `generation`
+/// Generation 0 contributes an empty seed, which hashes identically to no
+/// seed at all, so pools created before generations existed keep their address
+fn generation_seed(generation: u32) -> Vec<u8> {
+    if generation == 0 {
+        Vec::new()
+    } else {
+        generation.to_le_bytes().to_vec()
+    }
+}
+
+/// Replace the pool's metadata URI, resizing the account to fit; `payer` covers
+/// extra rent when it grows and is refunded the difference when it shrinks
+fn set_metadata_uri<'info>(
+    pool: &mut Account<'info, Pool>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    metadata_uri: String,
+) -> Result<()> {
+    require!(!pool.metadata_frozen, SipzyError::MetadataFrozen);
+    require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, SipzyError::MetadataUriTooLong);
+    require!(
+        matches_metadata_format(&metadata_uri, pool.metadata_format),
+        SipzyError::InvalidMetadataFormat
+    );
+    
+    resize_pool_for_metadata(&pool.to_account_info(), payer, system_program, metadata_uri.len())?;
+    pool.metadata_uri = metadata_uri;
+    
+    emit!(MetadataUriUpdated {
+        pool: pool.key(),
+        seq: next_seq(pool),
+        metadata_uri: pool.metadata_uri.clone(),
+        metadata_format: pool.metadata_format,
+    });
+    
+    Ok(())
+}
+
+/// Whether `uri` has the shape its declared MetadataFormat requires
+/// IPFS accepts a CIDv0 (46 base58 characters starting "Qm") or a base32
+/// CIDv1 (lowercase, starting "b"); Arweave a 43-character base64url TxID
+fn matches_metadata_format(uri: &str, metadata_format: u8) -> bool {
+    const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    
+    match metadata_format {
+        format if format == MetadataFormat::Raw as u8 => true,
+        format if format == MetadataFormat::IpfsCid as u8 => {
+            let cid_v0 = uri.len() == 46
+                && uri.starts_with("Qm")
+                && uri.bytes().all(|c| BASE58.contains(&c));
+            let cid_v1 = uri.len() >= 50
+                && uri.starts_with('b')
+                && uri.bytes().all(|c| c.is_ascii_lowercase() || (b'2'..=b'7').contains(&c));
+            cid_v0 || cid_v1
+        }
+        format if format == MetadataFormat::ArweaveTxId as u8 => {
+            uri.len() == 43
+                && uri.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_')
+        }
+        _ => false,
+    }
+}
+
+/// Account size for a pool whose metadata_uri holds `metadata_len` bytes
+/// Pool::INIT_SPACE reserves MAX_METADATA_URI_LEN; at 6,960 lamports per byte
+/// an empty URI saves 1,392,000 lamports (~0.0014 SOL) of rent per pool
+fn pool_space(metadata_len: usize) -> usize {
+    8 + Pool::INIT_SPACE - MAX_METADATA_URI_LEN + metadata_len
+}
+
+/// Resize a pool account to fit a `metadata_len`-byte URI, moving only the rent
+/// difference between `payer` and the pool so the reserve is never touched
+fn resize_pool_for_metadata<'info>(
+    pool: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    metadata_len: usize,
+) -> Result<()> {
+    let new_len = pool_space(metadata_len);
+    let old_len = pool.data_len();
+    if new_len == old_len {
+        return Ok(());
+    }
+    
+    let rent = Rent::get()?;
+    let old_rent = rent.minimum_balance(old_len);
+    let new_rent = rent.minimum_balance(new_len);
+    
+    if new_rent > old_rent {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: pool.clone(),
+                },
+            ),
+            new_rent - old_rent,
+        )?;
+    } else {
+        transfer_from_pool(pool, payer, old_rent - new_rent)?;
+    }
+    
+    pool.realloc(new_len, false)?;
+    Ok(())
+}
+
+// ============================================================================
+// POSITIONS
+// ============================================================================
+
+/// Whether `proof` links `leaf` to `root`, hashing each pair in sorted order
+fn verify_merkle_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (first, second) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        hashv(&[&first, &second]).to_bytes()
+    });
+    computed == *root
+}
+
+/// Fill in a freshly created HolderPosition (no-op if already initialized)
+fn open_position(position: &mut HolderPosition, pool: Pubkey, owner: Pubkey, bump: u8) {
+    if position.owner == Pubkey::default() {
+        position.pool = pool;
+        position.owner = owner;
+        position.bump = bump;
+    }
+}
+
+/// Add `amount` tokens acquired at `now`, folding them into the position's
+/// amount-weighted acquisition time so topping up can't inherit an old timestamp
+fn credit_position(position: &mut HolderPosition, amount: u64, now: i64) -> Result<()> {
+    let new_balance = position.balance.checked_add(amount).ok_or(SipzyError::Overflow)?;
+    let weighted = position.acquired_at as i128 * position.balance as i128
+        + now as i128 * amount as i128;
+    position.acquired_at = (weighted / new_balance as i128) as i64;
+    position.balance = new_balance;
+    Ok(())
+}
+
+/// Balance to pay a holder snapshot's airdrop on: the current balance, which
+/// can only have shrunk since the snapshot as long as no buy landed at or
+/// after the snapshot's slot
+fn snapshot_balance(position: &HolderPosition, snapshot: &HolderSnapshot) -> Result<u64> {
+    require!(
+        position.last_buy_slot < snapshot.taken_slot,
+        SipzyError::BoughtAfterSnapshot
+    );
+    Ok(position.balance)
+}
+
+/// Remember `client_order_id` on the position, rejecting one seen in the last
+/// RECENT_ORDER_IDS orders; 0 opts out of the check
+fn record_order_id(position: &mut HolderPosition, client_order_id: u64) -> Result<()> {
+    if client_order_id == 0 {
+        return Ok(());
+    }
+    require!(
+        !position.recent_order_ids.contains(&client_order_id),
+        SipzyError::DuplicateOrder
+    );
+    let slot = position.order_id_cursor as usize % RECENT_ORDER_IDS;
+    position.recent_order_ids[slot] = client_order_id;
+    position.order_id_cursor = ((slot + 1) % RECENT_ORDER_IDS) as u8;
+    Ok(())
+}
+
+/// Reject trades from a frozen position on a pool with compliance enabled
+fn ensure_not_frozen(pool: &Pool, position: &HolderPosition) -> Result<()> {
+    require!(
+        !(pool.compliance_enabled && position.frozen),
+        SipzyError::PositionFrozen
+    );
+    Ok(())
+}
+
+/// Shrink cost basis and tokens bought in proportion to `amount` of the balance
+/// being sold, so the average buy price of what remains is unchanged
+fn reduce_cost_basis(position: &mut HolderPosition, amount: u64) {
+    if position.balance == 0 {
+        return;
+    }
+    let sold = amount.min(position.balance) as u128;
+    let balance = position.balance as u128;
+    position.cost_basis_lamports -= (position.cost_basis_lamports as u128 * sold / balance) as u64;
+    position.tokens_bought -= (position.tokens_bought as u128 * sold / balance) as u64;
+}
+
+/// Early-sell penalty at `now`: max_sell_penalty_bps scaled by the share of
+/// the penalty window the position has not yet been held for
+/// Holding time runs from the position's amount-weighted acquisition time, so
+/// topping up a seasoned position only partly resets it
+fn sell_penalty_bps(pool: &Pool, position: &HolderPosition, now: i64) -> u64 {
+    if pool.penalty_window_secs == 0 {
+        return 0;
+    }
+    let held = now.saturating_sub(position.acquired_at).max(0) as u64;
+    let remaining = pool.penalty_window_secs.saturating_sub(held);
+    (pool.max_sell_penalty_bps as u128 * remaining as u128 / pool.penalty_window_secs as u128) as u64
+}
+
+/// Sell-fee discount earned by how long the position has been held
+/// Picks the largest discount among tiers whose minimum hold has elapsed
+fn loyalty_discount_bps(pool: &Pool, position: &HolderPosition, now: i64) -> u16 {
+    let held = now.saturating_sub(position.acquired_at).max(0) as u64;
+    pool.loyalty_tiers
+        .iter()
+        .filter(|tier| held >= tier.min_hold_secs)
+        .map(|tier| tier.discount_bps)
+        .max()
+        .unwrap_or(0)
+}
+
+// ============================================================================
+// SNAPSHOTS
+// ============================================================================
+
+/// Accumulate trade volume and, once the interval has elapsed, write the
+/// post-trade spot price and accumulated volume into the ring buffer
+fn record_snapshot(price_snapshot: &mut PriceSnapshot, pool: &Pool, volume: u64) -> Result<()> {
+    price_snapshot.pending_volume = price_snapshot.pending_volume.saturating_add(volume);
+    
+    let now = Clock::get()?.unix_timestamp;
+    if now - price_snapshot.last_snapshot_ts < price_snapshot.interval_secs {
+        return Ok(());
+    }
+    
+    let head = price_snapshot.head as usize;
+    price_snapshot.entries[head] = SnapshotEntry {
+        timestamp: now,
+        price: curve_supply(pool)
+            .and_then(|supply| calculate_spot_price(pool, supply))
+            .unwrap_or(u64::MAX),
+        volume: price_snapshot.pending_volume,
+    };
+    price_snapshot.head = ((head + 1) % MAX_SNAPSHOTS) as u16;
+    price_snapshot.count = (price_snapshot.count + 1).min(MAX_SNAPSHOTS as u16);
+    price_snapshot.last_snapshot_ts = now;
+    price_snapshot.pending_volume = 0;
+    
+    Ok(())
+}
+
+// ============================================================================
+// LEADERBOARD
+// ============================================================================
+
+/// Move `owner` to its rank for `balance`, dropping it when the balance is 0
+/// Entries stay sorted by balance, largest first; on a tie the holder already
+/// ranked keeps the higher spot, and a full board only admits a balance above
+/// its last entry. Holders below the board aren't tracked, so a holder who
+/// sells out leaves a slot that the next qualifying trader fills
+fn update_top_holders(top_holders: &mut TopHolders, owner: Pubkey, balance: u64) {
+    let mut count = top_holders.count as usize;
+    
+    if let Some(index) = top_holders.entries[..count].iter().position(|entry| entry.owner == owner) {
+        top_holders.entries.copy_within(index + 1..count, index);
+        count -= 1;
+        top_holders.entries[count] = HolderEntry::default();
+    }
+    
+    if balance > 0 {
+        let rank = top_holders.entries[..count]
+            .iter()
+            .position(|entry| entry.balance < balance)
+            .unwrap_or(count);
+        if rank < MAX_TOP_HOLDERS {
+            let end = count.min(MAX_TOP_HOLDERS - 1);
+            top_holders.entries.copy_within(rank..end, rank + 1);
+            top_holders.entries[rank] = HolderEntry { owner, balance };
+            count = end + 1;
+        }
+    }
+    
+    top_holders.count = count as u8;
+}
+
+// ============================================================================
+// REGISTRY
+// ============================================================================
+
+/// Arguments shared by initialize_creator_pool and the legacy initialize_pool
+struct CreatorPoolInit {
+    channel_id: String,
+    channel_name: String,
+    metadata_uri: String,
+    base_price: Option<u64>,
+    slope: Option<u64>,
+    generation: u32,
+    creator_wallet: Pubkey,
+    authority: Pubkey,
+    pool_bump: u8,
+    registry_bump: u8,
+}
+
+/// Initialize a freshly created Creator pool account and register it
+fn create_creator_pool<'info>(
+    pool: &mut Account<'info, Pool>,
+    registry: &mut Account<'info, CreatorRegistry>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    global_stats: Option<&mut GlobalStats>,
+    init: CreatorPoolInit,
+) -> Result<()> {
+    require!(init.channel_id.len() <= 32, SipzyError::IdentifierTooLong);
+    require!(init.channel_name.len() <= 64, SipzyError::NameTooLong);
+    require!(init.metadata_uri.len() <= MAX_METADATA_URI_LEN, SipzyError::MetadataUriTooLong);
+    
+    let clock = Clock::get()?;
+    
+    pool.pool_type = PoolType::Creator;
+    pool.identifier = init.channel_id;
+    pool.display_name = init.channel_name;
+    pool.parent_identifier = String::new(); // No parent for creator pools
+    pool.creator_wallet = init.creator_wallet;
+    pool.authority = init.authority;
+    pool.total_supply = 0;
+    pool.reserve_sol = 0;
+    pool.base_price = init.base_price.unwrap_or(DEFAULT_CREATOR_BASE_PRICE);
+    pool.curve_param = init.slope.unwrap_or(DEFAULT_CREATOR_SLOPE); // slope for linear
+    check_curve_param(PoolType::Creator, pool.curve_param)?;
+    pool.metadata_uri = init.metadata_uri;
+    pool.bump = init.pool_bump;
+    pool.created_at = clock.unix_timestamp;
+    pool.is_active = true;
+    pool.reserve_cap = 0;
+    pool.decimals = 0;
+    pool.tick_size = 0;
+    pool.founder_minted = false;
+    pool.reserve_debt = 0;
+    pool.total_buyback_sol = 0;
+    pool.total_buy_volume = 0;
+    pool.total_sell_volume = 0;
+    pool.lifetime_fees = 0;
+    pool.same_slot_sell_block = false;
+    pool.pending_creator_wallet = Pubkey::default();
+    pool.founder_allocation = 0;
+    pool.founder_vest_start = 0;
+    pool.founder_vest_duration = 0;
+    pool.founder_vested_sold = 0;
+    pool.loyalty_tiers = [LoyaltyTier::default(); MAX_LOYALTY_TIERS];
+    pool.graduation_threshold = 0;
+    pool.is_graduated = false;
+    pool.fee_bps = FEE_BASIS_POINTS;
+    pool.snipe_fee_bps = 0;
+    pool.snipe_window_secs = 0;
+    pool.launch_end = 0;
+    pool.compliance_enabled = false;
+    pool.reserve_backed_sells = false;
+    pool.generation = init.generation;
+    pool.expires_at = 0;
+    pool.max_supply = 0;
+    pool.net_deposited = 0;
+    pool.verified = false;
+    pool.precision_exp = DEFAULT_EXP_PRECISION;
+    pool.pause_until = 0;
+    pool.buy_tax_bps = 0;
+    pool.max_sell_penalty_bps = 0;
+    pool.penalty_window_secs = 0;
+    pool.emergency_withdrawn = 0;
+    pool.max_notional_per_window = 0;
+    pool.window_secs = 0;
+    pool.window_notional = 0;
+    pool.window_start = 0;
+    pool.burn_on_sell_bps = 0;
+    pool.burned_supply = 0;
+    pool.metadata_format = MetadataFormat::Raw as u8;
+    pool.min_reserve_ratio_bps = 0;
+    pool.price_ceiling = 0;
+    pool.fee_free_threshold = 0;
+    pool.seq = 0;
+    pool.buy_cooldown_secs = 0;
+    pool.reserve_vault_enabled = false;
+    pool.reserve_vault_bump = 0;
+    pool.whitelist_until = 0;
+    pool.sells_open_from = 0;
+    pool.sells_open_until = 0;
+    pool.quote_mint = None;
+    pool.quote_reserve_bump = 0;
+    pool.emit_events = true;
+    pool.holder_snapshot_count = 0;
+    pool.airdrop_claim_until = 0;
+    pool.default_slippage_bps = DEFAULT_SLIPPAGE_BPS;
+    pool.metadata_frozen = false;
+    pool.max_price_impact_bps = 0;
+    pool.fee_ceiling_bps = MAX_FEE_BPS;
+    
+    register_pool(
+        registry,
+        pool.creator_wallet,
+        pool.key(),
+        None,
+        init.registry_bump,
+    );
+    if let Some(stats) = global_stats {
+        record_pool_created(stats, PoolType::Creator);
+    }
+    
+    // Pools are created with no metadata capacity; grow to fit the URI
+    resize_pool_for_metadata(
+        &pool.to_account_info(),
+        &payer.to_account_info(),
+        &system_program.to_account_info(),
+        pool.metadata_uri.len(),
+    )?;
+    
+    emit!(PoolCreated {
+        pool: pool.key(),
+        seq: next_seq(pool),
+        pool_type: PoolType::Creator,
+        identifier: pool.identifier.clone(),
+        creator_wallet: pool.creator_wallet,
+        base_price: pool.base_price,
+        curve_param: pool.curve_param,
+        bump: pool.bump,
+        generation: pool.generation,
+    });
+    
+    Ok(())
+}
+
+/// Append a pool to its creator's registry, initializing the registry on first use
+/// Stream pools pass their parent `stream_channel` so they are also indexed
+/// under it for get_channel_streams. The creator wallet doesn't sign pool
+/// creation, so anyone can fill its registry; a full registry is skipped with
+/// a PoolNotRegistered event instead of failing the pool's creation
+fn register_pool(
+    registry: &mut CreatorRegistry,
+    creator_wallet: Pubkey,
+    pool: Pubkey,
+    stream_channel: Option<String>,
+    bump: u8,
+) {
+    if registry.creator_wallet == Pubkey::default() {
+        registry.creator_wallet = creator_wallet;
+        registry.bump = bump;
+    }
+    if registry.pools.len() >= MAX_REGISTRY_POOLS {
+        emit!(PoolNotRegistered { creator_wallet, pool });
+        return;
+    }
+    registry.pools.push(pool);
+    if let Some(channel_id) = stream_channel {
+        registry.streams.push(ChannelStream { channel_id, pool });
+    }
+}
+
+/// Drop a pool from its creator's registry
+fn remove_from_registry(registry: &mut CreatorRegistry, pool: Pubkey) {
+    registry.pools.retain(|key| *key != pool);
+    registry.streams.retain(|stream| stream.pool != pool);
+}
+
+// ============================================================================
+// ATTESTATION
+// ============================================================================
+
+/// Check that the instruction before the current one is an Ed25519 program
+/// verification of `signer`'s signature over exactly `message`
+/// The Ed25519 program has already checked the signature by the time we run;
+/// this only confirms which key and message it checked
+fn verify_oracle_signature(instructions: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let current = instructions_sysvar::load_current_index_checked(instructions)?;
+    require!(current > 0, SipzyError::InvalidAttestation);
+    let ix = instructions_sysvar::load_instruction_at_checked(current as usize - 1, instructions)?;
+    require!(ix.program_id == ed25519_program::ID, SipzyError::InvalidAttestation);
+    
+    // Layout: [num_signatures u8, padding u8, then one 14-byte offsets struct:
+    //   signature_offset, signature_ix, pubkey_offset, pubkey_ix,
+    //   message_offset, message_size, message_ix (all u16 LE)]
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, SipzyError::InvalidAttestation);
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+    
+    // Every part must live in the Ed25519 instruction itself (index u16::MAX)
+    let this_ix = u16::MAX as usize;
+    require!(
+        read_u16(4) == this_ix && read_u16(8) == this_ix && read_u16(14) == this_ix,
+        SipzyError::InvalidAttestation
+    );
+    
+    let pubkey_offset = read_u16(6);
+    let message_offset = read_u16(10);
+    let message_size = read_u16(12);
+    let pubkey = data
+        .get(pubkey_offset..pubkey_offset + 32)
+        .ok_or(SipzyError::InvalidAttestation)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(SipzyError::InvalidAttestation)?;
+    
+    require!(
+        pubkey == signer.as_ref() && signed_message == message,
+        SipzyError::InvalidAttestation
+    );
+    Ok(())
+}
+
+// ============================================================================
+// MULTISIG
+// ============================================================================
+
+/// Position of `signer` in the multisig's signer list (its approval bit)
+fn multisig_signer_index(multisig: &MultisigAuthority, signer: &Pubkey) -> Result<usize> {
+    multisig.signers
+        .iter()
+        .position(|key| key == signer)
+        .ok_or_else(|| SipzyError::NotMultisigSigner.into())
+}
+
+// ============================================================================
+// ENUMS
+// ============================================================================
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PoolType {
+    Creator, // Linear bonding curve - long-term equity
+    Stream,  // Exponential bonding curve - event hype
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TradeType {
+    Buy,
+    Sell,
+    PartialBuy,  // Buy filled for less than the requested amount
+    PartialSell, // Sell filled for less than the requested amount
+}
+
+/// How a sell was priced, reported in TokensTraded
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SellPricing {
+    Curve,          // Bonding curve integral (always used for buys)
+    ReserveProRata, // reserve_sol × amount / total_supply
+}
+
+/// Creator action a multisig can propose for a pool it controls
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum ProposalAction {
+    Deactivate,
+    Reactivate,
+    UpdateMetadataUri {
+        #[max_len(200)] // MAX_METADATA_URI_LEN
+        metadata_uri: String,
+    },
+    /// Pay out fees that accrued to the multisig account
+    Withdraw { destination: Pubkey, amount: u64 },
+    /// Run any creator-signed instruction of this program, with the multisig
+    /// PDA signing wherever it is listed as a signer. Instructions that make
+    /// the creator pay rent fail, since the multisig account holds data
+    Invoke {
+        #[max_len(256)] // MAX_PROPOSAL_DATA_LEN
+        data: Vec<u8>,
+        #[max_len(10)] // MAX_PROPOSAL_ACCOUNTS
+        accounts: Vec<ProposalAccount>,
+    },
+}
+
+/// An account an Invoke proposal passes, in instruction order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct ProposalAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl ProposalAccount {
+    fn to_meta(&self) -> AccountMeta {
+        if self.is_writable {
+            AccountMeta::new(self.pubkey, self.is_signer)
+        } else {
+            AccountMeta::new_readonly(self.pubkey, self.is_signer)
+        }
+    }
+}
+
+/// How a pool's metadata_uri resolves, stored as Pool::metadata_format (the discriminant)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataFormat {
+    Raw,         // 0: any URI, resolved as-is
+    IpfsCid,     // 1: bare IPFS CID
+    ArweaveTxId, // 2: bare Arweave transaction ID
+}
+
+/// Trading status reported by get_pool_summary as a u8 (the discriminant)
+/// Codes 2 and 4 are retired and never reported
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TradingStatus {
+    Open = 0,           // buys and sells accepted
+    Inactive = 1,       // deactivated by the creator
+    Graduated = 3,      // curve trading has ended
+    GloballyPaused = 5, // all pools halted by the program admin
+    Expired = 6,        // stream pool past expires_at, sells only
+    Paused = 7,         // creator's timed pause, lifts at pause_until
+    SellsClosed = 8,    // outside the sell window, buys only
+}
+
+// ============================================================================
+// RETURN TYPES
+// ============================================================================
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BuyResult {
+    pub tokens_bought: u64,
+    pub sol_spent: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SellResult {
+    pub tokens_sold: u64,
+    pub sol_received: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwapResult {
+    pub tokens_sold: u64,
+    pub sol_received: u64,
+    pub tokens_bought: u64,
+    pub sol_spent: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CurveInfo {
+    pub pool_type: PoolType,
+    pub base_price: u64,
+    /// Slope (Creator) or growth rate in bps (Stream)
+    pub curve_param: u64,
+    pub total_supply: u64,
+    pub decimals: u8,
+    pub tick_size: u64,
+    /// Price the Stream curve flattens at (0 = uncapped)
+    pub price_ceiling: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PoolTypeDistribution {
+    pub creator_pools: u64,
+    pub stream_pools: u64,
+    /// SOL locked across native pools (lamports)
+    pub total_reserve: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SimResult {
+    /// Curve cost (buy) or gross refund (sell), fee included
+    pub sol_amount: u64,
+    pub fee: u64,
+    /// SOL the trader pays (buy) or receives (sell)
+    pub net_sol: u64,
+    pub new_supply: u64,
+    pub new_reserve: u64,
+    pub new_price: u64,
+    /// Size of the spot price move in basis points of the current spot price
+    /// (up for buys, down for sells)
+    pub price_impact_bps: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PositionSummary {
+    pub holder: Pubkey,
+    pub balance: u64,
+    pub cost_basis_lamports: u64,
+    pub tokens_bought: u64,
+    pub average_cost: u64,
+    pub spot_price: u64,
+    pub unrealized_pnl: i128,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BuyCostComponents {
+    /// Cost of the tokens at base_price alone
+    pub base_component: u64,
+    /// Rest of the curve integral (slope or growth terms)
+    pub curve_component: u64,
+    /// Share of base_component + curve_component paid to the creator,
+    /// including any anti-sniping surcharge
+    pub fee: u64,
+    /// Buy tax paid into the reserve
+    pub buy_tax: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MarketCap {
+    /// Spot price × total_supply, in lamports
+    pub market_cap: u64,
+    /// Spot price × max_supply, in lamports (0 when uncapped)
+    pub fully_diluted_valuation: u64,
+    /// A value, or the spot price itself, exceeded u64 and was clamped
+    pub saturated: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PoolSummary {
+    pub pool_type: PoolType,
+    pub identifier: String,
+    pub display_name: String,
+    pub creator_wallet: Pubkey,
+    pub total_supply: u64,
+    pub reserve_sol: u64,
+    pub current_price: u64,
+    pub decimals: u8,
+    pub is_active: bool,
+    pub trading_status: u8,
+    pub total_buy_volume: u64,
+    pub total_sell_volume: u64,
+    pub lifetime_fees: u64,
+}
+
+// ============================================================================
+// ACCOUNTS
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(
+    channel_id: String,
+    channel_name: String,
+    metadata_uri: String,
+    base_price: Option<u64>,
+    slope: Option<u64>,
+    generation: u32,
+)]
+pub struct InitializeCreatorPool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = pool_space(0),
+        seeds = [b"creator_pool", channel_id.as_bytes(), &generation_seed(generation)],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// CHECK: Creator wallet to receive fees
+    pub creator_wallet: AccountInfo<'info>,
+    
+    /// Becomes pool.authority; does not pay rent
+    pub authority: Signer<'info>,
+    
+    /// Pays rent for the pool and registry (e.g. a platform sponsoring onboarding)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + CreatorRegistry::INIT_SPACE,
+        seeds = [b"registry", creator_wallet.key().as_ref()],
+        bump
+    )]
+    pub registry: Box<Account<'info, CreatorRegistry>>,
+    
+    /// Protocol-wide pool counters, updated when passed
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Option<Box<Account<'info, GlobalStats>>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    channel_id: String,
+    channel_name: String,
+    metadata_uri: String,
+    base_price: Option<u64>,
+    slope: Option<u64>,
+    generation: u32,
+)]
+pub struct InitializeCreatorPoolAndBuy<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = pool_space(0),
+        seeds = [b"creator_pool", channel_id.as_bytes(), &generation_seed(generation)],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// Receives fees and makes the first buy
+    #[account(mut)]
+    pub creator_wallet: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = creator_wallet,
+        space = 8 + HolderPosition::INIT_SPACE,
+        seeds = [b"position", pool.key().as_ref(), creator_wallet.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, HolderPosition>,
+    
+    /// Becomes pool.authority; does not pay rent
+    pub authority: Signer<'info>,
+    
+    /// Pays rent for the pool and registry
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + CreatorRegistry::INIT_SPACE,
+        seeds = [b"registry", creator_wallet.key().as_ref()],
+        bump
+    )]
+    pub registry: Box<Account<'info, CreatorRegistry>>,
+    
+    /// Protocol-wide pool counters, updated when passed
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Option<Box<Account<'info, GlobalStats>>>,
+    
+    /// CHECK: Insurance fund PDA, paid its share of the creator fee once created
+    #[account(mut, seeds = [b"insurance_fund"], bump)]
+    pub insurance_fund: UncheckedAccount<'info>,
+    
+    /// CHECK: Global config PDA, read for the program-wide pause; may not exist yet
+    #[account(
+        seeds = [b"global_config"],
+        bump,
+        constraint = !globally_paused(&global_config) @ SipzyError::GloballyPaused
+    )]
+    pub global_config: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Legacy initialize_pool accounts; the pool PDA is the generation-0 address
+#[derive(Accounts)]
+#[instruction(youtube_id: String)]
+pub struct InitializePool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = pool_space(0),
+        seeds = [b"creator_pool", youtube_id.as_bytes()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// CHECK: Creator wallet to receive fees
+    pub creator_wallet: AccountInfo<'info>,
+    
+    /// Becomes pool.authority; does not pay rent
+    pub authority: Signer<'info>,
+    
+    /// Pays rent for the pool and registry (e.g. a platform sponsoring onboarding)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + CreatorRegistry::INIT_SPACE,
+        seeds = [b"registry", creator_wallet.key().as_ref()],
+        bump
+    )]
+    pub registry: Box<Account<'info, CreatorRegistry>>,
+    
+    /// Protocol-wide pool counters, updated when passed
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Option<Box<Account<'info, GlobalStats>>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(video_id: String, channel_id: String)]
+pub struct InitializeStreamPool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = pool_space(0),
+        seeds = [b"stream_pool", video_id.as_bytes()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// CHECK: Creator wallet to receive fees
+    pub creator_wallet: AccountInfo<'info>,
+    
+    /// Becomes pool.authority; does not pay rent
+    pub authority: Signer<'info>,
+    
+    /// Pays rent for the pool and registry (e.g. a platform sponsoring onboarding)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + CreatorRegistry::INIT_SPACE,
+        seeds = [b"registry", creator_wallet.key().as_ref()],
+        bump
+    )]
+    pub registry: Box<Account<'info, CreatorRegistry>>,
+    
+    /// Parent channel's Creator pool; when passed, its fee settings are inherited
+    #[account(
+        seeds = [b"creator_pool", channel_id.as_bytes(), &generation_seed(parent_pool.generation)],
+        bump = parent_pool.bump,
+        constraint = parent_pool.pool_type == PoolType::Creator @ SipzyError::InvalidParentPool
+    )]
+    pub parent_pool: Option<Box<Account<'info, Pool>>>,
+    
+    /// Protocol-wide pool counters, updated when passed
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Option<Box<Account<'info, GlobalStats>>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Trade<'info> {
+    #[account(
+        mut,
+        constraint = pool.is_active @ SipzyError::PoolInactive
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = trader,
+        space = 8 + HolderPosition::INIT_SPACE,
+        seeds = [b"position", pool.key().as_ref(), trader.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, HolderPosition>,
+    
+    /// CHECK: Creator wallet for fee transfer, validated against pool state
+    #[account(
+        mut,
+        constraint = creator_wallet.key() == pool.creator_wallet @ SipzyError::InvalidCreatorWallet
+    )]
+    pub creator_wallet: AccountInfo<'info>,
+    
+    /// Optional wallet that receives sell proceeds instead of the trader
+    #[account(mut)]
+    pub recipient: Option<SystemAccount<'info>>,
+    
+    /// Optional price snapshot buffer, updated when the pool has opted in
+    #[account(
+        mut,
+        seeds = [b"snapshot", pool.key().as_ref()],
+        bump = price_snapshot.bump
+    )]
+    pub price_snapshot: Option<Box<Account<'info, PriceSnapshot>>>,
+    
+    /// Optional leaderboard, re-ranked when the pool has opted in
+    #[account(
+        mut,
+        seeds = [b"top_holders", pool.key().as_ref()],
+        bump = top_holders.bump
+    )]
+    pub top_holders: Option<Box<Account<'info, TopHolders>>>,
+    
+    /// CHECK: Insurance fund PDA, paid its share of the creator fee once created
+    #[account(mut, seeds = [b"insurance_fund"], bump)]
+    pub insurance_fund: UncheckedAccount<'info>,
+    
+    /// Reserve vault, required once the pool has enabled it
+    #[account(
+        mut,
+        seeds = [b"reserve", pool.key().as_ref()],
+        bump = pool.reserve_vault_bump
+    )]
+    pub reserve_vault: Option<SystemAccount<'info>>,
+    
+    /// Quote reserve token account, required when the pool has a quote_mint
+    #[account(
+        mut,
+        seeds = [b"quote_reserve", pool.key().as_ref()],
+        bump = pool.quote_reserve_bump
+    )]
+    pub quote_reserve: Option<Box<Account<'info, TokenAccount>>>,
+    
+    /// Trader's quote-token account: pays for buys and receives sell refunds
+    #[account(mut)]
+    pub trader_quote_account: Option<Box<Account<'info, TokenAccount>>>,
+    
+    /// Creator wallet's quote-token account, which receives the fee
+    #[account(mut)]
+    pub creator_quote_account: Option<Box<Account<'info, TokenAccount>>>,
+    
+    pub token_program: Option<Program<'info, Token>>,
+    
+    /// Protocol-wide pool counters, updated when passed
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Option<Box<Account<'info, GlobalStats>>>,
+    
+    /// CHECK: Global config PDA, read for the program-wide pause; may not exist yet
+    #[account(
+        seeds = [b"global_config"],
+        bump,
+        constraint = !globally_paused(&global_config) @ SipzyError::GloballyPaused
+    )]
+    pub global_config: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SellBasket<'info> {
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    
+    /// CHECK: Insurance fund PDA, paid its share of the creator fee once created
+    #[account(mut, seeds = [b"insurance_fund"], bump)]
+    pub insurance_fund: UncheckedAccount<'info>,
+    
+    /// CHECK: Global config PDA, read for the program-wide pause; may not exist yet
+    #[account(
+        seeds = [b"global_config"],
+        bump,
+        constraint = !globally_paused(&global_config) @ SipzyError::GloballyPaused
+    )]
+    pub global_config: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SwapBetweenPools<'info> {
+    #[account(
+        mut,
+        constraint = from_pool.is_active @ SipzyError::PoolInactive
+    )]
+    pub from_pool: Box<Account<'info, Pool>>,
+    
+    #[account(
+        mut,
+        constraint = to_pool.is_active @ SipzyError::PoolInactive,
+        constraint = to_pool.key() != from_pool.key() @ SipzyError::SamePoolSwap
+    )]
+    pub to_pool: Box<Account<'info, Pool>>,
+    
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"position", from_pool.key().as_ref(), trader.key().as_ref()],
+        bump = from_position.bump
+    )]
+    pub from_position: Box<Account<'info, HolderPosition>>,
+    
+    #[account(
+        init_if_needed,
+        payer = trader,
+        space = 8 + HolderPosition::INIT_SPACE,
+        seeds = [b"position", to_pool.key().as_ref(), trader.key().as_ref()],
+        bump
+    )]
+    pub to_position: Box<Account<'info, HolderPosition>>,
+    
+    /// CHECK: Creator wallet paid the sell fee, validated against pool state
+    #[account(
+        mut,
+        constraint = from_creator_wallet.key() == from_pool.creator_wallet @ SipzyError::InvalidCreatorWallet
+    )]
+    pub from_creator_wallet: AccountInfo<'info>,
+    
+    /// CHECK: Creator wallet paid the buy fee, validated against pool state
+    #[account(
+        mut,
+        constraint = to_creator_wallet.key() == to_pool.creator_wallet @ SipzyError::InvalidCreatorWallet
+    )]
+    pub to_creator_wallet: AccountInfo<'info>,
+    
+    /// Reserve vault of the sold pool, required once it has enabled one
+    #[account(
+        mut,
+        seeds = [b"reserve", from_pool.key().as_ref()],
+        bump = from_pool.reserve_vault_bump
+    )]
+    pub from_reserve_vault: Option<SystemAccount<'info>>,
+    
+    /// Reserve vault of the bought pool, required once it has enabled one
+    #[account(
+        mut,
+        seeds = [b"reserve", to_pool.key().as_ref()],
+        bump = to_pool.reserve_vault_bump
+    )]
+    pub to_reserve_vault: Option<SystemAccount<'info>>,
+    
+    /// CHECK: Insurance fund PDA, paid its share of the creator fee once created
+    #[account(mut, seeds = [b"insurance_fund"], bump)]
+    pub insurance_fund: UncheckedAccount<'info>,
+    
+    /// CHECK: Global config PDA, read for the program-wide pause; may not exist yet
+    #[account(
+        seeds = [b"global_config"],
+        bump,
+        constraint = !globally_paused(&global_config) @ SipzyError::GloballyPaused
+    )]
+    pub global_config: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePriceSnapshot<'info> {
+    #[account(
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + PriceSnapshot::INIT_SPACE,
+        seeds = [b"snapshot", pool.key().as_ref()],
+        bump
+    )]
+    pub price_snapshot: Box<Account<'info, PriceSnapshot>>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTopHolders<'info> {
+    #[account(
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + TopHolders::INIT_SPACE,
+        seeds = [b"top_holders", pool.key().as_ref()],
+        bump
+    )]
+    pub top_holders: Box<Account<'info, TopHolders>>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshTopHolder<'info> {
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"top_holders", pool.key().as_ref()],
+        bump = top_holders.bump
+    )]
+    pub top_holders: Box<Account<'info, TopHolders>>,
+    
+    #[account(
+        seeds = [b"position", pool.key().as_ref(), position.owner.as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, HolderPosition>,
+}
+
+#[derive(Accounts)]
+pub struct GetTopHolders<'info> {
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        seeds = [b"top_holders", pool.key().as_ref()],
+        bump = top_holders.bump
+    )]
+    pub top_holders: Box<Account<'info, TopHolders>>,
+}
+
+#[derive(Accounts)]
+pub struct GetPoolSummary<'info> {
+    pub pool: Account<'info, Pool>,
+    
+    /// CHECK: Global config PDA, read for the program-wide pause; may not exist yet
+    #[account(seeds = [b"global_config"], bump)]
+    pub global_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetPoolInfo<'info> {
+    pub pool: Account<'info, Pool>,
+}
+
+#[derive(Accounts)]
+pub struct GetGlobalStats<'info> {
+    #[account(seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+}
+
+#[derive(Accounts)]
+pub struct GetChannelStreams<'info> {
+    pub registry: Box<Account<'info, CreatorRegistry>>,
+}
+
+#[derive(Accounts)]
+#[instruction(holder: Pubkey)]
+pub struct GetPosition<'info> {
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        seeds = [b"position", pool.key().as_ref(), holder.as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, HolderPosition>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    #[account(
+        mut,
+        close = creator,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry", creator.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, CreatorRegistry>>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    /// Protocol-wide pool counters, updated when passed
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Option<Box<Account<'info, GlobalStats>>>,
+    
+    /// Reserve vault, required once the pool has enabled it
+    #[account(
+        mut,
+        seeds = [b"reserve", pool.key().as_ref()],
+        bump = pool.reserve_vault_bump
+    )]
+    pub reserve_vault: Option<SystemAccount<'info>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConvertPoolType<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry", creator.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, CreatorRegistry>>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    /// Protocol-wide pool counters, updated when passed
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Option<Box<Account<'info, GlobalStats>>>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptCreatorWallet<'info> {
+    #[account(
+        mut,
+        constraint = pool.pending_creator_wallet == new_creator_wallet.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry", pool.creator_wallet.as_ref()],
+        bump = old_registry.bump
+    )]
+    pub old_registry: Box<Account<'info, CreatorRegistry>>,
+    
+    #[account(
+        init_if_needed,
+        payer = new_creator_wallet,
+        space = 8 + CreatorRegistry::INIT_SPACE,
+        seeds = [b"registry", new_creator_wallet.key().as_ref()],
+        bump
+    )]
+    pub new_registry: Box<Account<'info, CreatorRegistry>>,
+    
+    #[account(mut)]
+    pub new_creator_wallet: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintFounderAllocation<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + HolderPosition::INIT_SPACE,
+        seeds = [b"position", pool.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, HolderPosition>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreatorBuyback<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    /// Reserve vault, required once the pool has enabled it
+    #[account(
+        mut,
+        seeds = [b"reserve", pool.key().as_ref()],
+        bump = pool.reserve_vault_bump
+    )]
+    pub reserve_vault: Option<SystemAccount<'info>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AuthorityManagePool<'info> {
+    #[account(
+        mut,
+        constraint = pool.authority == authority.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(holder: Pubkey)]
+pub struct FreezePosition<'info> {
+    #[account(
+        constraint = pool.authority == authority.key() @ SipzyError::Unauthorized,
+        constraint = pool.compliance_enabled @ SipzyError::ComplianceDisabled
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + HolderPosition::INIT_SPACE,
+        seeds = [b"position", pool.key().as_ref(), holder.as_ref()],
+        bump
+    )]
+    pub position: Account<'info, HolderPosition>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetActiveBatch<'info> {
+    pub creator: Signer<'info>,
+    
+    /// CHECK: Global config PDA, read for the program-wide pause; may not exist yet
+    #[account(seeds = [b"global_config"], bump)]
+    pub global_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SweepResidualReserve<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    /// Reserve vault, required once the pool has enabled it
+    #[account(
+        mut,
+        seeds = [b"reserve", pool.key().as_ref()],
+        bump = pool.reserve_vault_bump
+    )]
+    pub reserve_vault: Option<SystemAccount<'info>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManagePool<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReactivatePool<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    pub creator: Signer<'info>,
+    
+    /// CHECK: Global config PDA, read for the program-wide pause; may not exist yet
+    #[account(seeds = [b"global_config"], bump)]
+    pub global_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGlobalConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GlobalConfig::INIT_SPACE,
+        seeds = [b"global_config"],
+        bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ SipzyError::Unauthorized
+    )]
+    pub program: Program<'info, crate::program::SipzyVault>,
+    
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ SipzyError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGlobalConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ SipzyError::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyWithdrawReserve<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ SipzyError::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    pub admin: Signer<'info>,
+    
+    /// Recovery address that receives the rescued lamports
+    #[account(mut)]
+    pub destination: SystemAccount<'info>,
+    
+    /// Reserve vault, required once the pool has enabled it
+    #[account(
+        mut,
+        seeds = [b"reserve", pool.key().as_ref()],
+        bump = pool.reserve_vault_bump
+    )]
+    pub reserve_vault: Option<SystemAccount<'info>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClearEmergencyWithdrawal<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ SipzyError::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeInsuranceFund<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + InsuranceFund::INIT_SPACE,
+        seeds = [b"insurance_fund"],
+        bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+    
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ SipzyError::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGlobalStats<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats"],
+        bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+    
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ SipzyError::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateInsuranceFund<'info> {
+    #[account(
+        mut,
+        seeds = [b"insurance_fund"],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+    
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ SipzyError::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SnapshotHolders<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + HolderSnapshot::INIT_SPACE,
+        seeds = [
+            b"holder_snapshot",
+            pool.key().as_ref(),
+            &(pool.holder_snapshot_count + 1).to_le_bytes()
+        ],
+        bump
+    )]
+    pub snapshot: Account<'info, HolderSnapshot>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(snapshot_id: u64)]
+pub struct ClaimAirdrop<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_snapshot", pool.key().as_ref(), &snapshot_id.to_le_bytes()],
+        bump = snapshot.bump
+    )]
+    pub snapshot: Account<'info, HolderSnapshot>,
+    
+    #[account(
+        mut,
+        seeds = [b"position", pool.key().as_ref(), holder.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, HolderPosition>,
+    
+    #[account(mut)]
+    pub holder: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(snapshot_id: u64)]
+pub struct CloseHolderSnapshot<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"holder_snapshot", pool.key().as_ref(), &snapshot_id.to_le_bytes()],
+        bump = snapshot.bump
+    )]
+    pub snapshot: Account<'info, HolderSnapshot>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimInsurance<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"insurance_fund"],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+    
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ SipzyError::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    pub admin: Signer<'info>,
+    
+    /// Reserve vault, required once the pool has enabled it
+    #[account(
+        mut,
+        seeds = [b"reserve", pool.key().as_ref()],
+        bump = pool.reserve_vault_bump
+    )]
+    pub reserve_vault: Option<SystemAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyPool<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    /// CHECK: Instructions sysvar, address-checked
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetWhitelist<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + Whitelist::INIT_SPACE,
+        seeds = [b"whitelist", pool.key().as_ref()],
+        bump
+    )]
+    pub whitelist: Account<'info, Whitelist>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProveWhitelisted<'info> {
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        seeds = [b"whitelist", pool.key().as_ref()],
+        bump = whitelist.bump
+    )]
+    pub whitelist: Account<'info, Whitelist>,
+    
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = trader,
+        space = 8 + HolderPosition::INIT_SPACE,
+        seeds = [b"position", pool.key().as_ref(), trader.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, HolderPosition>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetQuoteMint<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    pub quote_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"quote_reserve", pool.key().as_ref()],
+        bump,
+        token::mint = quote_mint,
+        token::authority = quote_reserve
+    )]
+    pub quote_reserve: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EnableReserveVault<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"reserve", pool.key().as_ref()],
+        bump
+    )]
+    pub reserve_vault: SystemAccount<'info>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MultisigAuthority::INIT_SPACE,
+        seeds = [b"multisig", base.key().as_ref()],
+        bump
+    )]
+    pub multisig: Account<'info, MultisigAuthority>,
+    
+    /// Any one-off key; only used to derive a unique multisig address
+    pub base: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAction<'info> {
+    #[account(mut)]
+    pub multisig: Account<'info, MultisigAuthority>,
+    
+    #[account(
+        constraint = pool.creator_wallet == multisig.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + MultisigProposal::INIT_SPACE,
+        seeds = [b"proposal", multisig.key().as_ref(), &multisig.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    pub multisig: Account<'info, MultisigAuthority>,
+    
+    #[account(mut, has_one = multisig)]
+    pub proposal: Account<'info, MultisigProposal>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut)]
+    pub multisig: Account<'info, MultisigAuthority>,
+    
+    #[account(mut, has_one = multisig, has_one = pool)]
+    pub proposal: Account<'info, MultisigProposal>,
+    
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == multisig.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(mut)]
+    pub executor: Signer<'info>,
+    
+    /// CHECK: Withdraw destination; must match the proposal
+    #[account(mut)]
+    pub destination: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Global config PDA, read for the program-wide pause; may not exist yet
+    #[account(seeds = [b"global_config"], bump)]
+    pub global_config: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadataUri<'info> {
+    #[account(
+        mut,
+        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {
+    /// Type of pool (Creator or Stream)
+    pub pool_type: PoolType,
+    
+    /// Unique identifier (channel_id for Creator, video_id for Stream)
+    #[max_len(32)]
+    pub identifier: String,
+    
+    /// Display name (channel name or video title)
+    #[max_len(64)]
+    pub display_name: String,
+    
+    /// Parent identifier (channel_id for Stream pools, empty for Creator)
+    #[max_len(32)]
+    pub parent_identifier: String,
+    
+    /// Creator wallet address for fee distribution
+    pub creator_wallet: Pubkey,
+    
+    /// Pool authority (who initialized it)
+    pub authority: Pubkey,
+    
+    /// Total tokens in circulation
+    pub total_supply: u64,
+    
+    /// SOL locked in the bonding curve reserve (lamports)
+    pub reserve_sol: u64,
+    
+    /// Base price in lamports
+    pub base_price: u64,
+    
+    /// Curve parameter: slope (linear) or growth_rate in bps (exponential)
+    pub curve_param: u64,
+    
+    /// IPFS URI for token metadata
+    #[max_len(200)] // MAX_METADATA_URI_LEN; allocated to fit, see pool_space
+    pub metadata_uri: String,
+    
+    /// PDA bump seed
+    pub bump: u8,
+    
+    /// Unix timestamp of creation
+    pub created_at: i64,
+    
+    /// Whether pool is active for trading
+    pub is_active: bool,
+    
+    /// Maximum SOL the reserve may hold (lamports, 0 = uncapped)
+    pub reserve_cap: u64,
+    
+    /// Token decimals: supply and amounts are in base units of 10^-decimals tokens
+    pub decimals: u8,
+    
+    /// Spot prices are rounded down to a multiple of this (lamports, 0 = exact)
+    pub tick_size: u64,
+    
+    /// Whether the one-time founder allocation has been minted
+    pub founder_minted: bool,
+    
+    /// Curve cost of tokens minted without SOL backing (lamports)
+    pub reserve_debt: u64,
+    
+    /// SOL the creator has spent on buyback-and-burn (lamports)
+    pub total_buyback_sol: u64,
+    
+    /// Lifetime SOL paid by buyers, fees included (lamports)
+    pub total_buy_volume: u64,
+    
+    /// Lifetime gross SOL refunded to sellers, fees included (lamports)
+    pub total_sell_volume: u64,
+    
+    /// Lifetime trading fees paid to the creator (lamports)
+    pub lifetime_fees: u64,
+    
+    /// Reject sells in the same slot as the holder's last buy
+    pub same_slot_sell_block: bool,
+    
+    /// Proposed creator wallet awaiting acceptance (default = none)
+    pub pending_creator_wallet: Pubkey,
+    
+    /// Tokens minted by mint_founder_allocation
+    pub founder_allocation: u64,
+    
+    /// Unix timestamp the founder allocation started vesting
+    pub founder_vest_start: i64,
+    
+    /// Seconds for the founder allocation to vest linearly (0 = no vesting)
+    pub founder_vest_duration: u64,
+    
+    /// Founder-allocation tokens the founder has already sold
+    pub founder_vested_sold: u64,
+    
+    /// Sell-fee discounts by holding time (all zero = flat fee)
+    pub loyalty_tiers: [LoyaltyTier; MAX_LOYALTY_TIERS],
+    
+    /// Reserve (lamports) at which the pool graduates (0 = never)
+    pub graduation_threshold: u64,
+    
+    /// Curve buys have ended; sells remain open
+    pub is_graduated: bool,
+    
+    /// Trading fee paid to the creator, in basis points
+    pub fee_bps: u64,
+    
+    /// Extra buy fee at launch, decaying to zero by launch_end (basis points)
+    pub snipe_fee_bps: u64,
+    
+    /// Length of the anti-sniping window in seconds (0 = disabled)
+    pub snipe_window_secs: u64,
+    
+    /// Unix timestamp the anti-sniping window closes
+    pub launch_end: i64,
+    
+    /// Authority may freeze individual positions (opt-in before trading)
+    pub compliance_enabled: bool,
+    
+    /// Large sells are priced as a pro-rata share of the reserve
+    pub reserve_backed_sells: bool,
+    
+    /// Creator pool incarnation for this channel (part of the PDA seed)
+    pub generation: u32,
+    
+    /// Stream pools stop accepting buys at this unix timestamp (0 = never)
+    pub expires_at: i64,
+    
+    /// Supply cap (0 = uncapped); can only be raised once trading starts
+    pub max_supply: u64,
+    
+    /// SOL deposited by buys and buybacks minus SOL withdrawn by sells and sweeps
+    pub net_deposited: u64,
+    
+    /// Oracle attested that the identifier belongs to creator_wallet
+    pub verified: bool,
+    
+    /// Exponential curve fixed-point precision, as a power of ten (9, 12 or 15)
+    pub precision_exp: u8,
+    
+    /// Buys and sells are halted until this timestamp (0 = never paused)
+    pub pause_until: i64,
+    
+    /// Buy surcharge kept in the reserve as extra backing (bps of curve cost)
+    pub buy_tax_bps: u16,
+    
+    /// Extra sell fee for a just-bought position in basis points, decaying to 0
+    pub max_sell_penalty_bps: u64,
+    
+    /// Holding time after which the sell penalty reaches 0 (0 = disabled)
+    pub penalty_window_secs: u64,
+    
+    /// Reserve moved out by emergency_withdraw_reserve, in total
+    pub emergency_withdrawn: u64,
+    
+    /// Most SOL buys may spend per rate-limit window (0 = unlimited)
+    pub max_notional_per_window: u64,
+    
+    /// Length of the rate-limit window in seconds
+    pub window_secs: i64,
+    
+    /// SOL spent on buys in the current window
+    pub window_notional: u64,
+    
+    /// Unix timestamp the current window opened
+    pub window_start: i64,
+    
+    /// Share of each sell burned instead of returned to the curve, in bps
+    pub burn_on_sell_bps: u16,
+    
+    /// Tokens burned on sells; the curve prices from total_supply + burned_supply
+    pub burned_supply: u64,
+    
+    /// How metadata_uri resolves, a MetadataFormat discriminant
+    pub metadata_format: u8,
+    
+    /// Reserve buys top up to, as bps of the full sell value (0 = off)
+    pub min_reserve_ratio_bps: u16,
+    
+    /// Stream pools: price at which the curve flattens (0 = uncapped)
+    pub price_ceiling: u64,
+    
+    /// Trades of at most this many tokens pay no pool fee (0 = off)
+    pub fee_free_threshold: u64,
+    
+    /// Sequence number of the pool's latest event; every pool event carries
+    /// the next one, so a jump of more than 1 means an event was missed
+    pub seq: u64,
+    
+    /// Seconds a wallet must wait between buys (0 = no cooldown)
+    pub buy_cooldown_secs: i64,
+    
+    /// Whether the reserve is held in the reserve vault PDA instead of the pool
+    pub reserve_vault_enabled: bool,
+    
+    /// Bump of the reserve vault PDA (set when enabled)
+    pub reserve_vault_bump: u8,
+    
+    /// Until this timestamp only whitelisted wallets may buy (0 = open)
+    pub whitelist_until: i64,
+    
+    /// Sells are accepted from this timestamp (0 = from launch)
+    pub sells_open_from: i64,
+    
+    /// Sells are accepted until this timestamp (0 = no end)
+    pub sells_open_until: i64,
+    
+    /// SPL mint the pool is priced in (None = native SOL); reserve_sol and
+    /// every other SOL amount are then in this token's base units
+    pub quote_mint: Option<Pubkey>,
+    
+    /// Bump of the quote reserve token account PDA (set with quote_mint)
+    pub quote_reserve_bump: u8,
+    
+    /// Emit TokensTraded on every trade (lifecycle events are always emitted)
+    pub emit_events: bool,
+    
+    /// Holder snapshots taken so far; the latest one's ID
+    pub holder_snapshot_count: u64,
+    
+    /// End of the latest holder snapshot's claim window
+    pub airdrop_claim_until: i64,
+    
+    /// Tolerance buy_tokens_auto_slippage allows over the spot-price cost (bps)
+    pub default_slippage_bps: u16,
+    
+    /// Metadata URI and format are locked for good
+    pub metadata_frozen: bool,
+    
+    /// Largest spot price move a single buy may cause (bps, 0 = no cap)
+    pub max_price_impact_bps: u16,
+    
+    /// Highest fee set_fee_bps may set; decrease_fee lowers it for good
+    pub fee_ceiling_bps: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct HolderPosition {
+    /// Pool this position belongs to
+    pub pool: Pubkey,
+    
+    /// Wallet that owns the tokens
+    pub owner: Pubkey,
+    
+    /// Tokens currently held
+    pub balance: u64,
+    
+    /// PDA bump seed
+    pub bump: u8,
+    
+    /// Slot of the most recent buy
+    pub last_buy_slot: u64,
+    
+    /// Holds the founder allocation, so sells are subject to vesting
+    pub is_founder: bool,
+    
+    /// Amount-weighted unix timestamp the current balance was acquired
+    pub acquired_at: i64,
+    
+    /// SOL spent on buys, fees included, reduced pro rata on sells (lamports)
+    pub cost_basis_lamports: u64,
+    
+    /// Tokens bought on the curve, reduced pro rata on sells
+    pub tokens_bought: u64,
+    
+    /// Blocked from trading by the pool authority (compliance pools only)
+    pub frozen: bool,
+    
+    /// Ring buffer of recent nonzero client order IDs
+    pub recent_order_ids: [u64; RECENT_ORDER_IDS],
+    
+    /// Next slot to overwrite in recent_order_ids
+    pub order_id_cursor: u8,
+    
+    /// Unix timestamp of the most recent buy
+    pub last_buy_ts: i64,
+    
+    /// Whether the owner proved membership of the pool's presale whitelist
+    pub whitelisted: bool,
+    
+    /// ID of the latest holder snapshot whose airdrop this position claimed
+    pub last_airdrop_claimed: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PriceSnapshot {
+    /// Pool being charted
+    pub pool: Pubkey,
+    
+    /// Minimum seconds between recorded entries
+    pub interval_secs: i64,
+    
+    /// Timestamp of the most recent entry
+    pub last_snapshot_ts: i64,
+    
+    /// SOL volume traded since the most recent entry (lamports)
+    pub pending_volume: u64,
+    
+    /// Index the next entry will be written to
+    pub head: u16,
+    
+    /// Number of valid entries (saturates at the buffer size)
+    pub count: u16,
+    
+    /// Ring buffer of recorded entries
+    pub entries: [SnapshotEntry; MAX_SNAPSHOTS],
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct LoyaltyTier {
+    /// Minimum amount-weighted holding time, in seconds
+    pub min_hold_secs: u64,
+    /// Share of the sell fee waived, in basis points of the fee
+    pub discount_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct SnapshotEntry {
+    pub timestamp: i64,
+    pub price: u64,
+    pub volume: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TopHolders {
+    /// Pool being ranked
+    pub pool: Pubkey,
+    
+    /// Number of valid entries
+    pub count: u8,
+    
+    /// Holders sorted by balance, largest first
+    pub entries: [HolderEntry; MAX_TOP_HOLDERS],
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct HolderEntry {
+    pub owner: Pubkey,
+    pub balance: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CreatorRegistry {
+    /// Creator wallet whose pools are listed
+    pub creator_wallet: Pubkey,
+    
+    /// Pool accounts (Creator and Stream) launched for this creator
+    #[max_len(64)]
+    pub pools: Vec<Pubkey>,
+    
+    /// PDA bump seed
+    pub bump: u8,
+    
+    /// The Stream pools among `pools`, each with its parent channel
+    #[max_len(64)]
+    pub streams: Vec<ChannelStream>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ChannelStream {
+    /// Parent channel (the Stream pool's parent_identifier)
+    #[max_len(32)]
+    pub channel_id: String,
+    
+    pub pool: Pubkey,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalConfig {
+    /// May update the config
+    pub admin: Pubkey,
+    
+    /// Key whose Ed25519 attestations mark pools verified
+    pub oracle: Pubkey,
+    
+    /// Program-wide emergency state; required for emergency_withdraw_reserve
+    pub paused: bool,
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct InsuranceFund {
+    /// Share of every SOL trade's creator fee paid in (bps)
+    pub insurance_fee_bps: u16,
+    
+    /// Lifetime SOL paid in by trades (lamports)
+    pub total_contributed: u64,
+    
+    /// Lifetime SOL paid out to pool reserves (lamports)
+    pub total_claimed: u64,
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct HolderSnapshot {
+    /// Pool whose holders were snapshotted
+    pub pool: Pubkey,
+    
+    /// Sequential ID within the pool, starting at 1
+    pub snapshot_id: u64,
+    
+    /// Pool total_supply when the snapshot was taken
+    pub total_supply: u64,
+    
+    /// Unix timestamp the snapshot was taken
+    pub taken_at: i64,
+    
+    /// Slot the snapshot was taken; buys from this slot on disqualify a claim
+    pub taken_slot: u64,
+    
+    /// Claims are accepted until this timestamp
+    pub claim_until: i64,
+    
+    /// Airdrop escrowed in this account (lamports)
+    pub reward: u64,
+    
+    /// Paid out to holders so far (lamports)
+    pub claimed: u64,
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalStats {
+    /// Live Creator pools
+    pub creator_pools: u64,
+    
+    /// Live Stream pools
+    pub stream_pools: u64,
+    
+    /// SOL held in the reserves of live native pools (lamports)
+    pub total_reserve: u64,
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Whitelist {
+    /// Pool whose presale this gates
+    pub pool: Pubkey,
+    
+    /// Merkle root over sha256(wallet) leaves
+    pub merkle_root: [u8; 32],
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MultisigAuthority {
+    /// Key the PDA is derived from
+    pub base: Pubkey,
+    
+    /// Keys allowed to propose and approve
+    #[max_len(5)] // MAX_MULTISIG_SIGNERS
+    pub signers: Vec<Pubkey>,
+    
+    /// Approvals needed to execute a proposal
+    pub threshold: u8,
+    
+    /// Proposals created so far; seeds the next proposal's PDA
+    pub proposal_count: u64,
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MultisigProposal {
+    pub multisig: Pubkey,
+    
+    /// Pool the action applies to
+    pub pool: Pubkey,
+    
+    /// Sequence number within the multisig
+    pub index: u64,
+    
+    pub action: ProposalAction,
+    
+    /// Bitmap of approving signers, by index in MultisigAuthority::signers
+    pub approvals: u8,
+    
+    pub executed: bool,
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct PoolNotRegistered {
+    pub creator_wallet: Pubkey,
+    pub pool: Pubkey,
+}
+
+#[event]
+pub struct PoolCreated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub pool_type: PoolType,
+    pub identifier: String,
+    pub creator_wallet: Pubkey,
+    pub base_price: u64,
+    pub curve_param: u64,
+    /// Pool PDA bump, so event consumers can skip find_program_address
+    pub bump: u8,
+    /// Distinguishes relaunches of the same channel (0 for stream pools)
+    pub generation: u32,
+}
+
+#[event]
+pub struct TokensTraded {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub trader: Pubkey,
+    pub trade_type: TradeType,
+    pub amount: u64,
+    pub requested_amount: u64,
+    pub filled_amount: u64,
+    pub sol_amount: u64,
+    /// Total fee; always creator_fee + protocol_fee + referral_fee
+    pub fee: u64,
+    pub creator_fee: u64,
+    /// Protocol and referral cuts; zero until the program takes them
+    pub protocol_fee: u64,
+    pub referral_fee: u64,
+    /// Buy tax paid into the reserve (zero for sells)
+    pub buy_tax: u64,
+    /// Sold tokens burned into burned_supply (zero for buys)
+    pub burned: u64,
+    /// Creator fee deposited into the reserve to hold min_reserve_ratio_bps
+    /// (zero for sells); creator_fee is what the creator received after it
+    pub reserve_top_up: u64,
+    pub new_supply: u64,
+    pub new_reserve: u64,
+    pub pricing: SellPricing,
+    /// The pool's token decimals; token amounts above are in base units
+    pub decimals: u8,
+}
+
+#[event]
+pub struct PoolStatusChanged {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub is_active: bool,
+}
+
+#[event]
+pub struct PoolClosedStats {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub final_supply: u64,
+    pub final_reserve: u64,
+    pub total_buy_volume: u64,
+    pub total_sell_volume: u64,
+    pub lifetime_fees: u64,
+}
+
+#[event]
+pub struct PoolClosed {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub creator_wallet: Pubkey,
+    pub residual_reserve: u64,
+}
+
+#[event]
+pub struct FounderAllocation {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub creator_wallet: Pubkey,
+    pub amount: u64,
+    pub reserve_debt: u64,
+}
+
+#[event]
+pub struct Buyback {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub creator_wallet: Pubkey,
+    pub amount: u64,
+    pub sol_spent: u64,
+    pub new_reserve: u64,
+}
+
+#[event]
+pub struct CreatorWalletProposed {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub current_creator_wallet: Pubkey,
+    pub pending_creator_wallet: Pubkey,
+}
+
+#[event]
+pub struct CreatorWalletChanged {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub previous_creator_wallet: Pubkey,
+    pub new_creator_wallet: Pubkey,
+}
+
+#[event]
+pub struct SameSlotSellBlockUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct ReserveCapUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub reserve_cap: u64,
+}
+
+#[event]
+pub struct DecimalsUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub decimals: u8,
+}
+
+#[event]
+pub struct TickSizeUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub tick_size: u64,
+}
+
+#[event]
+pub struct ExpPrecisionUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub precision_exp: u8,
+}
+
+#[event]
+pub struct LegacyPoolUpgraded {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub display_name: String,
+    pub metadata_uri: String,
+}
+
+#[event]
+pub struct MetadataUriUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub metadata_uri: String,
+    pub metadata_format: u8,
+}
+
+#[event]
+pub struct FounderVestingUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub duration_secs: u64,
+}
+
+#[event]
+pub struct LoyaltyTiersUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub tiers: [LoyaltyTier; MAX_LOYALTY_TIERS],
+}
+
+#[event]
+pub struct GraduationThresholdUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub graduation_threshold: u64,
+}
+
+#[event]
+pub struct PoolGraduated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub total_supply: u64,
+    pub reserve_sol: u64,
+}
+
+#[event]
+pub struct GraduatedRedemption {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub sol_amount: u64,
+    pub new_supply: u64,
+    pub new_reserve: u64,
+}
+
+#[event]
+pub struct ResidualSwept {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub creator_wallet: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeeUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub fee_bps: u64,
+}
+
+#[event]
+pub struct BuyTaxUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub buy_tax_bps: u16,
+}
+
+#[event]
+pub struct FeeFreeThresholdUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub fee_free_threshold: u64,
+}
+
+#[event]
+pub struct PriceCeilingUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub price_ceiling: u64,
+}
+
+#[event]
+pub struct PoolTypeConverted {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub previous_type: PoolType,
+    pub new_type: PoolType,
+    pub base_price: u64,
+    pub curve_param: u64,
+}
+
+#[event]
+pub struct MinReserveRatioUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub min_reserve_ratio_bps: u16,
+}
+
+#[event]
+pub struct BurnOnSellUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub burn_on_sell_bps: u16,
+}
+
+#[event]
+pub struct GlobalConfigUpdated {
+    pub admin: Pubkey,
+    pub oracle: Pubkey,
+}
+
+#[event]
+pub struct GlobalPauseSet {
+    pub paused: bool,
+}
+
+#[event]
+pub struct EmergencyWithdrawal {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub admin: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub remaining_reserve: u64,
+    pub total_withdrawn: u64,
+}
+
+#[event]
+pub struct EmergencyWithdrawalCleared {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub admin: Pubkey,
+    /// emergency_withdrawn before it was reset
+    pub cleared: u64,
+}
+
+#[event]
+pub struct InsuranceFeeUpdated {
+    pub insurance_fee_bps: u16,
+}
+
+#[event]
+pub struct InsuranceContributed {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub amount: u64,
+    pub total_contributed: u64,
+}
+
+#[event]
+pub struct HolderSnapshotTaken {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub snapshot_id: u64,
+    pub total_supply: u64,
+    pub reward: u64,
+    pub claim_until: i64,
+}
+
+#[event]
+pub struct AirdropClaimed {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub snapshot_id: u64,
+    pub holder: Pubkey,
+    pub balance: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct HolderSnapshotClosed {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub snapshot_id: u64,
+    pub unclaimed: u64,
+}
+
+#[event]
+pub struct InsuranceClaimed {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub amount: u64,
+    pub new_reserve: u64,
+    pub total_claimed: u64,
+}
+
+#[event]
+pub struct PoolVerified {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub identifier: String,
+    pub creator_wallet: Pubkey,
+}
+
+#[event]
+pub struct ReserveVaultEnabled {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub reserve_vault: Pubkey,
+    /// Reserve moved from the pool into the vault
+    pub moved_reserve: u64,
+}
+
+#[event]
+pub struct QuoteMintSet {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub quote_mint: Pubkey,
+    pub quote_reserve: Pubkey,
+}
+
+#[event]
+pub struct MultisigCreated {
+    pub multisig: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct ActionProposed {
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub pool: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub signer: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub pool: Pubkey,
+    pub executor: Pubkey,
+}
+
+#[event]
+pub struct MaxSupplyUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub max_supply: u64,
+}
+
+#[event]
+pub struct ExpirySet {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub expires_at: i64,
 }
 
-// ============================================================================
-// ENUMS
-// ============================================================================
+#[event]
+pub struct WhitelistUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub merkle_root: [u8; 32],
+    pub whitelist_until: i64,
+}
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
-pub enum PoolType {
-    Creator, // Linear bonding curve - long-term equity
-    Stream,  // Exponential bonding curve - event hype
+#[event]
+pub struct PriceImpactCapUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub max_price_impact_bps: u16,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum TradeType {
-    Buy,
-    Sell,
+#[event]
+pub struct MetadataFrozen {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub metadata_uri: String,
 }
 
-// ============================================================================
-// ACCOUNTS
-// ============================================================================
+#[event]
+pub struct DefaultSlippageUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub default_slippage_bps: u16,
+}
 
-#[derive(Accounts)]
-#[instruction(channel_id: String)]
-pub struct InitializeCreatorPool<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Pool::INIT_SPACE,
-        seeds = [b"creator_pool", channel_id.as_bytes()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
-    
-    /// CHECK: Creator wallet to receive fees
-    pub creator_wallet: AccountInfo<'info>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct EmitEventsUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub emit_events: bool,
 }
 
-#[derive(Accounts)]
-#[instruction(video_id: String)]
-pub struct InitializeStreamPool<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Pool::INIT_SPACE,
-        seeds = [b"stream_pool", video_id.as_bytes()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
-    
-    /// CHECK: Creator wallet to receive fees
-    pub creator_wallet: AccountInfo<'info>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct SellWindowUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub sells_open_from: i64,
+    pub sells_open_until: i64,
 }
 
-#[derive(Accounts)]
-pub struct Trade<'info> {
-    #[account(
-        mut,
-        constraint = pool.is_active @ SipzyError::PoolInactive
-    )]
-    pub pool: Account<'info, Pool>,
-    
-    #[account(mut)]
-    pub trader: Signer<'info>,
-    
-    /// CHECK: Creator wallet for fee transfer, validated against pool state
-    #[account(
-        mut,
-        constraint = creator_wallet.key() == pool.creator_wallet @ SipzyError::InvalidCreatorWallet
-    )]
-    pub creator_wallet: AccountInfo<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct BuyCooldownUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub buy_cooldown_secs: i64,
 }
 
-#[derive(Accounts)]
-pub struct GetPoolInfo<'info> {
-    pub pool: Account<'info, Pool>,
+#[event]
+pub struct RateLimitUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub max_notional_per_window: u64,
+    pub window_secs: i64,
 }
 
-#[derive(Accounts)]
-pub struct ManagePool<'info> {
-    #[account(
-        mut,
-        constraint = pool.creator_wallet == creator.key() @ SipzyError::Unauthorized
-    )]
-    pub pool: Account<'info, Pool>,
-    
-    pub creator: Signer<'info>,
+#[event]
+pub struct TradingPauseSet {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub paused_at: i64,
+    pub pause_until: i64,
 }
 
-// ============================================================================
-// STATE
-// ============================================================================
+#[event]
+pub struct ReserveBackedSellsUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub enabled: bool,
+}
 
-#[account]
-#[derive(InitSpace)]
-pub struct Pool {
-    /// Type of pool (Creator or Stream)
-    pub pool_type: PoolType,
-    
-    /// Unique identifier (channel_id for Creator, video_id for Stream)
-    #[max_len(32)]
-    pub identifier: String,
-    
-    /// Display name (channel name or video title)
-    #[max_len(64)]
-    pub display_name: String,
-    
-    /// Parent identifier (channel_id for Stream pools, empty for Creator)
-    #[max_len(32)]
-    pub parent_identifier: String,
-    
-    /// Creator wallet address for fee distribution
-    pub creator_wallet: Pubkey,
-    
-    /// Pool authority (who initialized it)
-    pub authority: Pubkey,
-    
-    /// Total tokens in circulation
-    pub total_supply: u64,
-    
-    /// SOL locked in the bonding curve reserve (lamports)
-    pub reserve_sol: u64,
-    
-    /// Base price in lamports
-    pub base_price: u64,
-    
-    /// Curve parameter: slope (linear) or growth_rate in bps (exponential)
-    pub curve_param: u64,
-    
-    /// IPFS URI for token metadata
-    #[max_len(200)]
-    pub metadata_uri: String,
-    
-    /// PDA bump seed
-    pub bump: u8,
-    
-    /// Unix timestamp of creation
-    pub created_at: i64,
-    
-    /// Whether pool is active for trading
-    pub is_active: bool,
+#[event]
+pub struct ComplianceUpdated {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub enabled: bool,
 }
 
-// ============================================================================
-// EVENTS
-// ============================================================================
+#[event]
+pub struct PositionFrozen {
+    pub pool: Pubkey,
+    pub seq: u64,
+    pub holder: Pubkey,
+}
 
 #[event]
-pub struct PoolCreated {
+pub struct PositionUnfrozen {
     pub pool: Pubkey,
-    pub pool_type: PoolType,
-    pub identifier: String,
-    pub creator_wallet: Pubkey,
-    pub base_price: u64,
-    pub curve_param: u64,
+    pub seq: u64,
+    pub holder: Pubkey,
 }
 
 #[event]
-pub struct TokensTraded {
+pub struct SellPenaltyUpdated {
     pub pool: Pubkey,
-    pub trader: Pubkey,
-    pub trade_type: TradeType,
-    pub amount: u64,
-    pub sol_amount: u64,
-    pub fee: u64,
-    pub new_supply: u64,
-    pub new_reserve: u64,
+    pub seq: u64,
+    pub max_sell_penalty_bps: u64,
+    pub window_secs: u64,
 }
 
 #[event]
-pub struct PoolStatusChanged {
+pub struct SnipeFeeUpdated {
     pub pool: Pubkey,
-    pub is_active: bool,
+    pub seq: u64,
+    pub snipe_fee_bps: u64,
+    pub window_secs: u64,
+    pub launch_end: i64,
 }
 
 // ============================================================================
@@ -763,4 +7675,244 @@ pub enum SipzyError {
     
     #[msg("Unauthorized: only creator can perform this action")]
     Unauthorized,
+    
+    #[msg("Reserve cap reached: excess SOL is rejected rather than diverted, since a diverted reserve could not back curve-priced sells")]
+    ReserveCapReached,
+    
+    #[msg("Insufficient token balance in holder position")]
+    InsufficientBalance,
+    
+    #[msg("Decimals exceed the maximum of 9")]
+    InvalidDecimals,
+    
+    #[msg("Operation requires zero circulating supply")]
+    SupplyNotZero,
+    
+    #[msg("Creator registry is full (64 pools)")]
+    RegistryFull,
+    
+    #[msg("Tick size cannot exceed the base price")]
+    InvalidTickSize,
+    
+    #[msg("Batch too large for a single transaction")]
+    BatchTooLarge,
+    
+    #[msg("Founder allocation has already been minted")]
+    FounderAllocationMinted,
+    
+    #[msg("Position balance cannot cover the requested SOL target")]
+    InsufficientBalanceForTarget,
+    
+    #[msg("Interval must be greater than zero")]
+    InvalidInterval,
+    
+    #[msg("Cannot sell in the same slot as a buy")]
+    SameSlotTradeBlocked,
+    
+    #[msg("Founder tokens have not vested yet")]
+    FounderTokensLocked,
+    
+    #[msg("Fee discount cannot exceed 10000 basis points")]
+    InvalidFeeDiscount,
+    
+    #[msg("Pool has graduated; curve buys are closed")]
+    PoolGraduated,
+    
+    #[msg("Pool has not graduated")]
+    PoolNotGraduated,
+    
+    #[msg("Fee exceeds the maximum of 1000 basis points")]
+    InvalidFee,
+    
+    #[msg("Parent pool must be the channel's Creator pool")]
+    InvalidParentPool,
+    
+    #[msg("Buy would move the price by more than the allowed multiple")]
+    PriceMoveTooLarge,
+    
+    #[msg("Basket accounts do not match the amounts")]
+    InvalidBasket,
+    
+    #[msg("Position is frozen")]
+    PositionFrozen,
+    
+    #[msg("Compliance controls are not enabled for this pool")]
+    ComplianceDisabled,
+    
+    #[msg("Curve parameter is out of range")]
+    CurveParamOutOfRange,
+    
+    #[msg("Pool has expired; only sells are accepted")]
+    PoolExpired,
+    
+    #[msg("Only Stream pools support this operation")]
+    NotStreamPool,
+    
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    
+    #[msg("Max supply can only be raised, and not below the circulating supply")]
+    InvalidMaxSupply,
+    
+    #[msg("Trade would exceed the pool's max supply")]
+    MaxSupplyExceeded,
+    
+    #[msg("Reserve exceeds net deposits; accounting mismatch")]
+    ReserveAccountingMismatch,
+    
+    #[msg("Multisig needs 1-5 distinct signers and a threshold between 1 and the signer count")]
+    InvalidMultisig,
+    
+    #[msg("Signer is not a member of the multisig")]
+    NotMultisigSigner,
+    
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+    
+    #[msg("Proposal does not have enough approvals")]
+    ThresholdNotMet,
+    
+    #[msg("Destination account does not match the proposal")]
+    InvalidDestination,
+    
+    #[msg("Multisig balance cannot cover the withdrawal and stay rent-exempt")]
+    InsufficientMultisigBalance,
+    
+    #[msg("Order ID was already used for a recent trade")]
+    DuplicateOrder,
+    
+    #[msg("The curve never reaches this price")]
+    PriceUnreachable,
+    
+    #[msg("Missing or invalid oracle ownership attestation")]
+    InvalidAttestation,
+    
+    #[msg("Exponential precision must be 9, 12 or 15")]
+    InvalidExpPrecision,
+    
+    #[msg("Pause duration must be between 0 and 1 day")]
+    InvalidPauseDuration,
+    
+    #[msg("Trading is paused by the creator")]
+    TradingPaused,
+    
+    #[msg("Buy would cost more than the maximum SOL allowed")]
+    SlippageExceeded,
+    
+    #[msg("Buy tax exceeds the maximum allowed")]
+    InvalidBuyTax,
+    
+    #[msg("Pool already has a custom display name or metadata URI")]
+    NotLegacyPool,
+    
+    #[msg("The program must be globally paused first")]
+    NotGloballyPaused,
+    
+    #[msg("Buy exceeds the pool's notional limit for this window")]
+    RateLimitExceeded,
+    
+    #[msg("Burn-on-sell rate exceeds the maximum allowed")]
+    InvalidBurnRate,
+    
+    #[msg("Metadata URI does not match the declared metadata format")]
+    InvalidMetadataFormat,
+    
+    #[msg("Reserve ratio must be at most 10000 bps")]
+    InvalidReserveRatio,
+    
+    #[msg("Price ceiling must be zero or at least the base price")]
+    InvalidPriceCeiling,
+    
+    #[msg("Buy cooldown must be between 0 and 1 hour")]
+    InvalidBuyCooldown,
+    
+    #[msg("This wallet bought too recently; wait for the buy cooldown")]
+    BuyCooldownActive,
+    
+    #[msg("New fee must be lower than the current fee")]
+    FeeNotDecreased,
+    
+    #[msg("Reserve vault is already enabled")]
+    ReserveVaultEnabled,
+    
+    #[msg("This pool holds its reserve in a vault; pass reserve_vault")]
+    ReserveVaultMissing,
+    
+    #[msg("Batch is empty")]
+    EmptyBatch,
+    
+    #[msg("Wallet is not on the presale whitelist")]
+    NotWhitelisted,
+    
+    #[msg("Sell window must open before it closes")]
+    InvalidSellWindow,
+    
+    #[msg("Sells are closed outside the pool's sell window")]
+    SellWindowClosed,
+    
+    #[msg("Insurance fee cannot exceed 10000 bps")]
+    InvalidInsuranceFee,
+    
+    #[msg("Claim exceeds the pool's reserve shortfall")]
+    ClaimExceedsShortfall,
+    
+    #[msg("Insurance fund cannot cover the claim")]
+    InsufficientInsuranceFund,
+    
+    #[msg("Pool already has a quote mint")]
+    QuoteMintAlreadySet,
+    
+    #[msg("Not supported for pools priced in an SPL token")]
+    QuoteMintUnsupported,
+    
+    #[msg("Pool is priced in an SPL token; pass its quote token accounts")]
+    QuoteAccountsMissing,
+    
+    #[msg("Token account is not for the pool's quote mint")]
+    InvalidQuoteMint,
+    
+    #[msg("Pool has no holders to snapshot")]
+    NoHolders,
+    
+    #[msg("Claim window must be between 1 second and 30 days")]
+    InvalidClaimWindow,
+    
+    #[msg("The latest holder snapshot's claim window is still open")]
+    AirdropClaimOpen,
+    
+    #[msg("The holder snapshot's claim window has ended")]
+    AirdropClaimClosed,
+    
+    #[msg("Airdrop already claimed for this snapshot")]
+    AirdropAlreadyClaimed,
+    
+    #[msg("Position bought after the snapshot and cannot claim its airdrop")]
+    BoughtAfterSnapshot,
+    
+    #[msg("Default slippage cannot exceed 50%")]
+    InvalidSlippage,
+    
+    #[msg("Pool metadata is frozen")]
+    MetadataFrozen,
+    
+    #[msg("Buy would move the price more than the pool allows")]
+    ImpactTooHigh,
+    
+    #[msg("Pool already has this type")]
+    PoolTypeUnchanged,
+    
+    #[msg("Cannot swap a pool into itself")]
+    SamePoolSwap,
+    
+    #[msg("Proposal instruction data or account list is too long")]
+    ProposalTooLarge,
+    
+    #[msg("Trading is halted program-wide")]
+    GloballyPaused,
+    
+    #[msg("Reserve was rescued in an emergency; the admin must clear it first")]
+    EmergencyWithdrawalPending,
+    
+    #[msg("Fee cannot exceed the ceiling set by decrease_fee")]
+    FeeAboveCeiling,
 }
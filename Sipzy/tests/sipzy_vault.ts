@@ -2,8 +2,22 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { SipzyVault } from "../target/types/sipzy_vault";
 import { expect } from "chai";
-import { PublicKey, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  PublicKey,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+  Ed25519Program,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+} from "@solana/web3.js";
+import {
+  createMint,
+  createAssociatedTokenAccount,
+  mintTo,
+  getAccount,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 import BN from "bn.js";
+import { createHash } from "crypto";
 
 describe("sipzy_vault", () => {
   // Configure the client to use the local cluster
@@ -33,6 +47,22 @@ describe("sipzy_vault", () => {
     program.programId
   );
 
+  // Generation 0 keeps the original two-seed address
+  const creatorPoolPdaFor = (id: string, generation = 0) => {
+    const generationSeed = Buffer.alloc(generation === 0 ? 0 : 4);
+    if (generation !== 0) generationSeed.writeUInt32LE(generation);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("creator_pool"), Buffer.from(id), generationSeed],
+      program.programId
+    )[0];
+  };
+
+  const positionPda = (pool: PublicKey, owner: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), pool.toBuffer(), owner.toBuffer()],
+      program.programId
+    )[0];
+
   before(async () => {
     // Fund creator wallet for rent
     const airdropSig = await provider.connection.requestAirdrop(
@@ -50,9 +80,11 @@ describe("sipzy_vault", () => {
           channelName,
           metadataUri,
           null, // Use default base price
-          null  // Use default slope
+          null, // Use default slope
+          0     // First generation
         )
         .accounts({
+          pool: creatorPoolPda,
           creatorWallet: creatorWallet.publicKey,
         })
         .rpc();
@@ -76,7 +108,7 @@ describe("sipzy_vault", () => {
       const amount = new BN(10);
       
       const tx = await program.methods
-        .buyTokens(amount)
+        .buyTokens(amount, new BN(0))
         .accounts({
           pool: creatorPoolPda,
           trader: provider.wallet.publicKey,
@@ -91,6 +123,11 @@ describe("sipzy_vault", () => {
       
       expect(poolAccount.totalSupply.toNumber()).to.equal(10);
       expect(poolAccount.reserveSol.toNumber()).to.be.greaterThan(0);
+
+      const position = await program.account.holderPosition.fetch(
+        positionPda(creatorPoolPda, provider.wallet.publicKey)
+      );
+      expect(position.balance.toNumber()).to.equal(10);
       
       console.log("Creator pool supply:", poolAccount.totalSupply.toNumber());
       console.log("Creator pool reserve (lamports):", poolAccount.reserveSol.toNumber());
@@ -117,7 +154,7 @@ describe("sipzy_vault", () => {
       const supplyBefore = poolBefore.totalSupply.toNumber();
       
       const tx = await program.methods
-        .sellTokens(amount)
+        .sellTokens(amount, new BN(0))
         .accounts({
           pool: creatorPoolPda,
           trader: provider.wallet.publicKey,
@@ -131,89 +168,3605 @@ describe("sipzy_vault", () => {
       const poolAfter = await program.account.pool.fetch(creatorPoolPda);
       
       expect(poolAfter.totalSupply.toNumber()).to.equal(supplyBefore - 5);
+      // Deposits are tracked independently of the reserve and must agree
+      expect(poolAfter.netDeposited.toString()).to.equal(poolAfter.reserveSol.toString());
       console.log("Creator pool supply after sell:", poolAfter.totalSupply.toNumber());
     });
+
+    it("Sells tokens with proceeds sent to a recipient", async () => {
+      const recipient = anchor.web3.Keypair.generate();
+      const traderPosition = positionPda(creatorPoolPda, provider.wallet.publicKey);
+      const positionBefore = await program.account.holderPosition.fetch(traderPosition);
+
+      await program.methods
+        .sellTokens(new BN(1), new BN(0))
+        .accounts({
+          pool: creatorPoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          recipient: recipient.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const positionAfter = await program.account.holderPosition.fetch(traderPosition);
+      expect(positionAfter.balance.toNumber()).to.equal(positionBefore.balance.toNumber() - 1);
+
+      const recipientBalance = await provider.connection.getBalance(recipient.publicKey);
+      expect(recipientBalance).to.be.greaterThan(0);
+    });
+
+    it("Sells just enough tokens to cash out a SOL target", async () => {
+      const traderPosition = positionPda(creatorPoolPda, provider.wallet.publicKey);
+      const positionBefore = await program.account.holderPosition.fetch(traderPosition);
+
+      // One token refunds ~0.0102 SOL net, so a 0.015 SOL target needs two
+      await program.methods
+        .sellForSol(new BN(15_000_000))
+        .accounts({
+          pool: creatorPoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const positionAfter = await program.account.holderPosition.fetch(traderPosition);
+      expect(positionAfter.balance.toNumber()).to.equal(positionBefore.balance.toNumber() - 2);
+    });
+
+    it("Rejects buys that would exceed the reserve cap", async () => {
+      const poolBefore = await program.account.pool.fetch(creatorPoolPda);
+
+      await program.methods
+        .setReserveCap(poolBefore.reserveSol)
+        .accounts({
+          pool: creatorPoolPda,
+          creator: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      try {
+        await program.methods
+          .buyTokens(new BN(1), new BN(0))
+          .accounts({
+            pool: creatorPoolPda,
+            trader: provider.wallet.publicKey,
+            creatorWallet: creatorWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("buy should have been rejected by the reserve cap");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("ReserveCapReached");
+      }
+
+      // Remove the cap so later tests can keep trading
+      await program.methods
+        .setReserveCap(new BN(0))
+        .accounts({
+          pool: creatorPoolPda,
+          creator: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      const poolAfter = await program.account.pool.fetch(creatorPoolPda);
+      expect(poolAfter.totalSupply.toNumber()).to.equal(poolBefore.totalSupply.toNumber());
+    });
+
+    it("Reports the trading status in the pool summary", async () => {
+      const manage = { pool: creatorPoolPda, creator: creatorWallet.publicKey };
+
+      let summary = await program.methods.getPoolSummary().accounts({ pool: creatorPoolPda }).view();
+      expect(summary.tradingStatus).to.equal(0); // Open
+      expect(summary.identifier).to.equal(channelId);
+
+      await program.methods.deactivatePool().accounts(manage).signers([creatorWallet]).rpc();
+      summary = await program.methods.getPoolSummary().accounts({ pool: creatorPoolPda }).view();
+      expect(summary.tradingStatus).to.equal(1); // Inactive
+      expect(summary.isActive).to.equal(false);
+
+      await program.methods.reactivatePool().accounts(manage).signers([creatorWallet]).rpc();
+    });
+
+    it("Reports the pool age from the cluster clock", async () => {
+      const poolAccount = await program.account.pool.fetch(creatorPoolPda);
+      const age = await program.methods.getPoolAge().accounts({ pool: creatorPoolPda }).view();
+      const slotTime = await provider.connection.getBlockTime(await provider.connection.getSlot());
+
+      expect(age.toNumber()).to.be.at.least(0);
+      expect(age.toNumber()).to.be.at.most(slotTime - poolAccount.createdAt.toNumber() + 1);
+    });
+
+    it("Tracks cost basis and keeps the average price across sells", async () => {
+      const holder = provider.wallet.publicKey;
+      const before = await program.methods
+        .getPosition(holder)
+        .accounts({ pool: creatorPoolPda, position: positionPda(creatorPoolPda, holder) })
+        .view();
+      expect(before.balance.toNumber()).to.be.greaterThan(1);
+      expect(before.costBasisLamports.toNumber()).to.be.greaterThan(0);
+
+      await program.methods
+        .sellTokens(new BN(1), new BN(0))
+        .accounts({
+          pool: creatorPoolPda,
+          trader: holder,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const after = await program.methods
+        .getPosition(holder)
+        .accounts({ pool: creatorPoolPda, position: positionPda(creatorPoolPda, holder) })
+        .view();
+      expect(after.balance.toNumber()).to.equal(before.balance.toNumber() - 1);
+      expect(after.costBasisLamports.toNumber()).to.be.lessThan(before.costBasisLamports.toNumber());
+      // Pro-rata reduction leaves the average within rounding of where it was
+      expect(Math.abs(after.averageCost.toNumber() - before.averageCost.toNumber())).to.be.at.most(1);
+    });
+
+    it("Simulates trades with the same arithmetic as execution", async () => {
+      const trade = {
+        pool: creatorPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      const priceBefore = await program.methods.getPrice().accounts({ pool: creatorPoolPda }).view();
+      const buySim = await program.methods
+        .simulateTrade({ buy: {} }, new BN(3))
+        .accounts({ pool: creatorPoolPda })
+        .view();
+      expect(buySim.priceImpactBps.toString()).to.equal(
+        buySim.newPrice.sub(priceBefore).muln(10000).div(priceBefore).toString()
+      );
+      await program.methods.buyTokens(new BN(3), new BN(0)).accounts(trade).rpc();
+      let poolAccount = await program.account.pool.fetch(creatorPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(buySim.newSupply.toNumber());
+      expect(poolAccount.reserveSol.toNumber()).to.equal(buySim.newReserve.toNumber());
+
+      const sellSim = await program.methods
+        .simulateTrade({ sell: {} }, new BN(3))
+        .accounts({ pool: creatorPoolPda })
+        .view();
+      await program.methods.sellTokens(new BN(3), new BN(0)).accounts(trade).rpc();
+      poolAccount = await program.account.pool.fetch(creatorPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(sellSim.newSupply.toNumber());
+      expect(poolAccount.reserveSol.toNumber()).to.equal(sellSim.newReserve.toNumber());
+
+      const price = await program.methods.getPrice().accounts({ pool: creatorPoolPda }).view();
+      expect(price.toNumber()).to.equal(sellSim.newPrice.toNumber());
+    });
+
+    it("Averages the per-token price over the size of a trade", async () => {
+      const amount = new BN(10);
+      const buySim = await program.methods
+        .simulateTrade({ buy: {} }, amount)
+        .accounts({ pool: creatorPoolPda })
+        .view();
+      const effective = await program.methods
+        .getEffectivePrice(amount, { buy: {} })
+        .accounts({ pool: creatorPoolPda })
+        .view();
+      expect(effective.toString()).to.equal(buySim.solAmount.div(amount).toString());
+
+      // The curve rises, so a larger buy pays more per token than the spot price
+      const spot = await program.methods.getPrice().accounts({ pool: creatorPoolPda }).view();
+      expect(effective.gt(spot)).to.equal(true);
+    });
+
+    it("Reports no shortfall for a pool funded only by trades", async () => {
+      const imbalance = await program.methods
+        .getCirculatingVsReserveImbalance()
+        .accounts({
+          pool: creatorPoolPda,
+        })
+        .view();
+
+      // Buys round up and sells round down, so the reserve can only be in surplus
+      expect(imbalance.toNumber()).to.be.at.most(0);
+    });
   });
 
-  describe("Stream Pool (Exponential Curve)", () => {
-    it("Initializes a stream pool", async () => {
-      const tx = await program.methods
-        .initializeStreamPool(
-          videoId,
-          channelId, // Parent channel
-          videoTitle,
-          metadataUri,
-          null, // Use default base price
-          null  // Use default growth rate
-        )
+  describe("Linear integral boundaries", () => {
+    const boundaryChannelId = "UC_boundary_test";
+    const [boundaryPoolPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("creator_pool"), Buffer.from(boundaryChannelId)],
+      program.programId
+    );
+
+    before(async () => {
+      await program.methods
+        .initializeCreatorPool(boundaryChannelId, "Boundary Channel", "", null, null, 0)
         .accounts({
+          pool: creatorPoolPdaFor(boundaryChannelId),
           creatorWallet: creatorWallet.publicKey,
         })
         .rpc();
+    });
 
-      console.log("Initialize stream pool tx:", tx);
+    it("Prices the first token (supply 0 -> 1) at the base price", async () => {
+      const cost = await program.methods
+        .getBuyCost(new BN(1))
+        .accounts({ pool: boundaryPoolPda })
+        .view();
 
-      const poolAccount = await program.account.pool.fetch(streamPoolPda);
-      
-      expect(poolAccount.identifier).to.equal(videoId);
-      expect(poolAccount.displayName).to.equal(videoTitle);
-      expect(poolAccount.parentIdentifier).to.equal(channelId);
+      // base_price + 1% fee
+      expect(cost.toNumber()).to.equal(10_100_000);
+    });
+
+    it("Quotes zero tokens at zero cost", async () => {
+      const cost = await program.methods
+        .getBuyCost(new BN(0))
+        .accounts({ pool: boundaryPoolPda })
+        .view();
+
+      expect(cost.toNumber()).to.equal(0);
+    });
+
+    it("Sells the last token back down to zero supply", async () => {
+      const accounts = {
+        pool: boundaryPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods.buyTokens(new BN(1), new BN(0)).accounts(accounts).rpc();
+      await program.methods.sellTokens(new BN(1), new BN(0)).accounts(accounts).rpc();
+
+      const poolAccount = await program.account.pool.fetch(boundaryPoolPda);
       expect(poolAccount.totalSupply.toNumber()).to.equal(0);
-      // PoolType.Stream = 1
-      expect(poolAccount.poolType).to.deep.equal({ stream: {} });
     });
+  });
+
+  describe("Decimals", () => {
+    const decimalsChannelId = "UC_decimals_test";
+    const [decimalsPoolPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("creator_pool"), Buffer.from(decimalsChannelId)],
+      program.programId
+    );
+
+    it("Buys half a token from a pool with 2 decimals", async () => {
+      await program.methods
+        .initializeCreatorPool(decimalsChannelId, "Decimals Channel", metadataUri, null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(decimalsChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .setDecimals(2)
+        .accounts({
+          pool: decimalsPoolPda,
+          creator: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      const cost = await program.methods
+        .getBuyCost(new BN(50))
+        .accounts({ pool: decimalsPoolPda })
+        .view();
+
+      // Half a token near supply 0 costs about half the 0.01 SOL base price (+ fee)
+      expect(cost.toNumber()).to.be.greaterThan(5_000_000);
+      expect(cost.toNumber()).to.be.lessThan(5_200_000);
 
-    it("Buys tokens from stream pool (exponential)", async () => {
-      const amount = new BN(20);
-      
       const tx = await program.methods
-        .buyTokens(amount)
+        .buyTokens(new BN(50), new BN(0))
         .accounts({
-          pool: streamPoolPda,
+          pool: decimalsPoolPda,
           trader: provider.wallet.publicKey,
           creatorWallet: creatorWallet.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .rpc({ commitment: "confirmed" });
+
+      const poolAccount = await program.account.pool.fetch(decimalsPoolPda);
+      expect(poolAccount.decimals).to.equal(2);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(50);
+
+      // The trade event carries the decimals needed to format its base-unit amounts
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const traded = [...parser.parseLogs(txInfo.meta.logMessages)].find(
+        (event) => event.name.toLowerCase() === "tokenstraded"
+      );
+      expect(traded.data.amount.toNumber()).to.equal(50);
+      expect(traded.data.decimals).to.equal(2);
+    });
+  });
+
+  describe("Creator Registry", () => {
+    const [registryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), creatorWallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    it("Lists and delists pools as they are created and closed", async () => {
+      const closingChannelId = "UC_registry_close";
+      const [closingPoolPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("creator_pool"), Buffer.from(closingChannelId)],
+        program.programId
+      );
+
+      await program.methods
+        .initializeCreatorPool(closingChannelId, "Short Lived", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(closingChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
         .rpc();
 
-      console.log("Buy stream tokens tx:", tx);
+      let registry = await program.account.creatorRegistry.fetch(registryPda);
+      const listed = registry.pools.map((key) => key.toString());
+      expect(listed).to.include(creatorPoolPda.toString());
+      expect(listed).to.include(closingPoolPda.toString());
 
-      const poolAccount = await program.account.pool.fetch(streamPoolPda);
-      
-      expect(poolAccount.totalSupply.toNumber()).to.equal(20);
-      console.log("Stream pool supply:", poolAccount.totalSupply.toNumber());
-      console.log("Stream pool reserve (lamports):", poolAccount.reserveSol.toNumber());
+      await program.methods
+        .closePool()
+        .accounts({
+          pool: closingPoolPda,
+          creator: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      registry = await program.account.creatorRegistry.fetch(registryPda);
+      expect(registry.pools.map((key) => key.toString())).to.not.include(closingPoolPda.toString());
+      expect(await provider.connection.getAccountInfo(closingPoolPda)).to.be.null;
     });
 
-    it("Gets current price (exponential curve)", async () => {
-      const price = await program.methods
-        .getPrice()
+    it("Relaunches a closed channel under a new generation", async () => {
+      const closingChannelId = "UC_registry_close";
+      const relaunchedPoolPda = creatorPoolPdaFor(closingChannelId, 1);
+
+      await program.methods
+        .initializeCreatorPool(closingChannelId, "Second Life", "", null, null, 1)
         .accounts({
-          pool: streamPoolPda,
+          pool: relaunchedPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(relaunchedPoolPda);
+      expect(poolAccount.identifier).to.equal(closingChannelId);
+      expect(poolAccount.generation).to.equal(1);
+      expect(relaunchedPoolPda.toString()).to.not.equal(creatorPoolPdaFor(closingChannelId).toString());
+    });
+  });
+
+  describe("Founder Allocation", () => {
+    const founderChannelId = "UC_founder_test";
+    const founderPoolPda = creatorPoolPdaFor(founderChannelId);
+
+    before(async () => {
+      await program.methods
+        .initializeCreatorPool(founderChannelId, "Founder Channel", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(founderChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+    });
+
+    it("Mints the founder allocation once without moving SOL", async () => {
+      await program.methods
+        .mintFounderAllocation(new BN(100))
+        .accounts({
+          pool: founderPoolPda,
+          creator: creatorWallet.publicKey,
         })
+        .signers([creatorWallet])
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(founderPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(100);
+      expect(poolAccount.reserveSol.toNumber()).to.equal(0);
+      expect(poolAccount.founderMinted).to.equal(true);
+      expect(poolAccount.reserveDebt.toNumber()).to.be.greaterThan(0);
+
+      // Unpaid founder tokens are debt, not a shortfall
+      const imbalance = await program.methods
+        .getCirculatingVsReserveImbalance()
+        .accounts({ pool: founderPoolPda })
         .view();
+      expect(imbalance.toNumber()).to.equal(0);
 
-      console.log("Stream token price (lamports):", price.toNumber());
-      
-      // Exponential curve: base * 1.05^20 should be significantly higher than base
-      // 1_000_000 * 1.05^20 ≈ 2_653_298
-      expect(price.toNumber()).to.be.greaterThan(2_000_000);
+      const position = await program.account.holderPosition.fetch(
+        positionPda(founderPoolPda, creatorWallet.publicKey)
+      );
+      expect(position.balance.toNumber()).to.equal(100);
+
+      try {
+        await program.methods
+          .mintFounderAllocation(new BN(1))
+          .accounts({
+            pool: founderPoolPda,
+            creator: creatorWallet.publicKey,
+          })
+          .signers([creatorWallet])
+          .rpc();
+        expect.fail("second founder mint should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("FounderAllocationMinted");
+      }
     });
 
-    it("Gets buy cost for stream tokens", async () => {
-      const amount = new BN(10);
-      
-      const cost = await program.methods
-        .getBuyCost(amount)
+    it("Settles a sell where the trader is also the creator wallet", async () => {
+      // Back the founder tokens with a regular buy first
+      await program.methods
+        .buyTokens(new BN(10), new BN(0))
         .accounts({
-          pool: streamPoolPda,
+          pool: founderPoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const poolBefore = await program.account.pool.fetch(founderPoolPda);
+      const creatorBefore = await provider.connection.getBalance(creatorWallet.publicKey);
+
+      // trader and creatorWallet are the same account; the provider pays the tx fee
+      await program.methods
+        .sellTokens(new BN(1), new BN(0))
+        .accounts({
+          pool: founderPoolPda,
+          trader: creatorWallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      const poolAfter = await program.account.pool.fetch(founderPoolPda);
+      const creatorAfter = await provider.connection.getBalance(creatorWallet.publicKey);
+      const reserveOut = poolBefore.reserveSol.toNumber() - poolAfter.reserveSol.toNumber();
+
+      // Net refund and fee both land in the same wallet: nothing lost or double-counted
+      expect(creatorAfter - creatorBefore).to.equal(reserveOut);
+    });
+  });
+
+  describe("Founder Vesting", () => {
+    const vestingChannelId = "UC_vesting_test";
+    const vestingPoolPda = creatorPoolPdaFor(vestingChannelId);
+    const manage = { pool: vestingPoolPda, creator: creatorWallet.publicKey };
+    const creatorTrade = {
+      pool: vestingPoolPda,
+      trader: creatorWallet.publicKey,
+      creatorWallet: creatorWallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+
+    before(async () => {
+      await program.methods
+        .initializeCreatorPool(vestingChannelId, "Vesting Channel", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(vestingChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      // One year of linear vesting, then mint
+      await program.methods.setFounderVesting(new BN(365 * 24 * 60 * 60)).accounts(manage).signers([creatorWallet]).rpc();
+      await program.methods.mintFounderAllocation(new BN(50)).accounts(manage).signers([creatorWallet]).rpc();
+    });
+
+    it("Lets the founder sell bought tokens but not unvested founder tokens", async () => {
+      await program.methods.buyTokens(new BN(5), new BN(0)).accounts(creatorTrade).signers([creatorWallet]).rpc();
+      await program.methods.sellTokens(new BN(5), new BN(0)).accounts(creatorTrade).signers([creatorWallet]).rpc();
+
+      try {
+        await program.methods.sellTokens(new BN(1), new BN(0)).accounts(creatorTrade).signers([creatorWallet]).rpc();
+        expect.fail("unvested founder tokens should be locked");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("FounderTokensLocked");
+      }
+
+      const poolAccount = await program.account.pool.fetch(vestingPoolPda);
+      expect(poolAccount.founderAllocation.toNumber()).to.equal(50);
+      expect(poolAccount.founderVestedSold.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("Loyalty Fee Tiers", () => {
+    const loyaltyChannelId = "UC_loyalty_test";
+    const loyaltyPoolPda = creatorPoolPdaFor(loyaltyChannelId);
+    const manage = { pool: loyaltyPoolPda, creator: creatorWallet.publicKey };
+    const trade = {
+      pool: loyaltyPoolPda,
+      trader: provider.wallet.publicKey,
+      creatorWallet: creatorWallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+    const emptyTier = { minHoldSecs: new BN(0), discountBps: 0 };
+    const thirtyDays = new BN(30 * 24 * 60 * 60);
+
+    before(async () => {
+      await program.methods
+        .initializeCreatorPool(loyaltyChannelId, "Loyalty Channel", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(loyaltyChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods.buyTokens(new BN(10), new BN(0)).accounts(trade).rpc();
+    });
+
+    it("Only discounts sells once the holding period is met", async () => {
+      // Fee-free after 30 days: a fresh holder still pays the full fee
+      await program.methods
+        .setLoyaltyTiers([{ minHoldSecs: thirtyDays, discountBps: 10000 }, emptyTier, emptyTier])
+        .accounts(manage)
+        .signers([creatorWallet])
+        .rpc();
+
+      let feesBefore = (await program.account.pool.fetch(loyaltyPoolPda)).lifetimeFees.toNumber();
+      await program.methods.sellTokens(new BN(2), new BN(0)).accounts(trade).rpc();
+      let feesAfter = (await program.account.pool.fetch(loyaltyPoolPda)).lifetimeFees.toNumber();
+      expect(feesAfter).to.be.greaterThan(feesBefore);
+
+      // A tier with no minimum hold applies immediately
+      await program.methods
+        .setLoyaltyTiers([{ minHoldSecs: new BN(0), discountBps: 10000 }, emptyTier, emptyTier])
+        .accounts(manage)
+        .signers([creatorWallet])
+        .rpc();
+
+      feesBefore = feesAfter;
+      await program.methods.sellTokens(new BN(2), new BN(0)).accounts(trade).rpc();
+      feesAfter = (await program.account.pool.fetch(loyaltyPoolPda)).lifetimeFees.toNumber();
+      expect(feesAfter).to.equal(feesBefore);
+    });
+
+    it("Rejects discounts above 100%", async () => {
+      try {
+        await program.methods
+          .setLoyaltyTiers([{ minHoldSecs: thirtyDays, discountBps: 10001 }, emptyTier, emptyTier])
+          .accounts(manage)
+          .signers([creatorWallet])
+          .rpc();
+        expect.fail("discount above 10000 bps should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidFeeDiscount");
+      }
+    });
+  });
+
+  describe("Graduation", () => {
+    const graduationChannelId = "UC_graduation_test";
+    const graduationPoolPda = creatorPoolPdaFor(graduationChannelId);
+    const manage = { pool: graduationPoolPda, creator: creatorWallet.publicKey };
+    const trade = {
+      pool: graduationPoolPda,
+      trader: provider.wallet.publicKey,
+      creatorWallet: creatorWallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+
+    before(async () => {
+      await program.methods
+        .initializeCreatorPool(graduationChannelId, "Graduation Channel", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(graduationChannelId),
+          creatorWallet: creatorWallet.publicKey,
         })
+        .rpc();
+      // Any first buy reaches a 1-lamport threshold
+      await program.methods.setGraduationThreshold(new BN(1)).accounts(manage).signers([creatorWallet]).rpc();
+    });
+
+    it("Closes curve buys once the reserve reaches the threshold", async () => {
+      const wouldGraduate = await program.methods
+        .wouldGraduate(new BN(3))
+        .accounts({ pool: graduationPoolPda })
+        .view();
+      expect(wouldGraduate).to.equal(true);
+      const neverGraduates = await program.methods
+        .wouldGraduate(new BN(3))
+        .accounts({ pool: creatorPoolPda })
         .view();
+      expect(neverGraduates).to.equal(false);
+      const startProgress = await program.methods
+        .getGraduationProgress()
+        .accounts({ pool: graduationPoolPda })
+        .view();
+      expect(startProgress.toNumber()).to.equal(0);
 
-      console.log("Cost to buy 10 more stream tokens (lamports):", cost.toNumber());
-      
-      // Should be non-zero and include fee
-      expect(cost.toNumber()).to.be.greaterThan(0);
+      await program.methods.buyTokens(new BN(3), new BN(0)).accounts(trade).rpc();
+
+      const poolAccount = await program.account.pool.fetch(graduationPoolPda);
+      expect(poolAccount.isGraduated).to.equal(true);
+      // Far past a 1-lamport threshold, so capped
+      const progress = await program.methods
+        .getGraduationProgress()
+        .accounts({ pool: graduationPoolPda })
+        .view();
+      expect(progress.toNumber()).to.equal(10_000);
+      const reservePerToken = await program.methods
+        .getReservePerToken()
+        .accounts({ pool: graduationPoolPda })
+        .view();
+      expect(reservePerToken.toNumber()).to.equal(Math.floor(poolAccount.reserveSol.toNumber() / 3));
+
+      try {
+        await program.methods.buyTokens(new BN(1), new BN(0)).accounts(trade).rpc();
+        expect.fail("buy after graduation should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("PoolGraduated");
+      }
+    });
+
+    it("Redeems a pro-rata share of the reserve without a fee", async () => {
+      const before = await program.account.pool.fetch(graduationPoolPda);
+
+      await program.methods.redeemGraduated(new BN(1)).accounts(trade).rpc();
+
+      const poolAccount = await program.account.pool.fetch(graduationPoolPda);
+      const payout = Math.floor(before.reserveSol.toNumber() / before.totalSupply.toNumber());
+      expect(poolAccount.totalSupply.toNumber()).to.equal(2);
+      expect(poolAccount.reserveSol.toNumber()).to.equal(before.reserveSol.toNumber() - payout);
+    });
+
+    it("Sweeps the residual reserve once every token is sold back", async () => {
+      await program.methods.sellTokens(new BN(2), new BN(0)).accounts(trade).rpc();
+
+      const residual = (await program.account.pool.fetch(graduationPoolPda)).reserveSol.toNumber();
+      const creatorBefore = await provider.connection.getBalance(creatorWallet.publicKey);
+
+      // Lamports sent straight to the pool are not reserve and are not swept
+      const stray = 1000;
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: provider.wallet.publicKey,
+            toPubkey: graduationPoolPda,
+            lamports: stray,
+          })
+        )
+      );
+
+      await program.methods.sweepResidualReserve().accounts(manage).signers([creatorWallet]).rpc();
+
+      const poolAccount = await program.account.pool.fetch(graduationPoolPda);
+      expect(poolAccount.reserveSol.toNumber()).to.equal(0);
+      expect(poolAccount.netDeposited.toNumber()).to.equal(0);
+      const info = await provider.connection.getAccountInfo(graduationPoolPda);
+      expect(info.lamports).to.equal(
+        (await provider.connection.getMinimumBalanceForRentExemption(info.data.length)) + stray
+      );
+      // The creator also pays the transaction fee, so only bound the gain from below
+      const creatorAfter = await provider.connection.getBalance(creatorWallet.publicKey);
+      expect(creatorAfter - creatorBefore + 5000).to.be.at.least(residual);
+    });
+  });
+
+  describe("Legacy Pool Init", () => {
+    it("Rejects a creator wallet argument that differs from the account", async () => {
+      const legacyId = "legacy_wallet_mismatch";
+      const legacyPoolPda = creatorPoolPdaFor(legacyId);
+
+      try {
+        await program.methods
+          .initializePool(legacyId, anchor.web3.Keypair.generate().publicKey)
+          .accounts({
+            pool: legacyPoolPda,
+            creatorWallet: creatorWallet.publicKey,
+          })
+          .rpc();
+        expect.fail("mismatched creator wallet should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidCreatorWallet");
+      }
+    });
+
+    it("Records the creator wallet when the argument matches the account", async () => {
+      const legacyId = "legacy_wallet_match";
+      const legacyPoolPda = creatorPoolPdaFor(legacyId);
+      const legacyCreator = anchor.web3.Keypair.generate().publicKey;
+
+      await program.methods
+        .initializePool(legacyId, legacyCreator)
+        .accounts({
+          pool: legacyPoolPda,
+          creatorWallet: legacyCreator,
+        })
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(legacyPoolPda);
+      expect(poolAccount.creatorWallet.toBase58()).to.equal(legacyCreator.toBase58());
+      expect(poolAccount.identifier).to.equal(legacyId);
+    });
+  });
+
+  describe("Legacy Pool Upgrade", () => {
+    it("Backfills a legacy pool's name and metadata once", async () => {
+      const legacyId = "legacy_video_id";
+      const legacyPoolPda = creatorPoolPdaFor(legacyId);
+      const upgradeAccounts = { pool: legacyPoolPda, creator: creatorWallet.publicKey };
+
+      await program.methods
+        .initializePool(legacyId, creatorWallet.publicKey)
+        .accounts({
+          pool: legacyPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .upgradeLegacyPool("Legacy Channel", "ipfs://QmLegacy")
+        .accounts(upgradeAccounts)
+        .signers([creatorWallet])
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(legacyPoolPda);
+      expect(poolAccount.identifier).to.equal(legacyId);
+      expect(poolAccount.creatorWallet.toBase58()).to.equal(creatorWallet.publicKey.toBase58());
+      expect(poolAccount.displayName).to.equal("Legacy Channel");
+      expect(poolAccount.metadataUri).to.equal("ipfs://QmLegacy");
+
+      try {
+        await program.methods
+          .upgradeLegacyPool("Renamed", "ipfs://QmOther")
+          .accounts(upgradeAccounts)
+          .signers([creatorWallet])
+          .rpc();
+        expect.fail("an upgraded pool should not be upgraded again");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("NotLegacyPool");
+      }
+    });
+  });
+
+  describe("Buy Cost Components", () => {
+    it("Splits a linear buy into parts that add up to what it costs", async () => {
+      const amount = new BN(5);
+      const parts = await program.methods
+        .getBuyCostComponents(amount)
+        .accounts({ pool: creatorPoolPda })
+        .view();
+      const sim = await program.methods
+        .simulateTrade({ buy: {} }, amount)
+        .accounts({ pool: creatorPoolPda })
+        .view();
+      const poolAccount = await program.account.pool.fetch(creatorPoolPda);
+
+      expect(parts.baseComponent.toString()).to.equal(poolAccount.basePrice.muln(5).toString());
+      // Slope terms: slope × (s + (s+1) + ... + (s+4)) = slope × (5s + 10)
+      const supply = poolAccount.totalSupply.toNumber();
+      expect(parts.curveComponent.toString()).to.equal(
+        poolAccount.curveParam.muln(5 * supply + 10).toString()
+      );
+      // The fee comes out of the curve cost rather than on top of it
+      expect(
+        parts.baseComponent.add(parts.curveComponent).add(parts.buyTax).toString()
+      ).to.equal(sim.solAmount.toString());
+      expect(parts.fee.toString()).to.equal(sim.fee.toString());
+    });
+  });
+
+  describe("Early-Sell Penalty", () => {
+    it("Charges a just-bought position close to the full penalty", async () => {
+      const penaltyChannelId = "UC_sell_penalty_test";
+      const penaltyPoolPda = creatorPoolPdaFor(penaltyChannelId);
+      const tradeAccounts = {
+        pool: penaltyPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeCreatorPool(penaltyChannelId, "Penalty Channel", "", null, null, 0)
+        .accounts({
+          pool: penaltyPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setSellPenalty(new BN(5000), new BN(3600))
+        .accounts({ pool: penaltyPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+      await program.methods.buyTokens(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+
+      const tx = await program.methods
+        .sellTokens(new BN(1), new BN(0))
+        .accounts(tradeAccounts)
+        .rpc({ commitment: "confirmed" });
+
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const traded = [...parser.parseLogs(txInfo.meta.logMessages)].find(
+        (event) => event.name.toLowerCase() === "tokenstraded"
+      );
+      // 1% fee plus a penalty that has barely started to decay from 50%
+      const feeBps = traded.data.creatorFee.toNumber() * 10000 / traded.data.solAmount.toNumber();
+      expect(feeBps).to.be.greaterThan(5000);
+      expect(feeBps).to.be.at.most(5100);
+    });
+  });
+
+  describe("Buy Tax", () => {
+    it("Keeps the buy tax in the reserve without minting for it", async () => {
+      const taxedChannelId = "UC_buy_tax_test";
+      const taxedPoolPda = creatorPoolPdaFor(taxedChannelId);
+
+      await program.methods
+        .initializeCreatorPool(taxedChannelId, "Taxed Channel", "", null, null, 0)
+        .accounts({
+          pool: taxedPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setBuyTax(1000)
+        .accounts({ pool: taxedPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      const tx = await program.methods
+        .buyTokens(new BN(1), new BN(0))
+        .accounts({
+          pool: taxedPoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      // Curve cost 0.01 SOL: 1% fee leaves the pool, the 10% tax stays in it
+      const poolAccount = await program.account.pool.fetch(taxedPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(1);
+      expect(poolAccount.reserveSol.toNumber()).to.equal(10_000_000 - 100_000 + 1_000_000);
+
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const traded = [...parser.parseLogs(txInfo.meta.logMessages)].find(
+        (event) => event.name.toLowerCase() === "tokenstraded"
+      );
+      expect(traded.data.buyTax.toNumber()).to.equal(1_000_000);
+    });
+  });
+
+  describe("Event Sequence", () => {
+    it("Numbers each pool event one after another", async () => {
+      const seqChannelId = "UC_event_seq_test";
+      const seqPoolPda = creatorPoolPdaFor(seqChannelId);
+
+      await program.methods
+        .initializeCreatorPool(seqChannelId, "Sequenced Channel", "", null, null, 0)
+        .accounts({
+          pool: seqPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      expect((await program.account.pool.fetch(seqPoolPda)).seq.toNumber()).to.equal(1); // PoolCreated
+
+      const tx = await program.methods
+        .buyTokens(new BN(1), new BN(0))
+        .accounts({
+          pool: seqPoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const traded = [...parser.parseLogs(txInfo.meta.logMessages)].find(
+        (event) => event.name.toLowerCase() === "tokenstraded"
+      );
+      expect(traded.data.seq.toNumber()).to.equal(2);
+      expect((await program.account.pool.fetch(seqPoolPda)).seq.toNumber()).to.equal(2);
+    });
+  });
+
+  describe("Trade Event Toggle", () => {
+    it("Skips TokensTraded once the creator turns trade events off", async () => {
+      const quietChannelId = "UC_quiet_events_test";
+      const quietPoolPda = creatorPoolPdaFor(quietChannelId);
+      const trade = {
+        pool: quietPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeCreatorPool(quietChannelId, "Quiet Channel", "", null, null, 0)
+        .accounts({
+          pool: quietPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      expect((await program.account.pool.fetch(quietPoolPda)).emitEvents).to.be.true;
+
+      await program.methods
+        .setEmitEvents(false)
+        .accounts({ pool: quietPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      const tx = await program.methods
+        .buyTokens(new BN(2), new BN(0))
+        .accounts(trade)
+        .rpc({ commitment: "confirmed" });
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const traded = [...parser.parseLogs(txInfo.meta.logMessages)].find(
+        (event) => event.name.toLowerCase() === "tokenstraded"
+      );
+      expect(traded).to.be.undefined;
+
+      // State still moves, so indexers can poll it
+      expect((await program.account.pool.fetch(quietPoolPda)).totalSupply.toNumber()).to.equal(2);
+    });
+  });
+
+  describe("Fee-Free Threshold", () => {
+    it("Waives the fee on small trades only", async () => {
+      const smallChannelId = "UC_fee_free_test";
+      const smallPoolPda = creatorPoolPdaFor(smallChannelId);
+      const tradeAccounts = {
+        pool: smallPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeCreatorPool(smallChannelId, "Small Fans Channel", "", null, null, 0)
+        .accounts({
+          pool: smallPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setFeeFreeThreshold(new BN(2))
+        .accounts({ pool: smallPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      await program.methods.buyTokens(new BN(2), new BN(0)).accounts(tradeAccounts).rpc();
+      let poolAccount = await program.account.pool.fetch(smallPoolPda);
+      expect(poolAccount.lifetimeFees.toNumber()).to.equal(0);
+      expect(poolAccount.reserveSol.toString()).to.equal(poolAccount.totalBuyVolume.toString());
+
+      await program.methods.buyTokens(new BN(3), new BN(0)).accounts(tradeAccounts).rpc();
+      poolAccount = await program.account.pool.fetch(smallPoolPda);
+      expect(poolAccount.lifetimeFees.toNumber()).to.be.greaterThan(0);
+    });
+  });
+
+  describe("Minimum Reserve Ratio", () => {
+    it("Diverts the creator fee to keep the reserve fully backed", async () => {
+      const backedChannelId = "UC_reserve_ratio_test";
+      const backedPoolPda = creatorPoolPdaFor(backedChannelId);
+
+      await program.methods
+        .initializeCreatorPool(backedChannelId, "Backed Channel", "", null, null, 0)
+        .accounts({
+          pool: backedPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setMinReserveRatio(10_000)
+        .accounts({ pool: backedPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      const tx = await program.methods
+        .buyTokens(new BN(1), new BN(0))
+        .accounts({
+          pool: backedPoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      // The whole 1% fee on the 0.01 SOL curve cost goes back into the reserve
+      const poolAccount = await program.account.pool.fetch(backedPoolPda);
+      expect(poolAccount.reserveSol.toNumber()).to.equal(10_000_000);
+
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const traded = [...parser.parseLogs(txInfo.meta.logMessages)].find(
+        (event) => event.name.toLowerCase() === "tokenstraded"
+      );
+      expect(traded.data.reserveTopUp.toNumber()).to.equal(100_000);
+      expect(traded.data.creatorFee.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("Burn On Sell", () => {
+    it("Burns a share of each sell and prices later trades past it", async () => {
+      const burnChannelId = "UC_burn_on_sell_test";
+      const burnPoolPda = creatorPoolPdaFor(burnChannelId);
+      const tradeAccounts = {
+        pool: burnPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeCreatorPool(burnChannelId, "Burning Channel", "", null, null, 0)
+        .accounts({
+          pool: burnPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setBurnOnSell(5000)
+        .accounts({ pool: burnPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+      await program.methods.buyTokens(new BN(4), new BN(0)).accounts(tradeAccounts).rpc();
+      await program.methods.sellTokens(new BN(2), new BN(0)).accounts(tradeAccounts).rpc();
+
+      // Half of the 2 sold are burned, so the curve sits at 2 held + 1 burned
+      const poolAccount = await program.account.pool.fetch(burnPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(2);
+      expect(poolAccount.burnedSupply.toNumber()).to.equal(1);
+
+      const price = await program.methods.getPrice().accounts({ pool: burnPoolPda }).view();
+      const priceAtCurve = await program.methods
+        .getPriceAt(new BN(3))
+        .accounts({ pool: burnPoolPda })
+        .view();
+      expect(price.toString()).to.equal(priceAtCurve.toString());
+    });
+  });
+
+  describe("Linear Price Overflow", () => {
+    it("Fails the strict price where the linear curve outgrows u64", async () => {
+      const steepChannelId = "UC_linear_overflow_test";
+      const steepPoolPda = creatorPoolPdaFor(steepChannelId);
+
+      await program.methods
+        .initializeCreatorPool(steepChannelId, "Steep Linear", "", null, new BN("1000000000000"), 0)
+        .accounts({
+          pool: steepPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      const farSupply = new BN("100000000000000000");
+      try {
+        await program.methods.getPriceAt(farSupply).accounts({ pool: steepPoolPda }).view();
+        expect.fail("a linear price past u64 should not saturate");
+      } catch (err) {
+        expect(err.toString()).to.include("Overflow");
+      }
+    });
+  });
+
+  describe("Packed View", () => {
+    it("Packs the pool's key fields into the documented layout", async () => {
+      const packed = Buffer.from(
+        await program.methods.getPoolPacked().accounts({ pool: creatorPoolPda }).view()
+      );
+      const poolAccount = await program.account.pool.fetch(creatorPoolPda);
+
+      expect(packed.length).to.equal(111);
+      expect(packed[0]).to.equal(1); // layout version
+      expect(packed[1]).to.equal(0); // PoolType.Creator
+      expect(packed[3]).to.equal(poolAccount.decimals);
+      expect(new PublicKey(packed.subarray(5, 37)).toBase58()).to.equal(
+        poolAccount.creatorWallet.toBase58()
+      );
+      expect(packed.readBigUInt64LE(37).toString()).to.equal(poolAccount.totalSupply.toString());
+      expect(packed.readBigUInt64LE(45).toString()).to.equal(poolAccount.reserveSol.toString());
+      expect(packed.readUInt16LE(77)).to.equal(poolAccount.feeBps.toNumber());
+    });
+  });
+
+  describe("Create And Buy", () => {
+    it("Launches a pool with the creator as its first holder", async () => {
+      const launchChannelId = "UC_create_and_buy_test";
+      const launchPoolPda = creatorPoolPdaFor(launchChannelId);
+
+      await program.methods
+        .initializeCreatorPoolAndBuy(
+          launchChannelId,
+          "Launch Channel",
+          "",
+          null,
+          null,
+          0,
+          new BN(3),
+          new BN(LAMPORTS_PER_SOL)
+        )
+        .accounts({
+          pool: launchPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(launchPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(3);
+      const position = await program.account.holderPosition.fetch(
+        positionPda(launchPoolPda, creatorWallet.publicKey)
+      );
+      expect(position.balance.toNumber()).to.equal(3);
+    });
+
+    it("Rolls back the launch when the first buy is over budget", async () => {
+      const launchChannelId = "UC_create_and_buy_slippage";
+      const launchPoolPda = creatorPoolPdaFor(launchChannelId);
+
+      try {
+        await program.methods
+          .initializeCreatorPoolAndBuy(
+            launchChannelId,
+            "Launch Channel",
+            "",
+            null,
+            null,
+            0,
+            new BN(3),
+            new BN(1)
+          )
+          .accounts({
+            pool: launchPoolPda,
+            creatorWallet: creatorWallet.publicKey,
+          })
+          .signers([creatorWallet])
+          .rpc();
+        expect.fail("over-budget launch should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("SlippageExceeded");
+      }
+      expect(await provider.connection.getAccountInfo(launchPoolPda)).to.be.null;
+    });
+  });
+
+  describe("Holder Airdrop", () => {
+    it("Pays holders pro rata from a snapshot and rejects later buyers", async () => {
+      const airdropChannelId = "UC_holder_airdrop_test";
+      const airdropPoolPda = creatorPoolPdaFor(airdropChannelId);
+      const lateBuyer = anchor.web3.Keypair.generate();
+      const tradeAccounts = (trader: PublicKey) => ({
+        pool: airdropPoolPda,
+        trader,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      });
+      const [snapshotPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("holder_snapshot"), airdropPoolPda.toBuffer(), new BN(1).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      const airdropSig = await provider.connection.requestAirdrop(lateBuyer.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(airdropSig);
+      await program.methods
+        .initializeCreatorPool(airdropChannelId, "Airdrop Channel", "", null, null, 0)
+        .accounts({
+          pool: airdropPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .buyTokens(new BN(4), new BN(0))
+        .accounts(tradeAccounts(provider.wallet.publicKey))
+        .rpc();
+
+      const reward = new BN(LAMPORTS_PER_SOL / 10);
+      await program.methods
+        .snapshotHolders(reward, new BN(3600))
+        .accounts({ pool: airdropPoolPda, snapshot: snapshotPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+      const snapshot = await program.account.holderSnapshot.fetch(snapshotPda);
+      expect(snapshot.totalSupply.toNumber()).to.equal(4);
+
+      // The only holder at snapshot time gets the whole reward
+      const before = await provider.connection.getBalance(provider.wallet.publicKey);
+      await program.methods
+        .claimAirdrop(new BN(1))
+        .accounts({ pool: airdropPoolPda, snapshot: snapshotPda, holder: provider.wallet.publicKey })
+        .rpc();
+      const after = await provider.connection.getBalance(provider.wallet.publicKey);
+      expect(after - before).to.be.greaterThan(reward.toNumber() - 10_000);
+
+      try {
+        await program.methods
+          .claimAirdrop(new BN(1))
+          .accounts({ pool: airdropPoolPda, snapshot: snapshotPda, holder: provider.wallet.publicKey })
+          .rpc();
+        expect.fail("second claim should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("AirdropAlreadyClaimed");
+      }
+
+      await program.methods
+        .buyTokens(new BN(1), new BN(0))
+        .accounts(tradeAccounts(lateBuyer.publicKey))
+        .signers([lateBuyer])
+        .rpc();
+      try {
+        await program.methods
+          .claimAirdrop(new BN(1))
+          .accounts({ pool: airdropPoolPda, snapshot: snapshotPda, holder: lateBuyer.publicKey })
+          .signers([lateBuyer])
+          .rpc();
+        expect.fail("a buyer after the snapshot should not claim");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("BoughtAfterSnapshot");
+      }
+    });
+  });
+
+  describe("Buy Cooldown", () => {
+    it("Makes each wallet wait between buys", async () => {
+      const cooldownChannelId = "UC_buy_cooldown_test";
+      const cooldownPoolPda = creatorPoolPdaFor(cooldownChannelId);
+      const otherBuyer = anchor.web3.Keypair.generate();
+      const tradeAccounts = (trader: PublicKey) => ({
+        pool: cooldownPoolPda,
+        trader,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      });
+
+      const airdropSig = await provider.connection.requestAirdrop(otherBuyer.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(airdropSig);
+      await program.methods
+        .initializeCreatorPool(cooldownChannelId, "Cooldown Channel", "", null, null, 0)
+        .accounts({
+          pool: cooldownPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setBuyCooldown(new BN(600))
+        .accounts({ pool: cooldownPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      await program.methods
+        .buyTokens(new BN(1), new BN(0))
+        .accounts(tradeAccounts(provider.wallet.publicKey))
+        .rpc();
+      try {
+        await program.methods
+          .buyTokens(new BN(1), new BN(0))
+          .accounts(tradeAccounts(provider.wallet.publicKey))
+          .rpc();
+        expect.fail("second buy inside the cooldown should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("BuyCooldownActive");
+      }
+
+      // Another wallet is not held up by the first one's cooldown
+      await program.methods
+        .buyTokens(new BN(1), new BN(0))
+        .accounts(tradeAccounts(otherBuyer.publicKey))
+        .signers([otherBuyer])
+        .rpc();
+      const poolAccount = await program.account.pool.fetch(cooldownPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(2);
+    });
+  });
+
+  describe("Presale Whitelist", () => {
+    it("Lets only proven wallets buy before the whitelist closes", async () => {
+      const presaleChannelId = "UC_presale_whitelist_test";
+      const presalePoolPda = creatorPoolPdaFor(presaleChannelId);
+      const outsider = anchor.web3.Keypair.generate();
+      const tradeAccounts = (trader: PublicKey) => ({
+        pool: presalePoolPda,
+        trader,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      });
+
+      // Two-leaf tree: the provider wallet and one other wallet
+      const sha256 = (...parts: Buffer[]) => createHash("sha256").update(Buffer.concat(parts)).digest();
+      const insiderLeaf = sha256(provider.wallet.publicKey.toBuffer());
+      const otherLeaf = sha256(anchor.web3.Keypair.generate().publicKey.toBuffer());
+      const root = sha256(...[insiderLeaf, otherLeaf].sort(Buffer.compare));
+
+      const airdropSig = await provider.connection.requestAirdrop(outsider.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(airdropSig);
+      await program.methods
+        .initializeCreatorPool(presaleChannelId, "Presale Channel", "", null, null, 0)
+        .accounts({
+          pool: presalePoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      const whitelistUntil = new BN(Math.floor(Date.now() / 1000) + 3600);
+      await program.methods
+        .setWhitelist([...root], whitelistUntil)
+        .accounts({ pool: presalePoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      try {
+        await program.methods
+          .buyTokens(new BN(1), new BN(0))
+          .accounts(tradeAccounts(outsider.publicKey))
+          .signers([outsider])
+          .rpc();
+        expect.fail("a wallet off the whitelist should not buy during the presale");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("NotWhitelisted");
+      }
+
+      try {
+        await program.methods
+          .proveWhitelisted([[...otherLeaf]])
+          .accounts({ pool: presalePoolPda, trader: outsider.publicKey })
+          .signers([outsider])
+          .rpc();
+        expect.fail("a proof for another wallet should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("NotWhitelisted");
+      }
+
+      await program.methods
+        .proveWhitelisted([[...otherLeaf]])
+        .accounts({ pool: presalePoolPda, trader: provider.wallet.publicKey })
+        .rpc();
+      await program.methods
+        .buyTokens(new BN(2), new BN(0))
+        .accounts(tradeAccounts(provider.wallet.publicKey))
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(presalePoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(2);
+    });
+  });
+
+  describe("Sell Window", () => {
+    it("Accepts buys but rejects sells before the window opens", async () => {
+      const windowChannelId = "UC_sell_window_test";
+      const windowPoolPda = creatorPoolPdaFor(windowChannelId);
+      const tradeAccounts = {
+        pool: windowPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+      const manageAccounts = { pool: windowPoolPda, creator: creatorWallet.publicKey };
+
+      await program.methods
+        .initializeCreatorPool(windowChannelId, "Sell Window Channel", "", null, null, 0)
+        .accounts({
+          pool: windowPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      const opensAt = Math.floor(Date.now() / 1000) + 3600;
+      try {
+        await program.methods
+          .setSellWindow(new BN(opensAt), new BN(opensAt))
+          .accounts(manageAccounts)
+          .signers([creatorWallet])
+          .rpc();
+        expect.fail("an empty window should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidSellWindow");
+      }
+      await program.methods
+        .setSellWindow(new BN(opensAt), new BN(0))
+        .accounts(manageAccounts)
+        .signers([creatorWallet])
+        .rpc();
+
+      await program.methods.buyTokens(new BN(2), new BN(0)).accounts(tradeAccounts).rpc();
+      try {
+        await program.methods.sellTokens(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+        expect.fail("sell before the window opens should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("SellWindowClosed");
+      }
+
+      const summary = await program.methods.getPoolSummary().accounts({ pool: windowPoolPda }).view();
+      expect(summary.tradingStatus).to.equal(8); // SellsClosed
+    });
+  });
+
+  describe("Reserve Vault", () => {
+    it("Keeps the reserve in a separate vault account", async () => {
+      const vaultChannelId = "UC_reserve_vault_test";
+      const vaultPoolPda = creatorPoolPdaFor(vaultChannelId);
+      const [reserveVaultPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("reserve"), vaultPoolPda.toBuffer()],
+        program.programId
+      );
+      const tradeAccounts = {
+        pool: vaultPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeCreatorPool(vaultChannelId, "Vault Channel", "", null, null, 0)
+        .accounts({
+          pool: vaultPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .enableReserveVault()
+        .accounts({ pool: vaultPoolPda, reserveVault: reserveVaultPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      try {
+        await program.methods.buyTokens(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+        expect.fail("a buy without the vault should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("ReserveVaultMissing");
+      }
+
+      const rentMinimum = await provider.connection.getMinimumBalanceForRentExemption(0);
+      await program.methods
+        .buyTokens(new BN(5), new BN(0))
+        .accounts({ ...tradeAccounts, reserveVault: reserveVaultPda })
+        .rpc();
+      let poolAccount = await program.account.pool.fetch(vaultPoolPda);
+      expect(poolAccount.reserveVaultEnabled).to.be.true;
+      expect(await provider.connection.getBalance(reserveVaultPda)).to.equal(
+        rentMinimum + poolAccount.reserveSol.toNumber()
+      );
+
+      await program.methods
+        .sellTokens(new BN(2), new BN(0))
+        .accounts({ ...tradeAccounts, reserveVault: reserveVaultPda })
+        .rpc();
+      poolAccount = await program.account.pool.fetch(vaultPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(3);
+      expect(await provider.connection.getBalance(reserveVaultPda)).to.equal(
+        rentMinimum + poolAccount.reserveSol.toNumber()
+      );
+    });
+  });
+
+  describe("Price Impact Cap", () => {
+    it("Rejects single buys that move the price past the pool's cap", async () => {
+      const impactChannelId = "UC_price_impact_test";
+      const impactPoolPda = creatorPoolPdaFor(impactChannelId);
+      const tradeAccounts = {
+        pool: impactPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeCreatorPool(impactChannelId, "Impact Channel", "", null, null, 0)
+        .accounts({
+          pool: impactPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setMaxPriceImpact(100)
+        .accounts({ pool: impactPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      // One token moves the default curve by slope / base = 1%
+      await program.methods.buyTokens(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+
+      try {
+        await program.methods.buyTokens(new BN(5), new BN(0)).accounts(tradeAccounts).rpc();
+        expect.fail("a buy past the impact cap should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("ImpactTooHigh");
+      }
+      expect((await program.account.pool.fetch(impactPoolPda)).totalSupply.toNumber()).to.equal(1);
+    });
+  });
+
+  describe("Zero Base Price", () => {
+    it("Trades a linear pool whose curve starts at 0", async () => {
+      const zeroChannelId = "UC_zero_base_test";
+      const zeroPoolPda = creatorPoolPdaFor(zeroChannelId);
+
+      await program.methods
+        .initializeCreatorPool(zeroChannelId, "Zero Base", "", new BN(0), null, 0)
+        .accounts({
+          pool: zeroPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .buyTokens(new BN(5), new BN(0))
+        .accounts({
+          pool: zeroPoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(zeroPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(5);
+      expect(poolAccount.reserveSol.toNumber()).to.be.greaterThan(0);
+    });
+  });
+
+  describe("Pool Type Conversion", () => {
+    it("Converts an untraded pool and refuses once tokens exist", async () => {
+      const convertChannelId = "UC_convert_type_test";
+      const convertPoolPda = creatorPoolPdaFor(convertChannelId);
+
+      await program.methods
+        .initializeCreatorPool(convertChannelId, "Convert Channel", "", null, null, 0)
+        .accounts({
+          pool: convertPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      // A slope-sized parameter is rejected for an exponential curve
+      try {
+        await program.methods
+          .convertPoolType({ stream: {} }, new BN(100_000))
+          .accounts({ pool: convertPoolPda, creator: creatorWallet.publicKey })
+          .signers([creatorWallet])
+          .rpc();
+        expect.fail("a slope should not pass as a growth rate");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("CurveParamOutOfRange");
+      }
+
+      await program.methods
+        .convertPoolType({ stream: {} }, new BN(500))
+        .accounts({ pool: convertPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      const pool = await program.account.pool.fetch(convertPoolPda);
+      expect(pool.poolType).to.deep.equal({ stream: {} });
+      expect(pool.curveParam.toNumber()).to.equal(500);
+      // The untouched creator default becomes the stream default
+      expect(pool.basePrice.toNumber()).to.equal(1_000_000);
+
+      await program.methods
+        .buyTokens(new BN(1), new BN(0))
+        .accounts({
+          pool: convertPoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .convertPoolType({ creator: {} }, new BN(100_000))
+          .accounts({ pool: convertPoolPda, creator: creatorWallet.publicKey })
+          .signers([creatorWallet])
+          .rpc();
+        expect.fail("a traded pool should not be converted");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("SupplyNotZero");
+      }
+    });
+  });
+
+  describe("Rate Limit", () => {
+    it("Rejects buys past the window's notional limit", async () => {
+      const limitedChannelId = "UC_rate_limit_test";
+      const limitedPoolPda = creatorPoolPdaFor(limitedChannelId);
+      const tradeAccounts = {
+        pool: limitedPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeCreatorPool(limitedChannelId, "Limited Channel", "", null, null, 0)
+        .accounts({
+          pool: limitedPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods.buyTokens(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+      const firstCost = (await program.account.pool.fetch(limitedPoolPda)).totalBuyVolume;
+
+      // Room for one more token at the current price, but not two
+      await program.methods
+        .setRateLimit(firstCost.muln(2), new BN(3600))
+        .accounts({ pool: limitedPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+      await program.methods.buyTokens(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+
+      try {
+        await program.methods.buyTokens(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+        expect.fail("buy past the window limit should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("RateLimitExceeded");
+      }
+
+      const poolAccount = await program.account.pool.fetch(limitedPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(2);
+      expect(poolAccount.windowNotional.toNumber()).to.equal(
+        poolAccount.totalBuyVolume.sub(firstCost).toNumber()
+      );
+    });
+  });
+
+  describe("Timed Pause", () => {
+    it("Halts trading until the pause lifts on its own", async () => {
+      const pausedChannelId = "UC_timed_pause_test";
+      const pausedPoolPda = creatorPoolPdaFor(pausedChannelId);
+      const tradeAccounts = {
+        pool: pausedPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeCreatorPool(pausedChannelId, "Paused Channel", "", null, null, 0)
+        .accounts({
+          pool: pausedPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods.buyTokens(new BN(2), new BN(0)).accounts(tradeAccounts).rpc();
+      await program.methods
+        .pauseTradingFor(new BN(3))
+        .accounts({ pool: pausedPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      try {
+        await program.methods.sellTokens(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+        expect.fail("sell during the pause should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("TradingPaused");
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 5000));
+
+      await program.methods.sellTokens(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+      const poolAccount = await program.account.pool.fetch(pausedPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(1);
+    });
+  });
+
+  describe("Top Holders", () => {
+    it("Ranks holders by balance and drops those who sell out", async () => {
+      const boardChannelId = "UC_top_holders_test";
+      const boardPoolPda = creatorPoolPdaFor(boardChannelId);
+      const [topHoldersPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("top_holders"), boardPoolPda.toBuffer()],
+        program.programId
+      );
+      const holder = anchor.web3.Keypair.generate();
+      const tradeAccounts = (trader: PublicKey) => ({
+        pool: boardPoolPda,
+        trader,
+        creatorWallet: creatorWallet.publicKey,
+        topHolders: topHoldersPda,
+        systemProgram: SystemProgram.programId,
+      });
+
+      await program.methods
+        .initializeCreatorPool(boardChannelId, "Leaderboard Channel", "", null, null, 0)
+        .accounts({
+          pool: boardPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .initializeTopHolders()
+        .accounts({ pool: boardPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+      const airdropSig = await provider.connection.requestAirdrop(holder.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(airdropSig);
+
+      await program.methods
+        .buyTokens(new BN(2), new BN(0))
+        .accounts(tradeAccounts(provider.wallet.publicKey))
+        .rpc();
+      await program.methods
+        .buyTokens(new BN(3), new BN(0))
+        .accounts(tradeAccounts(holder.publicKey))
+        .signers([holder])
+        .rpc();
+
+      let board = await program.methods
+        .getTopHolders()
+        .accounts({ pool: boardPoolPda, topHolders: topHoldersPda })
+        .view();
+      expect(board.map((entry) => entry.owner.toBase58())).to.deep.equal([
+        holder.publicKey.toBase58(),
+        provider.wallet.publicKey.toBase58(),
+      ]);
+      expect(board.map((entry) => entry.balance.toNumber())).to.deep.equal([3, 2]);
+
+      const rankOf = (balance: number) =>
+        program.methods
+          .getHolderRank(new BN(balance))
+          .accounts({ pool: boardPoolPda, topHolders: topHoldersPda })
+          .view();
+      expect(await rankOf(3)).to.equal(1);
+      expect(await rankOf(2)).to.equal(2);
+      expect(await rankOf(1)).to.equal(3);
+      expect(await rankOf(0)).to.equal(4294967295); // unranked
+
+      await program.methods
+        .sellTokens(new BN(3), new BN(0))
+        .accounts(tradeAccounts(holder.publicKey))
+        .signers([holder])
+        .rpc();
+
+      board = await program.methods
+        .getTopHolders()
+        .accounts({ pool: boardPoolPda, topHolders: topHoldersPda })
+        .view();
+      expect(board).to.have.lengthOf(1);
+      expect(board[0].owner.toBase58()).to.equal(provider.wallet.publicKey.toBase58());
+    });
+  });
+
+  describe("Ownership Attestation", () => {
+    const oracle = anchor.web3.Keypair.generate();
+    const [globalConfigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("global_config")],
+      program.programId
+    );
+    const [programDataPda] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
+    const attestation = (signer: anchor.web3.Keypair, identifier: string, wallet: PublicKey) =>
+      Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: signer.secretKey,
+        message: Buffer.concat([Buffer.from("sipzy:owner:"), Buffer.from(identifier), wallet.toBuffer()]),
+      });
+
+    before(async () => {
+      // The test validator deploys with the provider wallet as upgrade authority
+      await program.methods
+        .initializeGlobalConfig(oracle.publicKey)
+        .accounts({ programData: programDataPda })
+        .rpc();
+    });
+
+    it("Verifies a pool with the oracle's signature", async () => {
+      await program.methods
+        .verifyPool()
+        .accounts({ pool: creatorPoolPda, instructions: SYSVAR_INSTRUCTIONS_PUBKEY })
+        .preInstructions([attestation(oracle, channelId, creatorWallet.publicKey)])
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(creatorPoolPda);
+      expect(poolAccount.verified).to.be.true;
+    });
+
+    it("Rejects an attestation from another key", async () => {
+      const impostor = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .verifyPool()
+          .accounts({ pool: creatorPoolPda, instructions: SYSVAR_INSTRUCTIONS_PUBKEY })
+          .preInstructions([attestation(impostor, channelId, creatorWallet.publicKey)])
+          .rpc();
+        expect.fail("attestation from a non-oracle key should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidAttestation");
+      }
+    });
+  });
+
+  // Relies on the global config created in "Ownership Attestation"
+  describe("Emergency Withdrawal", () => {
+    it("Rescues reserve only while globally paused", async () => {
+      const rescueChannelId = "UC_emergency_test";
+      const rescuePoolPda = creatorPoolPdaFor(rescueChannelId);
+      const recovery = anchor.web3.Keypair.generate();
+      const manage = { pool: rescuePoolPda, creator: creatorWallet.publicKey };
+
+      await program.methods
+        .initializeCreatorPool(rescueChannelId, "Rescue Channel", "", null, null, 0)
+        .accounts({
+          pool: rescuePoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .buyTokens(new BN(2), new BN(0))
+        .accounts({
+          pool: rescuePoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      const reserve = (await program.account.pool.fetch(rescuePoolPda)).reserveSol;
+
+      try {
+        await program.methods
+          .emergencyWithdrawReserve(reserve)
+          .accounts({ pool: rescuePoolPda, destination: recovery.publicKey })
+          .rpc();
+        expect.fail("withdrawal without a global pause should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("NotGloballyPaused");
+      }
+
+      await program.methods.setGlobalPause(true).rpc();
+      const summary = await program.methods.getPoolSummary().accounts({ pool: rescuePoolPda }).view();
+      expect(summary.tradingStatus).to.equal(5); // GloballyPaused
+      await program.methods
+        .emergencyWithdrawReserve(reserve)
+        .accounts({ pool: rescuePoolPda, destination: recovery.publicKey })
+        .rpc();
+
+      // Nothing trades or reopens while the pause holds, rescued or not
+      try {
+        await program.methods
+          .buyTokens(new BN(1), new BN(0))
+          .accounts({
+            pool: creatorPoolPda,
+            trader: provider.wallet.publicKey,
+            creatorWallet: creatorWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("buys should be rejected while globally paused");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("GloballyPaused");
+      }
+      try {
+        await program.methods.reactivatePool().accounts(manage).signers([creatorWallet]).rpc();
+        expect.fail("reactivation should be rejected while globally paused");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("GloballyPaused");
+      }
+      await program.methods.setGlobalPause(false).rpc();
+
+      const poolAccount = await program.account.pool.fetch(rescuePoolPda);
+      expect(poolAccount.reserveSol.toNumber()).to.equal(0);
+      expect(poolAccount.emergencyWithdrawn.toString()).to.equal(reserve.toString());
+      expect(poolAccount.isActive).to.equal(false);
+      expect(await provider.connection.getBalance(recovery.publicKey)).to.equal(reserve.toNumber());
+
+      // The rescued pool stays shut after the pause lifts until the admin clears it
+      try {
+        await program.methods.reactivatePool().accounts(manage).signers([creatorWallet]).rpc();
+        expect.fail("reactivation should wait for the admin to clear the withdrawal");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("EmergencyWithdrawalPending");
+      }
+      await program.methods.clearEmergencyWithdrawal().accounts({ pool: rescuePoolPda }).rpc();
+      await program.methods.reactivatePool().accounts(manage).signers([creatorWallet]).rpc();
+      expect((await program.account.pool.fetch(rescuePoolPda)).isActive).to.equal(true);
+    });
+  });
+
+  // Relies on the global config created in "Ownership Attestation"
+  describe("Insurance Fund", () => {
+    const [insuranceFundPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("insurance_fund")],
+      program.programId
+    );
+
+    after(async () => {
+      // Leave creator fees untouched for the suites that follow
+      await program.methods.setInsuranceFee(0).rpc();
+    });
+
+    it("Collects a share of fees and tops up an undercollateralized pool", async () => {
+      const insuredChannelId = "UC_insurance_test";
+      const insuredPoolPda = creatorPoolPdaFor(insuredChannelId);
+      const trade = {
+        pool: insuredPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        insuranceFund: insuranceFundPda,
+      };
+
+      await program.methods.initializeInsuranceFund(1000).rpc();
+      await program.methods
+        .initializeCreatorPool(insuredChannelId, "Insured Channel", "", null, null, 0)
+        .accounts({
+          pool: insuredPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods.buyTokens(new BN(5), new BN(0)).accounts(trade).rpc();
+
+      // 10% of the fee went to the fund, the rest to the creator
+      let fund = await program.account.insuranceFund.fetch(insuranceFundPda);
+      const contributed = fund.totalContributed.toNumber();
+      const creatorShare = (await program.account.pool.fetch(insuredPoolPda)).lifetimeFees.toNumber();
+      expect(contributed).to.be.greaterThan(0);
+      expect(contributed).to.equal(Math.floor((creatorShare + contributed) / 10));
+
+      // The fund is resolved from its seeds, so leaving it out still pays it
+      const { insuranceFund: _fund, ...tradeWithoutFund } = trade;
+      await program.methods.buyTokens(new BN(1), new BN(0)).accounts(tradeWithoutFund).rpc();
+      fund = await program.account.insuranceFund.fetch(insuranceFundPda);
+      expect(fund.totalContributed.toNumber()).to.be.greaterThan(contributed);
+
+      // and no other account can stand in for it
+      try {
+        await program.methods
+          .buyTokens(new BN(1), new BN(0))
+          .accounts({ ...trade, insuranceFund: anchor.web3.Keypair.generate().publicKey })
+          .rpc();
+        expect.fail("a trade must pay the real insurance fund");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("ConstraintSeeds");
+      }
+
+      try {
+        await program.methods
+          .claimInsurance(new BN(1))
+          .accounts({ pool: insuredPoolPda, insuranceFund: insuranceFundPda })
+          .rpc();
+        expect.fail("a fully backed pool should not draw on the fund");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("ClaimExceedsShortfall");
+      }
+
+      // Open a gap by rescuing part of the reserve
+      const reserve = (await program.account.pool.fetch(insuredPoolPda)).reserveSol;
+      await program.methods.setGlobalPause(true).rpc();
+      await program.methods
+        .emergencyWithdrawReserve(reserve.divn(2))
+        .accounts({ pool: insuredPoolPda, destination: anchor.web3.Keypair.generate().publicKey })
+        .rpc();
+      await program.methods.setGlobalPause(false).rpc();
+
+      const claim = new BN(10_000);
+      const before = await program.account.pool.fetch(insuredPoolPda);
+      await program.methods
+        .claimInsurance(claim)
+        .accounts({ pool: insuredPoolPda, insuranceFund: insuranceFundPda })
+        .rpc();
+
+      const after = await program.account.pool.fetch(insuredPoolPda);
+      expect(after.reserveSol.toString()).to.equal(before.reserveSol.add(claim).toString());
+      fund = await program.account.insuranceFund.fetch(insuranceFundPda);
+      expect(fund.totalClaimed.toString()).to.equal(claim.toString());
+    });
+  });
+
+  describe("Global Stats", () => {
+    it("Tracks pool counts and locked SOL across inits, trades and closes", async () => {
+      const statsChannelId = "UC_global_stats_test";
+      const statsPoolPda = creatorPoolPdaFor(statsChannelId);
+      const [globalStatsPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("global_stats")],
+        program.programId
+      );
+      const trade = {
+        pool: statsPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        globalStats: globalStatsPda,
+      };
+
+      await program.methods.initializeGlobalStats().rpc();
+      const start = await program.methods.getPoolTypeDistribution().view();
+      expect(start.creatorPools.toNumber()).to.equal(0);
+
+      await program.methods
+        .initializeCreatorPool(statsChannelId, "Stats Channel", "", null, null, 0)
+        .accounts({
+          pool: statsPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+          globalStats: globalStatsPda,
+        })
+        .rpc();
+      await program.methods
+        .initializeStreamPool("stats_stream_video", statsChannelId, "Stats Stream", "", null, null)
+        .accounts({
+          creatorWallet: creatorWallet.publicKey,
+          globalStats: globalStatsPda,
+        })
+        .rpc();
+      await program.methods.buyTokens(new BN(3), new BN(0)).accounts(trade).rpc();
+
+      let stats = await program.methods.getPoolTypeDistribution().view();
+      const pool = await program.account.pool.fetch(statsPoolPda);
+      expect(stats.creatorPools.toNumber()).to.equal(1);
+      expect(stats.streamPools.toNumber()).to.equal(1);
+      expect(stats.totalReserve.toString()).to.equal(pool.reserveSol.toString());
+
+      await program.methods.sellTokens(new BN(3), new BN(0)).accounts(trade).rpc();
+      const residual = (await program.account.pool.fetch(statsPoolPda)).reserveSol;
+      stats = await program.methods.getPoolTypeDistribution().view();
+      expect(stats.totalReserve.toString()).to.equal(residual.toString());
+
+      await program.methods
+        .closePool()
+        .accounts({
+          pool: statsPoolPda,
+          creator: creatorWallet.publicKey,
+          globalStats: globalStatsPda,
+        })
+        .signers([creatorWallet])
+        .rpc();
+      stats = await program.methods.getPoolTypeDistribution().view();
+      expect(stats.creatorPools.toNumber()).to.equal(0);
+      expect(stats.streamPools.toNumber()).to.equal(1);
+      expect(stats.totalReserve.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("Quote Mint", () => {
+    it("Settles trades in an SPL token instead of SOL", async () => {
+      const quoteChannelId = "UC_quote_mint_test";
+      const quotePoolPda = creatorPoolPdaFor(quoteChannelId);
+      const [quoteReservePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("quote_reserve"), quotePoolPda.toBuffer()],
+        program.programId
+      );
+      const payer = (provider.wallet as anchor.Wallet).payer;
+
+      await program.methods
+        .initializeCreatorPool(quoteChannelId, "Quote Mint Channel", "", null, null, 0)
+        .accounts({
+          pool: quotePoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      const quoteMint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+      const traderQuote = await createAssociatedTokenAccount(
+        provider.connection, payer, quoteMint, provider.wallet.publicKey
+      );
+      const creatorQuote = await createAssociatedTokenAccount(
+        provider.connection, payer, quoteMint, creatorWallet.publicKey
+      );
+      await mintTo(provider.connection, payer, quoteMint, traderQuote, payer, 1_000 * LAMPORTS_PER_SOL);
+
+      await program.methods
+        .setQuoteMint()
+        .accounts({
+          pool: quotePoolPda,
+          quoteMint,
+          quoteReserve: quoteReservePda,
+          creator: creatorWallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      const solTrade = {
+        pool: quotePoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+      try {
+        await program.methods.buyTokens(new BN(1), new BN(0)).accounts(solTrade).rpc();
+        expect.fail("a quote-mint pool should not take SOL");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("QuoteAccountsMissing");
+      }
+
+      const trade = {
+        ...solTrade,
+        quoteReserve: quoteReservePda,
+        traderQuoteAccount: traderQuote,
+        creatorQuoteAccount: creatorQuote,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+      await program.methods.buyTokens(new BN(3), new BN(0)).accounts(trade).rpc();
+
+      // The reserve token account holds exactly what the pool accounts for
+      let pool = await program.account.pool.fetch(quotePoolPda);
+      let reserve = await getAccount(provider.connection, quoteReservePda);
+      expect(pool.reserveSol.toNumber()).to.be.greaterThan(0);
+      expect(reserve.amount.toString()).to.equal(pool.reserveSol.toString());
+      const creatorFees = (await getAccount(provider.connection, creatorQuote)).amount;
+      expect(creatorFees.toString()).to.equal(pool.lifetimeFees.toString());
+
+      const traderBefore = (await getAccount(provider.connection, traderQuote)).amount;
+      await program.methods.sellTokens(new BN(3), new BN(0)).accounts(trade).rpc();
+      const traderAfter = (await getAccount(provider.connection, traderQuote)).amount;
+      expect(traderAfter > traderBefore).to.be.true;
+
+      pool = await program.account.pool.fetch(quotePoolPda);
+      reserve = await getAccount(provider.connection, quoteReservePda);
+      expect(reserve.amount.toString()).to.equal(pool.reserveSol.toString());
+    });
+  });
+
+  describe("Client Order IDs", () => {
+    it("Rejects a resubmitted order", async () => {
+      const trade = {
+        pool: creatorPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+      const orderId = new BN(Date.now());
+
+      await program.methods.buyTokens(new BN(1), orderId).accounts(trade).rpc();
+      try {
+        await program.methods.buyTokens(new BN(1), orderId).accounts(trade).rpc();
+        expect.fail("repeated order ID should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("DuplicateOrder");
+      }
+
+      // A fresh ID goes through, and 0 never counts as a duplicate
+      await program.methods.sellTokens(new BN(1), orderId.addn(1)).accounts(trade).rpc();
+      await program.methods.buyTokens(new BN(1), new BN(0)).accounts(trade).rpc();
+      await program.methods.sellTokens(new BN(1), new BN(0)).accounts(trade).rpc();
+    });
+  });
+
+  describe("Multisig Authority", () => {
+    it("Deactivates a team pool only after the threshold approves", async () => {
+      const base = anchor.web3.Keypair.generate();
+      const cosigner = anchor.web3.Keypair.generate();
+      const [multisigPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("multisig"), base.publicKey.toBuffer()],
+        program.programId
+      );
+      const teamChannelId = "UC_multisig_test";
+      const teamPoolPda = creatorPoolPdaFor(teamChannelId);
+
+      await program.methods
+        .createMultisig([provider.wallet.publicKey, cosigner.publicKey], 2)
+        .accounts({ base: base.publicKey })
+        .signers([base])
+        .rpc();
+      // The multisig PDA stands in for the creator wallet
+      await program.methods
+        .initializeCreatorPool(teamChannelId, "Team Channel", "", null, null, 0)
+        .accounts({
+          pool: teamPoolPda,
+          creatorWallet: multisigPda,
+        })
+        .rpc();
+
+      const [proposalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), multisigPda.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      await program.methods
+        .proposeAction({ deactivate: {} })
+        .accounts({ multisig: multisigPda, pool: teamPoolPda, proposal: proposalPda })
+        .rpc();
+
+      const executeAccounts = {
+        multisig: multisigPda,
+        proposal: proposalPda,
+        pool: teamPoolPda,
+        destination: null,
+      };
+      try {
+        await program.methods.executeProposal().accounts(executeAccounts).rpc();
+        expect.fail("a single approval should not meet a 2-of-2 threshold");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("ThresholdNotMet");
+      }
+
+      await program.methods
+        .approveProposal()
+        .accounts({ multisig: multisigPda, proposal: proposalPda, signer: cosigner.publicKey })
+        .signers([cosigner])
+        .rpc();
+      await program.methods.executeProposal().accounts(executeAccounts).rpc();
+
+      const poolAccount = await program.account.pool.fetch(teamPoolPda);
+      expect(poolAccount.isActive).to.be.false;
+      const proposal = await program.account.multisigProposal.fetch(proposalPda);
+      expect(proposal.executed).to.be.true;
+    });
+
+    it("Runs any creator instruction for a team pool through an Invoke proposal", async () => {
+      const base = anchor.web3.Keypair.generate();
+      const [multisigPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("multisig"), base.publicKey.toBuffer()],
+        program.programId
+      );
+      const teamChannelId = "UC_multisig_invoke_test";
+      const teamPoolPda = creatorPoolPdaFor(teamChannelId);
+
+      await program.methods
+        .createMultisig([provider.wallet.publicKey], 1)
+        .accounts({ base: base.publicKey })
+        .signers([base])
+        .rpc();
+      await program.methods
+        .initializeCreatorPool(teamChannelId, "Team Invoke Channel", "", null, null, 0)
+        .accounts({
+          pool: teamPoolPda,
+          creatorWallet: multisigPda,
+        })
+        .rpc();
+
+      // set_fee_bps needs the creator wallet to sign, which only the program can do for the PDA
+      const setFee = await program.methods
+        .setFeeBps(new BN(250))
+        .accounts({ pool: teamPoolPda, creator: multisigPda })
+        .instruction();
+      const [proposalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), multisigPda.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      await program.methods
+        .proposeAction({
+          invoke: {
+            data: setFee.data,
+            accounts: setFee.keys.map(({ pubkey, isSigner, isWritable }) => ({ pubkey, isSigner, isWritable })),
+          },
+        })
+        .accounts({ multisig: multisigPda, pool: teamPoolPda, proposal: proposalPda })
+        .rpc();
+
+      await program.methods
+        .executeProposal()
+        .accounts({ multisig: multisigPda, proposal: proposalPda, pool: teamPoolPda, destination: null })
+        .remainingAccounts([
+          ...setFee.keys.map(({ pubkey, isWritable }) => ({ pubkey, isWritable, isSigner: false })),
+          { pubkey: program.programId, isWritable: false, isSigner: false },
+        ])
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(teamPoolPda);
+      expect(poolAccount.feeBps.toNumber()).to.equal(250);
+    });
+  });
+
+  describe("Max Supply", () => {
+    it("Caps buys and only lets the cap grow", async () => {
+      const cappedChannelId = "UC_max_supply_test";
+      const cappedPoolPda = creatorPoolPdaFor(cappedChannelId);
+      const manageAccounts = { pool: cappedPoolPda, creator: creatorWallet.publicKey };
+      const tradeAccounts = {
+        pool: cappedPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeCreatorPool(cappedChannelId, "Capped Channel", "", null, null, 0)
+        .accounts({
+          pool: cappedPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setMaxSupply(new BN(3))
+        .accounts(manageAccounts)
+        .signers([creatorWallet])
+        .rpc();
+      await program.methods.buyTokens(new BN(3), new BN(0)).accounts(tradeAccounts).rpc();
+
+      try {
+        await program.methods.buyTokens(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+        expect.fail("buy past the cap should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("MaxSupplyExceeded");
+      }
+      try {
+        await program.methods.getPriceAt(new BN(4)).accounts({ pool: cappedPoolPda }).view();
+        expect.fail("a price past the cap should be rejected");
+      } catch (err) {
+        expect(err.toString()).to.include("MaxSupplyExceeded");
+      }
+
+      try {
+        await program.methods
+          .increaseMaxSupply(new BN(2))
+          .accounts(manageAccounts)
+          .signers([creatorWallet])
+          .rpc();
+        expect.fail("lowering the cap should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidMaxSupply");
+      }
+
+      await program.methods
+        .increaseMaxSupply(new BN(5))
+        .accounts(manageAccounts)
+        .signers([creatorWallet])
+        .rpc();
+      await program.methods.buyTokens(new BN(2), new BN(0)).accounts(tradeAccounts).rpc();
+
+      const poolAccount = await program.account.pool.fetch(cappedPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(5);
+    });
+
+    it("Fills a limit buy up to the cap only when partial fills are allowed", async () => {
+      const limitChannelId = "UC_limit_buy_test";
+      const limitPoolPda = creatorPoolPdaFor(limitChannelId);
+      const tradeAccounts = {
+        pool: limitPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+      const budget = new BN(LAMPORTS_PER_SOL);
+
+      await program.methods
+        .initializeCreatorPool(limitChannelId, "Limit Channel", "", null, null, 0)
+        .accounts({
+          pool: limitPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setMaxSupply(new BN(3))
+        .accounts({ pool: limitPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      try {
+        await program.methods
+          .buyWithLimit(new BN(5), budget, false, new BN(0))
+          .accounts(tradeAccounts)
+          .rpc();
+        expect.fail("an all-or-nothing buy past the cap should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("MaxSupplyExceeded");
+      }
+
+      try {
+        await program.methods
+          .buyWithLimit(new BN(1), new BN(1), false, new BN(0))
+          .accounts(tradeAccounts)
+          .rpc();
+        expect.fail("a buy over budget should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("SlippageExceeded");
+      }
+
+      await program.methods.buyWithLimit(new BN(5), budget, true, new BN(0)).accounts(tradeAccounts).rpc();
+      const poolAccount = await program.account.pool.fetch(limitPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(3);
+    });
+
+    it("Applies the pool's default slippage to auto-slippage buys", async () => {
+      const autoChannelId = "UC_auto_slippage_test";
+      const autoPoolPda = creatorPoolPdaFor(autoChannelId);
+      const tradeAccounts = {
+        pool: autoPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+      const manageAccounts = { pool: autoPoolPda, creator: creatorWallet.publicKey };
+
+      await program.methods
+        .initializeCreatorPool(autoChannelId, "Auto Slippage Channel", "", null, null, 0)
+        .accounts({
+          pool: autoPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      expect((await program.account.pool.fetch(autoPoolPda)).defaultSlippageBps).to.equal(100);
+
+      // One token on a linear curve costs exactly the spot price
+      await program.methods.buyTokensAutoSlippage(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+
+      // 100 tokens climb the slope well past 1% over spot
+      try {
+        await program.methods.buyTokensAutoSlippage(new BN(100), new BN(0)).accounts(tradeAccounts).rpc();
+        expect.fail("a buy past the default slippage should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("SlippageExceeded");
+      }
+
+      await program.methods
+        .setDefaultSlippage(5000)
+        .accounts(manageAccounts)
+        .signers([creatorWallet])
+        .rpc();
+      await program.methods.buyTokensAutoSlippage(new BN(100), new BN(0)).accounts(tradeAccounts).rpc();
+      expect((await program.account.pool.fetch(autoPoolPda)).totalSupply.toNumber()).to.equal(101);
+    });
+
+    it("Buys the most tokens an exact budget covers", async () => {
+      const exactChannelId = "UC_exact_cost_test";
+      const exactPoolPda = creatorPoolPdaFor(exactChannelId);
+      const tradeAccounts = {
+        pool: exactPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeCreatorPool(exactChannelId, "Exact Cost", "", null, null, 0)
+        .accounts({
+          pool: exactPoolPda,
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      // simulate_trade prices a buy exactly as it executes
+      const costOf = async (amount: number) =>
+        (
+          await program.methods
+            .simulateTrade({ buy: {} }, new BN(amount))
+            .accounts({ pool: exactPoolPda })
+            .view()
+        ).solAmount;
+      const costOfSeven = await costOf(7);
+      const budget = (await costOf(8)).subn(1);
+
+      const balanceBefore = await provider.connection.getBalance(provider.wallet.publicKey);
+      await program.methods.buyExactCost(budget, new BN(100), new BN(0)).accounts(tradeAccounts).rpc();
+      const balanceAfter = await provider.connection.getBalance(provider.wallet.publicKey);
+
+      const poolAccount = await program.account.pool.fetch(exactPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(7);
+      // Never more than the budget; the rest is the tx fee and position rent
+      expect(balanceBefore - balanceAfter).to.be.at.least(costOfSeven.toNumber());
+
+      try {
+        await program.methods
+          .buyExactCost(new BN(1), new BN(100), new BN(0))
+          .accounts(tradeAccounts)
+          .rpc();
+        expect.fail("a budget below one token should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("SlippageExceeded");
+      }
+    });
+  });
+
+  describe("Reserve-Backed Sells", () => {
+    it("Prices a large sell as a pro-rata share of the reserve", async () => {
+      const backedChannelId = "UC_reserve_backed_test";
+      const backedPoolPda = creatorPoolPdaFor(backedChannelId);
+      const tradeAccounts = {
+        pool: backedPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeCreatorPool(backedChannelId, "Reserve Backed", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(backedChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setReserveBackedSells(true)
+        .accounts({ pool: backedPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+      await program.methods.buyTokens(new BN(4), new BN(0)).accounts(tradeAccounts).rpc();
+
+      const reserveBefore = (await program.account.pool.fetch(backedPoolPda)).reserveSol;
+
+      // The top 3 of 4 tokens are worth well over half the reserve on the curve
+      const tx = await program.methods
+        .sellTokens(new BN(3), new BN(0))
+        .accounts(tradeAccounts)
+        .rpc({ commitment: "confirmed" });
+
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const traded = [...parser.parseLogs(txInfo.meta.logMessages)].find(
+        (event) => event.name.toLowerCase() === "tokenstraded"
+      );
+      expect(traded.data.pricing).to.deep.equal({ reserveProRata: {} });
+      // The fee breakdown always sums to the aggregate fee
+      const { fee, creatorFee, protocolFee, referralFee } = traded.data;
+      expect(creatorFee.add(protocolFee).add(referralFee).toString()).to.equal(fee.toString());
+
+      const poolAccount = await program.account.pool.fetch(backedPoolPda);
+      const proRata = reserveBefore.muln(3).divn(4);
+      expect(poolAccount.reserveSol.toString()).to.equal(reserveBefore.sub(proRata).toString());
+    });
+  });
+
+  describe("Compliance Freeze", () => {
+    const complianceChannelId = "UC_compliance_test";
+    const compliancePoolPda = creatorPoolPdaFor(complianceChannelId);
+    const holder = anchor.web3.Keypair.generate();
+    const holderTrade = {
+      pool: compliancePoolPda,
+      trader: holder.publicKey,
+      creatorWallet: creatorWallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+
+    before(async () => {
+      await program.methods
+        .initializeCreatorPool(complianceChannelId, "Compliance Channel", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(complianceChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      // The provider wallet initialized the pool, so it is the pool authority
+      await program.methods
+        .setComplianceEnabled(true)
+        .accounts({ pool: compliancePoolPda, authority: provider.wallet.publicKey })
+        .rpc();
+
+      const airdropSig = await provider.connection.requestAirdrop(holder.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(airdropSig);
+    });
+
+    it("Blocks a frozen holder until unfrozen", async () => {
+      const freezeAccounts = { pool: compliancePoolPda, authority: provider.wallet.publicKey };
+
+      // Freezing works before the holder has ever bought
+      await program.methods.freezePosition(holder.publicKey).accounts(freezeAccounts).rpc();
+
+      try {
+        await program.methods.buyTokens(new BN(1), new BN(0)).accounts(holderTrade).signers([holder]).rpc();
+        expect.fail("frozen holder should not be able to buy");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("PositionFrozen");
+      }
+
+      await program.methods.unfreezePosition(holder.publicKey).accounts(freezeAccounts).rpc();
+      await program.methods.buyTokens(new BN(1), new BN(0)).accounts(holderTrade).signers([holder]).rpc();
+
+      const position = await program.account.holderPosition.fetch(positionPda(compliancePoolPda, holder.publicKey));
+      expect(position.balance.toNumber()).to.equal(1);
+      expect(position.frozen).to.equal(false);
+    });
+  });
+
+  describe("Anti-Sniping Fee", () => {
+    it("Charges early buyers the decaying launch surcharge", async () => {
+      const snipeChannelId = "UC_snipe_test";
+      const snipePoolPda = creatorPoolPdaFor(snipeChannelId);
+
+      await program.methods
+        .initializeCreatorPool(snipeChannelId, "Snipe Channel", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(snipeChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      // +50% fee at launch, decaying to the normal fee over an hour
+      await program.methods
+        .setSnipeFee(new BN(5000), new BN(3600))
+        .accounts({ pool: snipePoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      await program.methods
+        .buyTokens(new BN(2), new BN(0))
+        .accounts({
+          pool: snipePoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(snipePoolPda);
+      const feeShare = poolAccount.lifetimeFees.toNumber() / poolAccount.totalBuyVolume.toNumber();
+      // Seconds into the window, the fee is still close to 1% + 50%
+      expect(feeShare).to.be.greaterThan(0.45);
+      expect(feeShare).to.be.at.most(0.51);
+    });
+  });
+
+  describe("Sell Basket", () => {
+    const basketChannelIds = ["UC_basket_a", "UC_basket_b"];
+    const basketPools = basketChannelIds.map(creatorPoolPdaFor);
+    const trader = provider.wallet.publicKey;
+
+    before(async () => {
+      for (const [i, channel] of basketChannelIds.entries()) {
+        await program.methods
+          .initializeCreatorPool(channel, `Basket ${i}`, "", null, null, 0)
+          .accounts({
+            pool: creatorPoolPdaFor(channel),
+            creatorWallet: creatorWallet.publicKey,
+          })
+          .rpc();
+        await program.methods
+          .buyTokens(new BN(4), new BN(0))
+          .accounts({
+            pool: basketPools[i],
+            trader,
+            creatorWallet: creatorWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+      }
+    });
+
+    it("Sells from several pools in one transaction", async () => {
+      const writable = (pubkey: PublicKey) => ({ pubkey, isWritable: true, isSigner: false });
+
+      await program.methods
+        .sellBasket([new BN(3), new BN(1)])
+        .accounts({ trader })
+        .remainingAccounts(
+          basketPools.flatMap((pool) => [
+            writable(pool),
+            writable(creatorWallet.publicKey),
+            writable(positionPda(pool, trader)),
+          ])
+        )
+        .rpc();
+
+      const [positionA, positionB] = await Promise.all(
+        basketPools.map((pool) => program.account.holderPosition.fetch(positionPda(pool, trader)))
+      );
+      expect(positionA.balance.toNumber()).to.equal(1);
+      expect(positionB.balance.toNumber()).to.equal(3);
+
+      const [poolA, poolB] = await Promise.all(basketPools.map((pool) => program.account.pool.fetch(pool)));
+      expect(poolA.totalSupply.toNumber()).to.equal(1);
+      expect(poolB.totalSupply.toNumber()).to.equal(3);
+    });
+  });
+
+  describe("Pool Swap", () => {
+    it("Sells one pool and buys another with the proceeds", async () => {
+      const [fromChannelId, toChannelId] = ["UC_swap_from", "UC_swap_to"];
+      const fromPool = creatorPoolPdaFor(fromChannelId);
+      const toPool = creatorPoolPdaFor(toChannelId);
+      const trader = provider.wallet.publicKey;
+
+      for (const [pool, channel] of [[fromPool, fromChannelId], [toPool, toChannelId]] as const) {
+        await program.methods
+          .initializeCreatorPool(channel, "Swap Channel", "", null, null, 0)
+          .accounts({ pool, creatorWallet: creatorWallet.publicKey })
+          .rpc();
+      }
+      await program.methods
+        .buyTokens(new BN(4), new BN(0))
+        .accounts({
+          pool: fromPool,
+          trader,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const swapAccounts = {
+        fromPool,
+        toPool,
+        trader,
+        fromCreatorWallet: creatorWallet.publicKey,
+        toCreatorWallet: creatorWallet.publicKey,
+      };
+
+      // Fees on both legs leave fewer tokens than were sold
+      try {
+        await program.methods.swapBetweenPools(new BN(3), new BN(3)).accounts(swapAccounts).rpc();
+        expect.fail("a buy leg below the minimum should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("SlippageExceeded");
+      }
+
+      await program.methods.swapBetweenPools(new BN(3), new BN(1)).accounts(swapAccounts).rpc();
+
+      const fromPosition = await program.account.holderPosition.fetch(positionPda(fromPool, trader));
+      const toPosition = await program.account.holderPosition.fetch(positionPda(toPool, trader));
+      expect(fromPosition.balance.toNumber()).to.equal(1);
+      expect(toPosition.balance.toNumber()).to.be.within(1, 2);
+      const toPoolAccount = await program.account.pool.fetch(toPool);
+      expect(toPoolAccount.totalSupply.toNumber()).to.equal(toPosition.balance.toNumber());
+    });
+  });
+
+  describe("Batch Activation", () => {
+    it("Flips several of a creator's pools in one transaction", async () => {
+      const batchChannelIds = ["UC_batch_active_a", "UC_batch_active_b"];
+      const batchPools = batchChannelIds.map(creatorPoolPdaFor);
+      const writable = (pubkey: PublicKey) => ({ pubkey, isWritable: true, isSigner: false });
+
+      for (const [i, channel] of batchChannelIds.entries()) {
+        await program.methods
+          .initializeCreatorPool(channel, `Batch ${i}`, "", null, null, 0)
+          .accounts({
+            pool: batchPools[i],
+            creatorWallet: creatorWallet.publicKey,
+          })
+          .rpc();
+      }
+
+      const stranger = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .setActiveBatch(false)
+          .accounts({ creator: stranger.publicKey })
+          .remainingAccounts(batchPools.map(writable))
+          .signers([stranger])
+          .rpc();
+        expect.fail("another wallet should not deactivate the creator's pools");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("Unauthorized");
+      }
+
+      await program.methods
+        .setActiveBatch(false)
+        .accounts({ creator: creatorWallet.publicKey })
+        .remainingAccounts(batchPools.map(writable))
+        .signers([creatorWallet])
+        .rpc();
+      let pools = await Promise.all(batchPools.map((pool) => program.account.pool.fetch(pool)));
+      expect(pools.every((pool) => !pool.isActive)).to.be.true;
+
+      await program.methods
+        .setActiveBatch(true)
+        .accounts({ creator: creatorWallet.publicKey })
+        .remainingAccounts(batchPools.map(writable))
+        .signers([creatorWallet])
+        .rpc();
+      pools = await Promise.all(batchPools.map((pool) => program.account.pool.fetch(pool)));
+      expect(pools.every((pool) => pool.isActive)).to.be.true;
+    });
+  });
+
+  describe("Sponsored Creation", () => {
+    it("Lets a separate payer front the rent for an unfunded authority", async () => {
+      const sponsoredChannelId = "UC_sponsored_test";
+      const sponsoredPoolPda = creatorPoolPdaFor(sponsoredChannelId);
+      const unfundedAuthority = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initializeCreatorPool(sponsoredChannelId, "Sponsored Channel", "ipfs://sponsored", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(sponsoredChannelId),
+          creatorWallet: creatorWallet.publicKey,
+          authority: unfundedAuthority.publicKey,
+          payer: provider.wallet.publicKey,
+        })
+        .signers([unfundedAuthority])
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(sponsoredPoolPda);
+      expect(poolAccount.authority.toString()).to.equal(unfundedAuthority.publicKey.toString());
+      expect(await provider.connection.getBalance(unfundedAuthority.publicKey)).to.equal(0);
+    });
+  });
+
+  describe("Metadata", () => {
+    const metadataChannelId = "UC_metadata_test";
+    const metadataPoolPda = creatorPoolPdaFor(metadataChannelId);
+    const uri = "ipfs://QmSipzyMetadataExample";
+
+    it("Sizes pools to their metadata and resizes on update", async () => {
+      await program.methods
+        .initializeCreatorPool(metadataChannelId, "Metadata Channel", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(metadataChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      const emptySize = (await provider.connection.getAccountInfo(metadataPoolPda)).data.length;
+
+      await program.methods
+        .updateMetadataUri(uri)
+        .accounts({
+          pool: metadataPoolPda,
+          creator: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      let info = await provider.connection.getAccountInfo(metadataPoolPda);
+      expect(info.data.length).to.equal(emptySize + uri.length);
+      expect(info.lamports).to.equal(
+        await provider.connection.getMinimumBalanceForRentExemption(emptySize + uri.length)
+      );
+      let poolAccount = await program.account.pool.fetch(metadataPoolPda);
+      expect(poolAccount.metadataUri).to.equal(uri);
+
+      // Clearing the URI shrinks the account and refunds the rent difference
+      await program.methods
+        .updateMetadataUri("")
+        .accounts({
+          pool: metadataPoolPda,
+          creator: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      info = await provider.connection.getAccountInfo(metadataPoolPda);
+      expect(info.data.length).to.equal(emptySize);
+      poolAccount = await program.account.pool.fetch(metadataPoolPda);
+      expect(poolAccount.metadataUri).to.equal("");
+    });
+
+    it("Validates the URI against its declared format", async () => {
+      const cid = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+      const manage = { pool: metadataPoolPda, creator: creatorWallet.publicKey };
+
+      await program.methods
+        .updateMetadataFormat(1, cid) // IPFS CID
+        .accounts(manage)
+        .signers([creatorWallet])
+        .rpc();
+      const poolAccount = await program.account.pool.fetch(metadataPoolPda);
+      expect(poolAccount.metadataFormat).to.equal(1);
+      expect(poolAccount.metadataUri).to.equal(cid);
+
+      // A full URI is not a bare CID
+      try {
+        await program.methods.updateMetadataUri(uri).accounts(manage).signers([creatorWallet]).rpc();
+        expect.fail("URI not matching the IPFS format should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidMetadataFormat");
+      }
+
+      try {
+        await program.methods
+          .updateMetadataFormat(2, cid) // Arweave TxID
+          .accounts(manage)
+          .signers([creatorWallet])
+          .rpc();
+        expect.fail("CID declared as an Arweave TxID should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidMetadataFormat");
+      }
+    });
+
+    it("Locks the metadata for good once frozen", async () => {
+      const manage = { pool: metadataPoolPda, creator: creatorWallet.publicKey };
+      const frozenUri = (await program.account.pool.fetch(metadataPoolPda)).metadataUri;
+
+      await program.methods.freezeMetadata().accounts(manage).signers([creatorWallet]).rpc();
+      expect((await program.account.pool.fetch(metadataPoolPda)).metadataFrozen).to.be.true;
+
+      try {
+        await program.methods.updateMetadataUri(frozenUri).accounts(manage).signers([creatorWallet]).rpc();
+        expect.fail("frozen metadata should not be updated");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("MetadataFrozen");
+      }
+      expect((await program.account.pool.fetch(metadataPoolPda)).metadataUri).to.equal(frozenUri);
+    });
+  });
+
+  describe("Pool Created Event", () => {
+    it("Includes the pool PDA bump", async () => {
+      const eventChannelId = "UC_event_bump_test";
+      const [eventPoolPda, bump] = PublicKey.findProgramAddressSync(
+        [Buffer.from("creator_pool"), Buffer.from(eventChannelId)],
+        program.programId
+      );
+
+      const tx = await program.methods
+        .initializeCreatorPool(eventChannelId, "Event Channel", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(eventChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const created = [...parser.parseLogs(txInfo.meta.logMessages)].find(
+        (event) => event.name.toLowerCase() === "poolcreated"
+      );
+
+      expect(created.data.pool.toString()).to.equal(eventPoolPda.toString());
+      expect(created.data.bump).to.equal(bump);
+    });
+  });
+
+  describe("Creator Wallet Rotation", () => {
+    const rotationChannelId = "UC_rotation_test";
+    const rotationPoolPda = creatorPoolPdaFor(rotationChannelId);
+    const newCreatorWallet = anchor.web3.Keypair.generate();
+
+    before(async () => {
+      await program.methods
+        .initializeCreatorPool(rotationChannelId, "Rotation Channel", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(rotationChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      const airdropSig = await provider.connection.requestAirdrop(
+        newCreatorWallet.publicKey,
+        LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+    });
+
+    it("Rotates the creator wallet in two steps", async () => {
+      await program.methods
+        .proposeCreatorWallet(newCreatorWallet.publicKey)
+        .accounts({
+          pool: rotationPoolPda,
+          creator: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      await program.methods
+        .acceptCreatorWallet()
+        .accounts({
+          pool: rotationPoolPda,
+          newCreatorWallet: newCreatorWallet.publicKey,
+        })
+        .signers([newCreatorWallet])
+        .rpc();
+
+      const poolAccount = await program.account.pool.fetch(rotationPoolPda);
+      expect(poolAccount.creatorWallet.toString()).to.equal(newCreatorWallet.publicKey.toString());
+      expect(poolAccount.pendingCreatorWallet.toString()).to.equal(PublicKey.default.toString());
+    });
+
+    it("Accrues fees to a PDA creator wallet", async () => {
+      // Stand-in for a DAO treasury vault: an off-curve address no keypair controls
+      const [treasuryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("test_treasury")],
+        program.programId
+      );
+      const treasuryChannelId = "UC_treasury_test";
+      const treasuryPoolPda = creatorPoolPdaFor(treasuryChannelId);
+
+      // Fund the vault to rent exemption so small fee credits are accepted
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: provider.wallet.publicKey,
+            toPubkey: treasuryPda,
+            lamports: LAMPORTS_PER_SOL / 100,
+          })
+        )
+      );
+
+      await program.methods
+        .initializeCreatorPool(treasuryChannelId, "Treasury Channel", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(treasuryChannelId),
+          creatorWallet: treasuryPda,
+        })
+        .rpc();
+
+      const trade = {
+        pool: treasuryPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: treasuryPda,
+        systemProgram: SystemProgram.programId,
+      };
+
+      let before = await provider.connection.getBalance(treasuryPda);
+      await program.methods.buyTokens(new BN(5), new BN(0)).accounts(trade).rpc();
+      let poolAccount = await program.account.pool.fetch(treasuryPoolPda);
+      expect(await provider.connection.getBalance(treasuryPda) - before).to.equal(
+        poolAccount.lifetimeFees.toNumber()
+      );
+
+      const feesBeforeSell = poolAccount.lifetimeFees.toNumber();
+      before = await provider.connection.getBalance(treasuryPda);
+      await program.methods.sellTokens(new BN(2), new BN(0)).accounts(trade).rpc();
+      poolAccount = await program.account.pool.fetch(treasuryPoolPda);
+      expect(await provider.connection.getBalance(treasuryPda) - before).to.equal(
+        poolAccount.lifetimeFees.toNumber() - feesBeforeSell
+      );
+    });
+  });
+
+  describe("Stream Pool (Exponential Curve)", () => {
+    it("Initializes a stream pool", async () => {
+      const tx = await program.methods
+        .initializeStreamPool(
+          videoId,
+          channelId, // Parent channel
+          videoTitle,
+          metadataUri,
+          null, // Use default base price
+          null  // Use default growth rate
+        )
+        .accounts({
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      console.log("Initialize stream pool tx:", tx);
+
+      const poolAccount = await program.account.pool.fetch(streamPoolPda);
+      
+      expect(poolAccount.identifier).to.equal(videoId);
+      expect(poolAccount.displayName).to.equal(videoTitle);
+      expect(poolAccount.parentIdentifier).to.equal(channelId);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(0);
+      // PoolType.Stream = 1
+      expect(poolAccount.poolType).to.deep.equal({ stream: {} });
+    });
+
+    it("Buys tokens from stream pool (exponential)", async () => {
+      const amount = new BN(20);
+      
+      const tx = await program.methods
+        .buyTokens(amount, new BN(0))
+        .accounts({
+          pool: streamPoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("Buy stream tokens tx:", tx);
+
+      const poolAccount = await program.account.pool.fetch(streamPoolPda);
+      
+      expect(poolAccount.totalSupply.toNumber()).to.equal(20);
+      console.log("Stream pool supply:", poolAccount.totalSupply.toNumber());
+      console.log("Stream pool reserve (lamports):", poolAccount.reserveSol.toNumber());
+    });
+
+    it("Gets current price (exponential curve)", async () => {
+      const price = await program.methods
+        .getPrice()
+        .accounts({
+          pool: streamPoolPda,
+        })
+        .view();
+
+      console.log("Stream token price (lamports):", price.toNumber());
+      
+      // Exponential curve: base * 1.05^20 should be significantly higher than base
+      // 1_000_000 * 1.05^20 ≈ 2_653_298
+      expect(price.toNumber()).to.be.greaterThan(2_000_000);
+    });
+
+    it("Returns the live curve parameters in one call", async () => {
+      const curve = await program.methods.getCurve().accounts({ pool: streamPoolPda }).view();
+      const poolAccount = await program.account.pool.fetch(streamPoolPda);
+
+      expect(curve.poolType).to.deep.equal({ stream: {} });
+      expect(curve.basePrice.toNumber()).to.equal(poolAccount.basePrice.toNumber());
+      expect(curve.curveParam.toNumber()).to.equal(500);
+      expect(curve.totalSupply.toNumber()).to.equal(poolAccount.totalSupply.toNumber());
+    });
+
+    it("Finds the supply at which a target price is reached", async () => {
+      const creatorPool = await program.account.pool.fetch(creatorPoolPda);
+      const target = creatorPool.basePrice.add(creatorPool.curveParam.muln(10));
+
+      const exact = await program.methods
+        .getSupplyForPrice(target)
+        .accounts({ pool: creatorPoolPda })
+        .view();
+      const justAbove = await program.methods
+        .getSupplyForPrice(target.addn(1))
+        .accounts({ pool: creatorPoolPda })
+        .view();
+
+      // Linear: price(n) = base + slope × n
+      expect(exact.toNumber()).to.equal(10);
+      expect(justAbove.toNumber()).to.equal(11);
+    });
+
+    it("Reports the one-token round-trip spread", async () => {
+      const spread = await program.methods.getSpread().accounts({ pool: streamPoolPda }).view();
+      const buySim = await program.methods
+        .simulateTrade({ buy: {} }, new BN(1))
+        .accounts({ pool: streamPoolPda })
+        .view();
+
+      // Both fees plus one token of curve slope, but far less than the token itself
+      expect(spread.toNumber()).to.be.greaterThan(0);
+      expect(spread.toNumber()).to.be.lessThan(buySim.solAmount.toNumber());
+    });
+
+    it("Quotes the next token's ask and bid", async () => {
+      const ask = await program.methods.getNextBuyPrice().accounts({ pool: streamPoolPda }).view();
+      const bid = await program.methods.getNextSellPrice().accounts({ pool: streamPoolPda }).view();
+      const buySim = await program.methods
+        .simulateTrade({ buy: {} }, new BN(1))
+        .accounts({ pool: streamPoolPda })
+        .view();
+      const sellSim = await program.methods
+        .simulateTrade({ sell: {} }, new BN(1))
+        .accounts({ pool: streamPoolPda })
+        .view();
+
+      expect(ask.toString()).to.equal(buySim.netSol.toString());
+      expect(bid.toString()).to.equal(sellSim.netSol.toString());
+      expect(ask.gt(bid)).to.equal(true);
+    });
+
+    it("Gets buy cost for stream tokens", async () => {
+      const amount = new BN(10);
+      
+      const cost = await program.methods
+        .getBuyCost(amount)
+        .accounts({
+          pool: streamPoolPda,
+        })
+        .view();
+
+      console.log("Cost to buy 10 more stream tokens (lamports):", cost.toNumber());
+      
+      // Should be non-zero and include fee
+      expect(cost.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("Inherits the fee from the parent Creator pool", async () => {
+      const parentChannelId = "UC_fee_parent";
+      const parentPoolPda = creatorPoolPdaFor(parentChannelId);
+      const childVideoId = "fee_child_vid";
+      const [childPoolPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stream_pool"), Buffer.from(childVideoId)],
+        program.programId
+      );
+
+      await program.methods
+        .initializeCreatorPool(parentChannelId, "Fee Parent", "", null, null, 0)
+        .accounts({
+          pool: creatorPoolPdaFor(parentChannelId),
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setFeeBps(new BN(250))
+        .accounts({ pool: parentPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      await program.methods
+        .initializeStreamPool(childVideoId, parentChannelId, "Fee Child", "", null, null)
+        .accounts({
+          creatorWallet: creatorWallet.publicKey,
+          parentPool: parentPoolPda,
+        })
+        .rpc();
+
+      const child = await program.account.pool.fetch(childPoolPda);
+      expect(child.feeBps.toNumber()).to.equal(250);
+
+      try {
+        await program.methods
+          .setFeeBps(new BN(1001))
+          .accounts({ pool: childPoolPda, creator: creatorWallet.publicKey })
+          .signers([creatorWallet])
+          .rpc();
+        expect.fail("fee above the maximum should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidFee");
+      }
+
+      // The fee can be lowered at any time, but never raised back
+      const parentManage = { pool: parentPoolPda, creator: creatorWallet.publicKey };
+      await program.methods.decreaseFee(new BN(200)).accounts(parentManage).signers([creatorWallet]).rpc();
+      expect((await program.account.pool.fetch(parentPoolPda)).feeBps.toNumber()).to.equal(200);
+      try {
+        await program.methods.decreaseFee(new BN(250)).accounts(parentManage).signers([creatorWallet]).rpc();
+        expect.fail("raising the fee should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("FeeNotDecreased");
+      }
+
+      // Nor by set_fee_bps once the supply is back to zero
+      expect((await program.account.pool.fetch(parentPoolPda)).totalSupply.toNumber()).to.equal(0);
+      try {
+        await program.methods.setFeeBps(new BN(250)).accounts(parentManage).signers([creatorWallet]).rpc();
+        expect.fail("set_fee_bps above the lowered ceiling should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("FeeAboveCeiling");
+      }
+    });
+
+    it("Stops buys but allows sells once a stream pool expires", async () => {
+      const expiringVideoId = "expiring_vid";
+      const [expiringPoolPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stream_pool"), Buffer.from(expiringVideoId)],
+        program.programId
+      );
+      const tradeAccounts = {
+        pool: expiringPoolPda,
+        trader: provider.wallet.publicKey,
+        creatorWallet: creatorWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      };
+
+      await program.methods
+        .initializeStreamPool(expiringVideoId, channelId, "Expiring Stream", "", null, null)
+        .accounts({
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setExpiry(new BN(Math.floor(Date.now() / 1000) + 3))
+        .accounts({ pool: expiringPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+      await program.methods.buyTokens(new BN(2), new BN(0)).accounts(tradeAccounts).rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 5000));
+
+      try {
+        await program.methods.buyTokens(new BN(1), new BN(0)).accounts(tradeAccounts).rpc();
+        expect.fail("buy after expiry should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("PoolExpired");
+      }
+
+      await program.methods.sellTokens(new BN(2), new BN(0)).accounts(tradeAccounts).rpc();
+      const poolAccount = await program.account.pool.fetch(expiringPoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(0);
+    });
+
+    it("Rejects a stream pool with zero growth", async () => {
+      try {
+        await program.methods
+          .initializeStreamPool("flat_curve_vid", channelId, "Flat Curve", "", null, new BN(0))
+          .accounts({
+            creatorWallet: creatorWallet.publicKey,
+          })
+          .rpc();
+        expect.fail("zero growth rate should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("CurveParamOutOfRange");
+      }
+    });
+
+    it("Rejects curve params shaped for the other pool type", async () => {
+      // The default Creator slope (lamports) passed as a Stream growth rate
+      try {
+        await program.methods
+          .initializeStreamPool("slope_as_growth_vid", channelId, "Slope As Growth", "", null, new BN(100_000))
+          .accounts({
+            creatorWallet: creatorWallet.publicKey,
+          })
+          .rpc();
+        expect.fail("a slope-sized growth rate should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("CurveParamOutOfRange");
+      }
+
+      // A 5000 bps growth rate passed as a Creator slope
+      const mixedChannelId = "UC_growth_as_slope";
+      try {
+        await program.methods
+          .initializeCreatorPool(mixedChannelId, "Growth As Slope", "", null, new BN(5000), 0)
+          .accounts({
+            pool: creatorPoolPdaFor(mixedChannelId),
+            creatorWallet: creatorWallet.publicKey,
+          })
+          .rpc();
+        expect.fail("a bps-sized slope should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("CurveParamOutOfRange");
+      }
+    });
+
+    it("Opts a low-growth pool into finer exponential precision", async () => {
+      const fineVideoId = "fine_precision_vid";
+      const [finePoolPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stream_pool"), Buffer.from(fineVideoId)],
+        program.programId
+      );
+      const manageAccounts = { pool: finePoolPda, creator: creatorWallet.publicKey };
+
+      // 10 bps growth
+      await program.methods
+        .initializeStreamPool(fineVideoId, channelId, "Fine Precision", "", null, new BN(10))
+        .accounts({
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      try {
+        await program.methods.setExpPrecision(10).accounts(manageAccounts).signers([creatorWallet]).rpc();
+        expect.fail("unsupported precision should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidExpPrecision");
+      }
+
+      await program.methods.setExpPrecision(15).accounts(manageAccounts).signers([creatorWallet]).rpc();
+      const poolAccount = await program.account.pool.fetch(finePoolPda);
+      expect(poolAccount.precisionExp).to.equal(15);
+
+      const cost = await program.methods
+        .getBuyCost(new BN(50))
+        .accounts({ pool: finePoolPda })
+        .view();
+      expect(cost.toNumber()).to.be.greaterThan(50 * poolAccount.basePrice.toNumber());
+    });
+
+    it("Prices multi-chunk buys at every supported precision", async () => {
+      const reserves: number[] = [];
+      for (const precisionExp of [9, 12, 15]) {
+        const chunkVideoId = `chunk_precision_${precisionExp}_vid`;
+        const [chunkPoolPda] = PublicKey.findProgramAddressSync(
+          [Buffer.from("stream_pool"), Buffer.from(chunkVideoId)],
+          program.programId
+        );
+
+        // 400 bps growth from the default base price
+        await program.methods
+          .initializeStreamPool(chunkVideoId, channelId, "Chunk Precision", "", null, new BN(400))
+          .accounts({
+            creatorWallet: creatorWallet.publicKey,
+          })
+          .rpc();
+        await program.methods
+          .setExpPrecision(precisionExp)
+          .accounts({ pool: chunkPoolPda, creator: creatorWallet.publicKey })
+          .signers([creatorWallet])
+          .rpc();
+
+        // Past MAX_SUMMATION_TERMS, so priced in 32-token chunks
+        await program.methods
+          .buyTokens(new BN(101), new BN(0))
+          .accounts({
+            pool: chunkPoolPda,
+            trader: provider.wallet.publicKey,
+            creatorWallet: creatorWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+        const poolAccount = await program.account.pool.fetch(chunkPoolPda);
+        expect(poolAccount.totalSupply.toNumber()).to.equal(101);
+        reserves.push(poolAccount.reserveSol.toNumber());
+      }
+
+      // Finer precision only moves the last few lamports
+      for (const reserve of reserves) {
+        expect(Math.abs(reserve / reserves[0] - 1)).to.be.lessThan(1e-6);
+      }
+    });
+
+    it("Lists a channel's stream pools from the creator registry", async () => {
+      const listedChannelId = "UC_channel_streams";
+      const listedVideoId = "channel_streams_vid";
+      const [listedPoolPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stream_pool"), Buffer.from(listedVideoId)],
+        program.programId
+      );
+      const [registryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("registry"), creatorWallet.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializeStreamPool(listedVideoId, listedChannelId, "Listed Stream", "", null, null)
+        .accounts({
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      const streams = await program.methods
+        .getChannelStreams(listedChannelId)
+        .accounts({ registry: registryPda })
+        .view();
+      expect(streams.map((pool) => pool.toBase58())).to.deep.equal([listedPoolPda.toBase58()]);
+
+      const otherChannel = await program.methods
+        .getChannelStreams("UC_no_streams_here")
+        .accounts({ registry: registryPda })
+        .view();
+      expect(otherChannel).to.have.lengthOf(0);
+    });
+
+    it("Flattens the curve at the price ceiling", async () => {
+      const cappedVideoId = "price_ceiling_vid";
+      const [cappedPoolPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stream_pool"), Buffer.from(cappedVideoId)],
+        program.programId
+      );
+
+      await program.methods
+        .initializeStreamPool(cappedVideoId, channelId, "Capped Stream", "", null, null)
+        .accounts({
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      const basePrice = (await program.account.pool.fetch(cappedPoolPda)).basePrice;
+      const ceiling = basePrice.muln(2);
+      await program.methods
+        .setPriceCeiling(ceiling)
+        .accounts({ pool: cappedPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      // Far past where the uncapped price would overflow u64
+      const farPrice = await program.methods
+        .getPriceAt(new BN("1000000000000"))
+        .accounts({ pool: cappedPoolPda })
+        .view();
+      expect(farPrice.toString()).to.equal(ceiling.toString());
+
+      const startPrice = await program.methods
+        .getPriceAt(new BN(0))
+        .accounts({ pool: cappedPoolPda })
+        .view();
+      expect(startPrice.toString()).to.equal(basePrice.toString());
+    });
+
+    it("Saturates market cap instead of overflowing u64", async () => {
+      const hotVideoId = "hot_market_cap_vid";
+      const [hotPoolPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stream_pool"), Buffer.from(hotVideoId)],
+        program.programId
+      );
+
+      // Doubling from 3e18, so price(2) = 1.2e19 still fits u64 but price × supply does not
+      await program.methods
+        .initializeStreamPool(hotVideoId, channelId, "Hot Stream", "", new BN("3000000000000000000"), new BN(10_000))
+        .accounts({
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .mintFounderAllocation(new BN(2))
+        .accounts({
+          pool: hotPoolPda,
+          creator: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      const cap = await program.methods.getMarketCap().accounts({ pool: hotPoolPda }).view();
+      expect(cap.saturated).to.equal(true);
+      expect(cap.marketCap.toString()).to.equal("18446744073709551615");
+      // Uncapped pools have no fully diluted supply
+      expect(cap.fullyDilutedValuation.toNumber()).to.equal(0);
+
+      const plain = await program.methods.getMarketCap().accounts({ pool: creatorPoolPda }).view();
+      expect(plain.saturated).to.equal(false);
+    });
+
+    it("Rejects steep buys and saturates the price view past u64", async () => {
+      const steepVideoId = "steep_curve_vid";
+      const [steepPoolPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stream_pool"), Buffer.from(steepVideoId)],
+        program.programId
+      );
+
+      const peakVideoId = "peak_curve_vid";
+      const [peakPoolPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stream_pool"), Buffer.from(peakVideoId)],
+        program.programId
+      );
+
+      // 100% growth, the steepest a Stream pool accepts
+      await program.methods
+        .initializeStreamPool(steepVideoId, channelId, "Steep Curve", "", new BN(1), new BN(10_000))
+        .accounts({
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      // 7 tokens would move the price 128x, past MAX_PRICE_MULTIPLE
+      try {
+        await program.methods
+          .buyTokens(new BN(7), new BN(0))
+          .accounts({
+            pool: steepPoolPda,
+            trader: provider.wallet.publicKey,
+            creatorWallet: creatorWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("buy should exceed the price move limit");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("PriceMoveTooLarge");
+      }
+
+      // Doubling from 5e18, so price(2) = 2e19 lamports; the founder allocation
+      // doesn't go through the buy guard
+      await program.methods
+        .initializeStreamPool(peakVideoId, channelId, "Peak Curve", "", new BN("5000000000000000000"), new BN(10_000))
+        .accounts({
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .mintFounderAllocation(new BN(2))
+        .accounts({ pool: peakPoolPda, creator: creatorWallet.publicKey })
+        .signers([creatorWallet])
+        .rpc();
+
+      try {
+        await program.methods.getPrice().accounts({ pool: peakPoolPda }).view();
+        expect.fail("strict price should overflow");
+      } catch (err) {
+        expect(err.toString()).to.include("Overflow");
+      }
+
+      const price = await program.methods.getPriceSaturating().accounts({ pool: peakPoolPda }).view();
+      expect(price.eq(new BN("18446744073709551615"))).to.equal(true);
+    });
+
+    it("Prices a 10,000-token buy in chunks and reports compute units", async () => {
+      const largeVideoId = "large_buy_vid";
+      const [largePoolPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stream_pool"), Buffer.from(largeVideoId)],
+        program.programId
+      );
+      const basePrice = 10_000;
+      const growthBps = 1;
+
+      await program.methods
+        .initializeStreamPool(largeVideoId, channelId, "Large Buy", "", new BN(basePrice), new BN(growthBps))
+        .accounts({
+          creatorWallet: creatorWallet.publicKey,
+        })
+        .rpc();
+
+      const tx = await program.methods
+        .buyTokens(new BN(10_000), new BN(0))
+        .accounts({
+          pool: largePoolPda,
+          trader: provider.wallet.publicKey,
+          creatorWallet: creatorWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([
+          anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 }),
+        ])
+        .rpc({ commitment: "confirmed" });
+
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      // The chunked closed form keeps even a 10,000-token buy well inside one transaction
+      expect(txInfo.meta.computeUnitsConsumed).to.be.lessThan(1_400_000);
+
+      // Closed-form geometric sum, computed in floating point as a reference
+      const r = 1 + growthBps / 10_000;
+      const expected = (basePrice * (Math.pow(r, 10_000) - 1)) / (r - 1);
+      const poolAccount = await program.account.pool.fetch(largePoolPda);
+      expect(poolAccount.totalSupply.toNumber()).to.equal(10_000);
+      // The creator fee comes out of the curve cost and never reaches the reserve
+      const expectedReserve = expected * (1 - poolAccount.feeBps.toNumber() / 10_000);
+      expect(Math.abs(poolAccount.reserveSol.toNumber() / expectedReserve - 1)).to.be.lessThan(1e-5);
     });
   });
 });